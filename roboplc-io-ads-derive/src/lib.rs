@@ -0,0 +1,330 @@
+extern crate proc_macro;
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{parse_macro_input, Data, DeriveInput, Field, Fields, LitInt, LitStr};
+
+/// Automatically implements the `PlcLayout` trait for a structure with named fields
+///
+/// Fields are laid out sequentially, the same way `binrw` serializes them, so the offset of each
+/// field is the sum of the sizes of all fields before it. All field types must implement
+/// `PlcLayout` as well, fields of nested structures are compared with the PLC types recursively.
+///
+/// Field attribute arguments:
+///
+/// * `name` - Specifies the field name in the PLC type. If not specified, the Rust field name is
+///   used. Names are compared case-insensitively, same as IEC 61131-3 identifiers
+///
+/// * `offset` - Specifies the explicit offset of the field, in bytes. Following fields are placed
+///   after this one
+///
+/// * `skip` - The field is not a part of the PLC type (e.g. explicit padding). The field still
+///   occupies its size in the layout but is not compared
+///
+/// Structure attribute arguments:
+///
+/// * `size` - Specifies the total size of the structure, in bytes (e.g. if the PLC type has got
+///   trailing padding). If not specified, the end of the last field is used
+///
+/// Example:
+///
+/// ```rust,ignore
+/// use roboplc_io_ads::layout::PlcLayout;
+///
+/// #[derive(PlcLayout)]
+/// #[plc(size = 16)]
+/// struct Drive {
+///     #[plc(name = "fSpeed")]
+///     speed: f64,
+///     #[plc(name = "nState")]
+///     state: u16,
+///     #[plc(name = "bEnabled", offset = 12)]
+///     enabled: bool,
+/// }
+/// ```
+#[proc_macro_derive(PlcLayout, attributes(plc))]
+pub fn plc_layout_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    plc_layout_impl(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn plc_layout_impl(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut total_size: Option<usize> = None;
+    for attr in &input.attrs {
+        if !attr.path().is_ident("plc") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("size") {
+                let lit: LitInt = meta.value()?.parse()?;
+                total_size = Some(lit.base10_parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported plc attribute"))
+            }
+        })?;
+    }
+
+    let fields = named_fields(&input, "PlcLayout")?;
+
+    let mut field_entries = Vec::new();
+    for field in fields {
+        let ident = field
+            .ident
+            .as_ref()
+            .ok_or_else(|| syn::Error::new_spanned(field, "named field expected"))?;
+        let ty = &field.ty;
+        let mut plc_name = ident.to_string();
+        let mut offset: Option<usize> = None;
+        let mut skip = false;
+        for attr in &field.attrs {
+            if !attr.path().is_ident("plc") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    plc_name = lit.value();
+                    Ok(())
+                } else if meta.path.is_ident("offset") {
+                    let lit: LitInt = meta.value()?.parse()?;
+                    offset = Some(lit.base10_parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported plc attribute"))
+                }
+            })?;
+        }
+        let set_offset = offset.map(|o| quote! { offset = #o; });
+        let push = if skip {
+            None
+        } else {
+            Some(quote! {
+                fields.push(::roboplc_io_ads::layout::LayoutField {
+                    name: #plc_name,
+                    offset,
+                    size,
+                    fields: <#ty as ::roboplc_io_ads::layout::PlcLayout>::plc_fields(),
+                });
+            })
+        };
+        field_entries.push(quote! {
+            #set_offset
+            let size = <#ty as ::roboplc_io_ads::layout::PlcLayout>::plc_size();
+            #push
+            offset += size;
+            end = end.max(offset);
+        });
+    }
+
+    let layout = quote! {
+        let mut fields = ::std::vec::Vec::new();
+        let mut offset = 0usize;
+        let mut end = 0usize;
+        #(#field_entries)*
+    };
+    let size_expr = if let Some(size) = total_size {
+        quote! { #size }
+    } else {
+        quote! {
+            #layout
+            end
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::roboplc_io_ads::layout::PlcLayout for #name #ty_generics #where_clause {
+            #[allow(unused_assignments, unused_mut, unused_variables)]
+            fn plc_size() -> usize {
+                #size_expr
+            }
+
+            #[allow(unused_assignments, unused_mut, unused_variables)]
+            fn plc_fields() -> ::std::vec::Vec<::roboplc_io_ads::layout::LayoutField> {
+                #layout
+                fields
+            }
+        }
+    })
+}
+
+/// Return the named fields of a structure, `derive` is the name of the macro for error messages.
+fn named_fields<'a>(
+    input: &'a DeriveInput,
+    derive: &str,
+) -> syn::Result<&'a Punctuated<Field, Comma>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            format!("{} can be derived for structures only", derive),
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &data.fields,
+            format!(
+                "{} can be derived for structures with named fields only",
+                derive
+            ),
+        ));
+    };
+    Ok(&fields.named)
+}
+
+/// Automatically implements the `AdsVars` trait for a structure with named fields, binding each
+/// field to a PLC symbol
+///
+/// The structure type is bound to a device with `AdsVars::bind`, all fields are read or written
+/// with a single sum-up request by the `read_all`/`write_all` trait methods. Field types must
+/// implement `binrw` reading and writing, as well as `PlcLayout` (unless the size is specified).
+///
+/// Field attribute arguments:
+///
+/// * `symbol` - Specifies the PLC symbol of the field (required unless the field is skipped)
+///
+/// * `size` - Specifies the buffer size of the field, in bytes. If not specified, the
+///   `PlcLayout` size of the field type is used
+///
+/// * `skip` - The field is not bound to a symbol and is never read or written
+///
+/// Example:
+///
+/// ```rust,no_run
+/// use roboplc_io_ads::strings::PlcString;
+/// use roboplc_io_ads::vars::AdsVars;
+///
+/// #[derive(AdsVars)]
+/// struct Motor {
+///     #[ads(symbol = "MAIN.fSpeed")]
+///     speed: f64,
+///     #[ads(symbol = "MAIN.nState")]
+///     state: u16,
+///     #[ads(symbol = "MAIN.sName", size = 81)]
+///     name: PlcString,
+/// }
+///
+/// # fn example(device: &roboplc_io_ads::Device) -> roboplc::Result<()> {
+/// let mut image = Motor::bind(device)?;
+/// let mut motor = Motor {
+///     speed: 0.0,
+///     state: 0,
+///     name: PlcString::with_capacity(80),
+/// };
+/// motor.read_all(&mut image)?;
+/// # Ok(())
+/// # }
+/// ```
+#[proc_macro_derive(AdsVars, attributes(ads))]
+pub fn ads_vars_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    ads_vars_impl(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn ads_vars_impl(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "AdsVars can not be derived for generic structures",
+        ));
+    }
+    let fields = named_fields(&input, "AdsVars")?;
+
+    let mut symbols = Vec::new();
+    let mut getters = Vec::new();
+    let mut setters = Vec::new();
+    for field in fields {
+        let ident = field
+            .ident
+            .as_ref()
+            .ok_or_else(|| syn::Error::new_spanned(field, "named field expected"))?;
+        let ty = &field.ty;
+        let mut symbol: Option<String> = None;
+        let mut size: Option<usize> = None;
+        let mut skip = false;
+        for attr in &field.attrs {
+            if !attr.path().is_ident("ads") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("symbol") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    symbol = Some(lit.value());
+                    Ok(())
+                } else if meta.path.is_ident("size") {
+                    let lit: LitInt = meta.value()?.parse()?;
+                    size = Some(lit.base10_parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported ads attribute"))
+                }
+            })?;
+        }
+        if skip {
+            continue;
+        }
+        let Some(symbol) = symbol else {
+            return Err(syn::Error::new_spanned(
+                field,
+                format!(
+                    "symbol is not specified for {}, use #[ads(symbol = \"...\")] or #[ads(skip)]",
+                    ident
+                ),
+            ));
+        };
+        let index = symbols.len();
+        let size_expr = if let Some(size) = size {
+            quote! { #size }
+        } else {
+            quote! { <#ty as ::roboplc_io_ads::layout::PlcLayout>::plc_size() }
+        };
+        symbols.push(quote! { (#symbol, #size_expr) });
+        getters.push(quote! {
+            if let Some(value) = ::roboplc_io_ads::vars::get_field::<#ty>(image, #index, errors) {
+                self.#ident = value;
+            }
+        });
+        setters.push(quote! {
+            image.set(#index, &self.#ident)?;
+        });
+    }
+
+    Ok(quote! {
+        impl ::roboplc_io_ads::vars::AdsVars for #name {
+            fn ads_fields() -> ::std::vec::Vec<(&'static str, usize)> {
+                ::std::vec![#(#symbols),*]
+            }
+
+            fn ads_get(
+                &mut self,
+                image: &::roboplc_io_ads::AdsProcessImage,
+                errors: &mut ::std::vec::Vec<::std::string::String>,
+            ) {
+                #(#getters)*
+            }
+
+            fn ads_set(
+                &self,
+                image: &mut ::roboplc_io_ads::AdsProcessImage,
+            ) -> ::roboplc::Result<()> {
+                #(#setters)*
+                Ok(())
+            }
+        }
+    })
+}
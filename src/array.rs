@@ -0,0 +1,299 @@
+//! PLC arrays whose length is known at runtime only.
+//!
+//! [`PlcArray`] is a typed array which can be read with a [`Handle`] or with
+//! [`crate::AdsMapping`] (the whole data returned by the PLC is decoded). [`ArraySymbol`] is sized
+//! from the symbol info and reads or writes ranges of elements of large arrays, without
+//! transferring the whole symbol:
+//!
+//! ```rust,no_run
+//! use roboplc_io_ads::array::ArraySymbol;
+//! # fn example(device: &roboplc_io_ads::Device) -> roboplc::Result<()> {
+//! let values = ArraySymbol::<f32>::new(device, "GVL_Log.afSamples")?;
+//! // the last 10 samples
+//! let tail = values.read_range(values.len().saturating_sub(10)..)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Element sizes are taken from [`PlcLayout`], so elements are decoded correctly even if the
+//! Rust type is smaller than the PLC one (e.g. a structure with trailing padding).
+
+use std::io::Cursor;
+use std::marker::PhantomData;
+use std::ops::{Bound, Deref, DerefMut, RangeBounds};
+
+use roboplc::io::binrw::{BinRead, BinResult, BinWrite, Endian};
+use roboplc::{Error, Result};
+
+use crate::layout::PlcLayout;
+use crate::symbol::{self, Symbol};
+use crate::{Device, Handle};
+
+/// A typed PLC array with the length known at runtime.
+///
+/// Reading with binrw consumes all the data available, so the array can be used as the last field
+/// of a binrw structure only.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlcArray<T>(Vec<T>);
+
+impl<T> PlcArray<T> {
+    /// Create a new empty array.
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Convert into the elements vector.
+    pub fn into_vec(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> PlcArray<T>
+where
+    T: PlcLayout + for<'a> BinRead<Args<'a> = ()>,
+{
+    /// Read `len` elements using a symbol handle.
+    pub fn read(handle: &Handle, len: usize) -> Result<Self> {
+        let mut buf = vec![0; len * T::plc_size()];
+        handle.read(&mut buf)?;
+        Self::decode(&buf)
+    }
+
+    /// Decode elements from raw data. The data length must be a multiple of the element size.
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let element_size = T::plc_size();
+        if element_size == 0 || data.len() % element_size != 0 {
+            return Err(Error::invalid_data(format!(
+                "invalid array data length: {} (element size {})",
+                data.len(),
+                element_size
+            )));
+        }
+        data.chunks(element_size)
+            .map(|chunk| T::read_le(&mut Cursor::new(chunk)).map_err(Into::into))
+            .collect::<Result<Vec<_>>>()
+            .map(Self)
+    }
+}
+
+impl<T> PlcArray<T>
+where
+    T: PlcLayout + for<'a> BinWrite<Args<'a> = ()>,
+{
+    /// Write all elements using a symbol handle.
+    pub fn write(&self, handle: &Handle) -> Result<()> {
+        handle.write(&self.encode()?)
+    }
+
+    /// Encode elements into raw data, each element is padded to the element size.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        encode_elements(&self.0)
+    }
+}
+
+impl<T> Deref for PlcArray<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for PlcArray<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> From<Vec<T>> for PlcArray<T> {
+    fn from(v: Vec<T>) -> Self {
+        Self(v)
+    }
+}
+
+impl<T> BinRead for PlcArray<T>
+where
+    T: PlcLayout + for<'a> BinRead<Args<'a> = ()>,
+{
+    type Args<'a> = ();
+
+    fn read_options<R: std::io::Read + std::io::Seek>(
+        reader: &mut R,
+        _endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let pos = reader.stream_position()?;
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Self::decode(&data).map_err(|e| roboplc::io::binrw::Error::AssertFail {
+            pos,
+            message: e.to_string(),
+        })
+    }
+}
+
+impl<T> BinWrite for PlcArray<T>
+where
+    T: PlcLayout + for<'a> BinWrite<Args<'a> = ()>,
+{
+    type Args<'a> = ();
+
+    fn write_options<W: std::io::Write + std::io::Seek>(
+        &self,
+        writer: &mut W,
+        _endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<()> {
+        let pos = writer.stream_position()?;
+        let data = self
+            .encode()
+            .map_err(|e| roboplc::io::binrw::Error::AssertFail {
+                pos,
+                message: e.to_string(),
+            })?;
+        writer.write_all(&data)?;
+        Ok(())
+    }
+}
+
+/// An array symbol, sized from the symbol info. Elements are accessed by their zero-based
+/// positions, regardless of the array bounds in the PLC. Multi-dimensional arrays are accessed
+/// flattened, in memory order.
+#[allow(clippy::module_name_repetitions)]
+pub struct ArraySymbol<T> {
+    device: Device,
+    ix_group: u32,
+    ix_offset: u32,
+    len: usize,
+    _element: PhantomData<fn() -> T>,
+}
+
+impl<T: PlcLayout> ArraySymbol<T> {
+    /// Create a new array symbol. Returns an error if the symbol size is not a multiple of the
+    /// element size.
+    pub fn new(device: &Device, symbol: &str) -> Result<Self> {
+        Self::from_symbol(device, &symbol::get_info(device, symbol)?)
+    }
+
+    /// Create a new array symbol from the symbol info (e.g. from an uploaded symbol table).
+    pub fn from_symbol(device: &Device, symbol: &Symbol) -> Result<Self> {
+        let element_size = T::plc_size();
+        if element_size == 0 || symbol.size % element_size != 0 {
+            return Err(Error::invalid_data(format!(
+                "{}: symbol size {} is not a multiple of the element size {}",
+                symbol.name, symbol.size, element_size
+            )));
+        }
+        Ok(Self {
+            device: device.clone(),
+            ix_group: symbol.ix_group,
+            ix_offset: symbol.ix_offset,
+            len: symbol.size / element_size,
+            _element: PhantomData,
+        })
+    }
+
+    /// Number of elements.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Returns the index offset of an element.
+    fn element_offset(&self, index: usize) -> Result<u32> {
+        index
+            .checked_mul(T::plc_size())
+            .and_then(|offset| u32::try_from(offset).ok())
+            .and_then(|offset| self.ix_offset.checked_add(offset))
+            .ok_or_else(|| Error::invalid_data("array offset overflow"))
+    }
+}
+
+impl<T> ArraySymbol<T>
+where
+    T: PlcLayout + for<'a> BinRead<Args<'a> = ()>,
+{
+    /// Read all elements.
+    pub fn read(&self) -> Result<PlcArray<T>> {
+        self.read_range(..)
+    }
+
+    /// Read a range of elements (e.g. `10..20`, `..5`, `100..`).
+    pub fn read_range(&self, range: impl RangeBounds<usize>) -> Result<PlcArray<T>> {
+        let start = match range.start_bound() {
+            Bound::Included(v) => *v,
+            Bound::Excluded(v) => v.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(v) => v.saturating_add(1),
+            Bound::Excluded(v) => *v,
+            Bound::Unbounded => self.len,
+        };
+        if start > end || end > self.len {
+            return Err(Error::invalid_data(format!(
+                "array range {}..{} out of bounds (length {})",
+                start, end, self.len
+            )));
+        }
+        let mut buf = vec![0; (end - start) * T::plc_size()];
+        if !buf.is_empty() {
+            self.device
+                .read_exact(self.ix_group, self.element_offset(start)?, &mut buf)?;
+        }
+        PlcArray::decode(&buf)
+    }
+}
+
+impl<T> ArraySymbol<T>
+where
+    T: PlcLayout + for<'a> BinWrite<Args<'a> = ()>,
+{
+    /// Write elements, starting at the given position.
+    pub fn write_range(&self, start: usize, values: &[T]) -> Result<()> {
+        if start
+            .checked_add(values.len())
+            .map_or(true, |end| end > self.len)
+        {
+            return Err(Error::invalid_data(format!(
+                "array range {}..{} out of bounds (length {})",
+                start,
+                start.saturating_add(values.len()),
+                self.len
+            )));
+        }
+        if values.is_empty() {
+            return Ok(());
+        }
+        self.device.write(
+            self.ix_group,
+            self.element_offset(start)?,
+            &encode_elements(values)?,
+        )
+    }
+}
+
+fn encode_elements<T>(values: &[T]) -> Result<Vec<u8>>
+where
+    T: PlcLayout + for<'a> BinWrite<Args<'a> = ()>,
+{
+    let element_size = T::plc_size();
+    let mut c = Cursor::new(Vec::with_capacity(values.len() * element_size));
+    for (i, value) in values.iter().enumerate() {
+        value.write_le(&mut c)?;
+        let end = (i + 1) * element_size;
+        if c.get_ref().len() > end {
+            return Err(Error::invalid_data(format!(
+                "array element is larger than the element size {}",
+                element_size
+            )));
+        }
+        c.get_mut().resize(end, 0);
+        c.set_position(end as u64);
+    }
+    Ok(c.into_inner())
+}
@@ -0,0 +1,201 @@
+//! Bit-addressed access to BOOL and BIT variables.
+//!
+//! Bits can be addressed either with IEC 61131-3 direct addresses (`%MX12.3`, `%IX0.1`,
+//! `%QX2.7`) or with symbol names. Direct addresses are mapped to the bit index groups
+//! ([`crate::index::PLC_RW_MX`], [`crate::index::IO_RW_IX`], [`crate::index::IO_RW_QX`]) with
+//! the index offset `byte * 8 + bit`. Symbols which are marked as bit values (e.g. `BIT` members
+//! packed inside a structure, see [`crate::symbol::SYMBOL_FLAG_BIT_VALUE`]) are accessed the same
+//! way, other symbols (`BOOL` and `ARRAY OF BOOL`) are accessed as bytes.
+
+use std::fmt;
+use std::str::FromStr;
+
+use roboplc::{Error, Result};
+
+use crate::client::{ReadRequest, WriteRequest, MAX_SUMUP_REQUESTS};
+use crate::index;
+use crate::symbol::{self, SYMBOL_FLAG_BIT_VALUE};
+use crate::Device;
+
+/// A bit address: a bit index group and a bit offset.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BitAddress {
+    /// Bit index group (e.g. [`crate::index::PLC_RW_MX`]).
+    pub index_group: u32,
+    /// Bit offset (`byte * 8 + bit`).
+    pub bit_offset: u32,
+}
+
+impl BitAddress {
+    /// Create a new bit address from the bit offset.
+    pub fn new(index_group: u32, bit_offset: u32) -> Self {
+        Self {
+            index_group,
+            bit_offset,
+        }
+    }
+
+    /// Create a new bit address from the byte offset and the bit number (0-7).
+    pub fn from_byte(index_group: u32, byte: u32, bit: u8) -> Result<Self> {
+        if bit > 7 {
+            return Err(Error::invalid_data(format!("invalid bit number: {}", bit)));
+        }
+        let bit_offset = byte
+            .checked_mul(8)
+            .and_then(|offset| offset.checked_add(u32::from(bit)))
+            .ok_or_else(|| Error::invalid_data("bit offset overflow"))?;
+        Ok(Self::new(index_group, bit_offset))
+    }
+
+    /// Byte offset of the bit.
+    pub fn byte(&self) -> u32 {
+        self.bit_offset / 8
+    }
+
+    /// Bit number (0-7) within the byte.
+    pub fn bit(&self) -> u8 {
+        (self.bit_offset % 8) as u8
+    }
+}
+
+impl FromStr for BitAddress {
+    type Err = Error;
+
+    /// Parse an IEC 61131-3 direct address: `%MX12.3`, `%IX0.1` or `%QX2.7` (the percent sign
+    /// is optional).
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::invalid_data(format!("invalid bit address: {}", s));
+        let addr = s.strip_prefix('%').unwrap_or(s);
+        let (area, rest) = addr.get(..2).zip(addr.get(2..)).ok_or_else(invalid)?;
+        let index_group = match area.to_ascii_uppercase().as_str() {
+            "MX" => index::PLC_RW_MX,
+            "IX" => index::IO_RW_IX,
+            "QX" => index::IO_RW_QX,
+            _ => return Err(invalid()),
+        };
+        let (byte, bit) = rest.split_once('.').ok_or_else(invalid)?;
+        let byte = byte.parse().map_err(|_| invalid())?;
+        let bit = bit.parse().map_err(|_| invalid())?;
+        Self::from_byte(index_group, byte, bit)
+    }
+}
+
+impl fmt::Display for BitAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let area = match self.index_group {
+            index::PLC_RW_MX => "MX",
+            index::IO_RW_IX => "IX",
+            index::IO_RW_QX => "QX",
+            _ => return write!(f, "0x{:x}:{}", self.index_group, self.bit_offset),
+        };
+        write!(f, "%{}{}.{}", area, self.byte(), self.bit())
+    }
+}
+
+// Resolved location of bits.
+enum Location {
+    // Bit-addressed, one bit per index offset
+    Bits(BitAddress),
+    // Byte-addressed BOOL values
+    Bytes { index_group: u32, index_offset: u32 },
+}
+
+fn resolve(device: &Device, address: &str) -> Result<Location> {
+    if address.starts_with('%') {
+        return Ok(Location::Bits(address.parse()?));
+    }
+    let info = symbol::get_info(device, address)?;
+    if info.flags & SYMBOL_FLAG_BIT_VALUE == 0 {
+        Ok(Location::Bytes {
+            index_group: info.ix_group,
+            index_offset: info.ix_offset,
+        })
+    } else {
+        Ok(Location::Bits(BitAddress::new(
+            info.ix_group,
+            info.ix_offset,
+        )))
+    }
+}
+
+pub(crate) fn read_bits(device: &Device, address: &str, count: usize) -> Result<Vec<bool>> {
+    match resolve(device, address)? {
+        Location::Bytes {
+            index_group,
+            index_offset,
+        } => {
+            let mut buf = vec![0; count];
+            device.read_exact(index_group, index_offset, &mut buf)?;
+            Ok(buf.into_iter().map(|b| b != 0).collect())
+        }
+        Location::Bits(addr) if count == 1 => {
+            let mut buf = [0; 1];
+            device.read_exact(addr.index_group, addr.bit_offset, &mut buf)?;
+            Ok(vec![buf[0] != 0])
+        }
+        Location::Bits(addr) => {
+            let offsets = bit_offsets(addr, count)?;
+            let mut result = Vec::with_capacity(count);
+            for chunk in offsets.chunks(MAX_SUMUP_REQUESTS) {
+                let mut buffers = vec![[0; 1]; chunk.len()];
+                let mut requests = chunk
+                    .iter()
+                    .zip(buffers.iter_mut())
+                    .map(|(offset, buf)| ReadRequest::new(addr.index_group, *offset, buf))
+                    .collect::<Result<Vec<_>>>()?;
+                device.read_multi(&mut requests)?;
+                for request in &requests {
+                    let data = request.data()?;
+                    result.push(data.first().ok_or_else(|| Error::io("short read"))? != &0);
+                }
+            }
+            Ok(result)
+        }
+    }
+}
+
+pub(crate) fn write_bits(device: &Device, address: &str, values: &[bool]) -> Result<()> {
+    match resolve(device, address)? {
+        Location::Bytes {
+            index_group,
+            index_offset,
+        } => {
+            let buf = values.iter().map(|v| u8::from(*v)).collect::<Vec<_>>();
+            device.write(index_group, index_offset, &buf)
+        }
+        Location::Bits(addr) if values.len() == 1 => {
+            device.write(addr.index_group, addr.bit_offset, &[u8::from(values[0])])
+        }
+        Location::Bits(addr) => {
+            let offsets = bit_offsets(addr, values.len())?;
+            let buffers = values.iter().map(|v| [u8::from(*v)]).collect::<Vec<_>>();
+            for (offsets, buffers) in offsets
+                .chunks(MAX_SUMUP_REQUESTS)
+                .zip(buffers.chunks(MAX_SUMUP_REQUESTS))
+            {
+                let mut requests = offsets
+                    .iter()
+                    .zip(buffers)
+                    .map(|(offset, buf)| WriteRequest::new(addr.index_group, *offset, buf))
+                    .collect::<Result<Vec<_>>>()?;
+                device.write_multi(&mut requests)?;
+                for request in &requests {
+                    request.ensure()?;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+fn bit_offsets(addr: BitAddress, count: usize) -> Result<Vec<u32>> {
+    (0..count)
+        .map(|i| {
+            u32::try_from(i)
+                .ok()
+                .and_then(|i| addr.bit_offset.checked_add(i))
+                .ok_or_else(|| Error::invalid_data("bit offset overflow"))
+        })
+        .collect()
+}
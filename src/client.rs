@@ -8,13 +8,14 @@ use std::mem::{self, size_of};
 use std::net::{IpAddr, ToSocketAddrs};
 use std::str::FromStr;
 use std::sync::atomic::{AtomicU32, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use std::thread;
 use std::time::{Duration, Instant};
 
 use roboplc::locking::{Condvar, Mutex, RawMutex};
 use roboplc::{policy_channel, DataDeliveryPolicy, Error, Result};
 
+use bma_ts::Timestamp;
 use byteorder::{ByteOrder, ReadBytesExt as _, LE};
 use itertools::Itertools;
 use roboplc::comm::{CommReader, SessionGuard, Timeouts};
@@ -22,7 +23,8 @@ use roboplc::policy_channel::{Receiver, Sender};
 use tracing::{debug, error, trace, warn};
 
 use crate::errors::ads_error;
-use crate::{notif, AdsMapping};
+use crate::layout::PlcLayout;
+use crate::{notif, AdsIndexMapping, AdsMapping};
 use crate::{AmsAddr, AmsNetId};
 
 use zerocopy::byteorder::{U16, U32};
@@ -38,6 +40,17 @@ impl DataDeliveryPolicy for AdsCommResult {}
 
 const MAX_NOTIFICATION_QUEUE: usize = 16384;
 const MAX_BUF_QUEUE: usize = 1024;
+const MAX_SYMBOL_VERSION_QUEUE: usize = 64;
+const MAX_EARLY_SAMPLES: usize = 1024;
+/// Handles of client notifications start from this value, to be distinguished from server ones.
+const CLIENT_NOTIF_HANDLE_BASE: u32 = 0x8000_0000;
+const MIN_POLL_CYCLE: Duration = Duration::from_millis(1);
+/// The scheduler of client notifications checks if the client is still alive at least this often.
+const MAX_SCHEDULER_WAIT: Duration = Duration::from_secs(1);
+
+/// Maximum number of sub-requests in a single sum-up request, as recommended by Beckhoff. Larger
+/// batches are split into several requests.
+pub const MAX_SUMUP_REQUESTS: usize = 500;
 
 type DataCell<P> = rtsc::cell::DataCell<P, RawMutex, Condvar>;
 type ReplyMap = Arc<Mutex<BTreeMap<u32, DataCell<AdsCommResult>>>>;
@@ -154,6 +167,15 @@ impl Client {
         self.inner.notif_recv.clone()
     }
 
+    /// Get a receiver for symbol version change events of watched devices (see
+    /// [`Device::watch_symbol_version`]).
+    ///
+    /// The events have got a delivery policy `Single` per device, so only the latest change is
+    /// kept in the channel if the events are not processed in time.
+    pub fn get_symbol_version_channel(&self) -> Receiver<SymbolVersionEvent> {
+        self.inner.symbol_version_recv.clone()
+    }
+
     /// Return a wrapper that executes operations for a target device (known by
     /// NetID and port).
     ///
@@ -236,8 +258,18 @@ struct ClientInner {
     reply_map: ReplyMap,
     /// Receiver for notifications: cloned and given out to interested parties
     notif_recv: Receiver<notif::Notification>,
+    /// Sender for notifications of client transmission modes
+    notif_send: Sender<notif::Notification>,
+    /// Notifications of client transmission modes
+    client_notifs: Arc<ClientNotifs>,
     /// Active notification handles: these will be closed on Drop
     notif_handles: Mutex<BTreeSet<(AmsAddr, notif::Handle)>>,
+    /// Symbol version watches (shared with the reader)
+    symbol_versions: Arc<SymbolVersions>,
+    /// Routed notifications (shared with the reader)
+    routes: Arc<Routes>,
+    /// Receiver for symbol version change events
+    symbol_version_recv: Receiver<SymbolVersionEvent>,
 }
 
 impl ClientInner {
@@ -277,15 +309,25 @@ impl ClientInner {
 
         let (restart_tx, restart_rx) = policy_channel::bounded(1);
 
+        let (symbol_version_send, symbol_version_recv) =
+            policy_channel::bounded(MAX_SYMBOL_VERSION_QUEUE);
+        let symbol_versions = Arc::new(SymbolVersions {
+            watches: <_>::default(),
+            event_send: symbol_version_send,
+        });
+        let routes = Arc::new(Routes::default());
+
         let reader = Reader {
             client: client.clone(),
             reply_map: reply_map.clone(),
             reader_rx,
             source: source_bytes,
             buf_recv,
-            notif_send,
+            notif_send: notif_send.clone(),
             restart_rx,
             restart_tx,
+            symbol_versions: symbol_versions.clone(),
+            routes: routes.clone(),
         };
 
         Ok((
@@ -295,6 +337,8 @@ impl ClientInner {
                 buf_send,
                 reply_map,
                 notif_recv,
+                notif_send,
+                client_notifs: <_>::default(),
                 invoke_id: <_>::default(),
                 read_timeout: if read_timeout > Duration::from_secs(0) {
                     Some(read_timeout)
@@ -302,6 +346,9 @@ impl ClientInner {
                     None
                 },
                 notif_handles: <_>::default(),
+                symbol_versions,
+                routes,
+                symbol_version_recv,
             },
             reader,
         ))
@@ -469,6 +516,199 @@ impl DataDeliveryPolicy for RestartEvent {
     }
 }
 
+/// Received every time when the symbol version of a watched device has been changed (e.g. after
+/// an online change), see [`Device::watch_symbol_version`].
+#[derive(Copy, Clone, Debug)]
+pub struct SymbolVersionEvent {
+    /// The device address.
+    pub addr: AmsAddr,
+    /// The new symbol version.
+    pub version: u8,
+}
+
+impl DataDeliveryPolicy for SymbolVersionEvent {
+    fn delivery_policy(&self) -> roboplc::prelude::DeliveryPolicy {
+        roboplc::prelude::DeliveryPolicy::Single
+    }
+    fn eq_kind(&self, other: &Self) -> bool {
+        self.addr == other.addr
+    }
+}
+
+#[derive(Default)]
+struct SymbolVersionWatch {
+    /// The notification handle of the watch
+    handle: notif::Handle,
+    /// The session the notification has been added in
+    session_id: usize,
+    /// The last known symbol version
+    version: Option<u8>,
+    /// Incremented on each symbol version change
+    generation: usize,
+}
+
+/// Symbol version watches, shared between the client and the reader.
+struct SymbolVersions {
+    watches: Mutex<BTreeMap<AmsAddr, SymbolVersionWatch>>,
+    event_send: Sender<SymbolVersionEvent>,
+}
+
+impl SymbolVersions {
+    /// Process a notification sample. Returns true if the sample is a watch one.
+    fn process(&self, source: AmsAddr, sample: &notif::Sample) -> bool {
+        let mut watches = self.watches.lock();
+        let Some(watch) = watches.get_mut(&source) else {
+            return false;
+        };
+        if sample.handle != watch.handle {
+            return false;
+        }
+        if let Some(&version) = sample.data.first() {
+            self.set_version(source, watch, version);
+        }
+        true
+    }
+
+    fn set_version(&self, addr: AmsAddr, watch: &mut SymbolVersionWatch, version: u8) {
+        if watch.version.map_or(false, |v| v != version) {
+            debug!(%addr, version, "symbol version changed");
+            watch.generation += 1;
+            let _r = self.event_send.send(SymbolVersionEvent { addr, version });
+        }
+        watch.version = Some(version);
+    }
+}
+
+/// The latest sample of a cached notification.
+pub(crate) struct CachedSample {
+    /// The timestamp of the sample, set by the server
+    pub(crate) timestamp: Timestamp,
+    /// When the sample has been received
+    pub(crate) received: Instant,
+    pub(crate) data: Vec<u8>,
+}
+
+pub(crate) type SampleSlot = Arc<Mutex<Option<CachedSample>>>;
+
+/// A callback for notification samples, called by the reader thread.
+pub(crate) type SampleCallback = Box<dyn Fn(&notif::Sample) + Send + Sync>;
+
+/// Where the samples of a routed notification are delivered.
+pub(crate) enum Route {
+    /// The latest sample is kept
+    Cache(SampleSlot),
+    Channel(Sender<notif::OwnedSample>),
+    Callback(SampleCallback),
+}
+
+impl Route {
+    fn deliver(&self, source: AmsAddr, sample: &notif::Sample) {
+        match self {
+            Route::Cache(slot) => {
+                slot.lock().replace(CachedSample {
+                    timestamp: sample.timestamp,
+                    received: Instant::now(),
+                    data: sample.data.to_vec(),
+                });
+            }
+            Route::Channel(tx) => {
+                if tx
+                    .try_send(notif::OwnedSample::new(source, sample))
+                    .is_err()
+                {
+                    warn!(%source, handle = sample.handle, "subscription channel full, sample dropped");
+                }
+            }
+            Route::Callback(f) => f(sample),
+        }
+    }
+}
+
+#[derive(Default)]
+struct RoutesInner {
+    /// Routes by source and handle, with the session the notification has been added in
+    routes: BTreeMap<(AmsAddr, notif::Handle), (usize, Route)>,
+    /// Number of routed notifications being added, per device
+    pending: BTreeMap<AmsAddr, usize>,
+    /// Samples received from devices with notifications being added, which are not routed yet.
+    /// The server may send the first sample before the handle is known to the client.
+    early: Vec<notif::OwnedSample>,
+}
+
+/// Routed notifications, which samples are not sent to the notification channel, shared between
+/// the client and the reader.
+#[derive(Default)]
+struct Routes {
+    inner: Mutex<RoutesInner>,
+}
+
+impl Routes {
+    /// Process a notification sample. Returns true if the sample has been routed or kept until
+    /// the notifications being added are known.
+    fn process(&self, source: AmsAddr, sample: &notif::Sample) -> bool {
+        let mut inner = self.inner.lock();
+        if let Some((_, route)) = inner.routes.get(&(source, sample.handle)) {
+            route.deliver(source, sample);
+            true
+        } else if inner.pending.contains_key(&source) && inner.early.len() < MAX_EARLY_SAMPLES {
+            inner.early.push(notif::OwnedSample::new(source, sample));
+            true
+        } else {
+            false
+        }
+    }
+
+    fn begin(&self, addr: AmsAddr) {
+        *self.inner.lock().pending.entry(addr).or_default() += 1;
+    }
+
+    /// Finish adding a notification, delivering the samples received while it was being added.
+    /// When no more notifications are being added to the device, returns the samples which have
+    /// not been routed, to be dispatched again.
+    #[must_use]
+    fn end(
+        &self,
+        addr: AmsAddr,
+        route: Option<(notif::Handle, usize, Route)>,
+    ) -> Vec<notif::OwnedSample> {
+        let mut inner = self.inner.lock();
+        if let Some((handle, session_id, route)) = route {
+            inner.early.retain(|sample| {
+                if sample.source == addr && sample.handle == handle {
+                    route.deliver(addr, &sample.as_sample());
+                    false
+                } else {
+                    true
+                }
+            });
+            inner.routes.insert((addr, handle), (session_id, route));
+        }
+        let pending = inner.pending.entry(addr).or_default();
+        *pending = pending.saturating_sub(1);
+        if *pending > 0 {
+            return Vec::new();
+        }
+        inner.pending.remove(&addr);
+        let (unrouted, early) = std::mem::take(&mut inner.early)
+            .into_iter()
+            .partition(|sample| sample.source == addr);
+        inner.early = early;
+        unrouted
+    }
+
+    /// Remove a route, if it has been added in the given session
+    fn remove(&self, addr: AmsAddr, handle: notif::Handle, session_id: usize) {
+        let mut inner = self.inner.lock();
+        if inner
+            .routes
+            .get(&(addr, handle))
+            .map_or(false, |(s, _)| *s == session_id)
+        {
+            inner.routes.remove(&(addr, handle));
+        }
+    }
+}
+
 /// Implementation detail: reader thread that takes replies and notifications
 /// and distributes them accordingly.
 pub struct Reader {
@@ -480,6 +720,8 @@ pub struct Reader {
     notif_send: Sender<notif::Notification>,
     restart_rx: Receiver<RestartEvent>,
     restart_tx: Sender<RestartEvent>,
+    symbol_versions: Arc<SymbolVersions>,
+    routes: Arc<Routes>,
 }
 
 impl Reader {
@@ -599,12 +841,276 @@ impl Reader {
                 continue;
             }
 
-            // Send the notification to whoever wants to receive it.
             if let Ok(notif) = notif::Notification::new(buf) {
-                self.notif_send.send(notif).expect("never disconnects");
+                dispatch_notification(&self.symbol_versions, &self.routes, &self.notif_send, notif);
+            }
+        }
+    }
+}
+
+/// Send the notification to whoever wants to receive it, unless all its samples are internal or
+/// routed ones.
+fn dispatch_notification(
+    symbol_versions: &SymbolVersions,
+    routes: &Routes,
+    notif_send: &Sender<notif::Notification>,
+    notif: notif::Notification,
+) {
+    if let Some(notif) = route_notification(symbol_versions, routes, notif) {
+        notif_send.send(notif).expect("never disconnects");
+    }
+}
+
+/// Process internal and routed samples of the notification. Returns the notification back if it
+/// has samples for the notification channel.
+fn route_notification(
+    symbol_versions: &SymbolVersions,
+    routes: &Routes,
+    notif: notif::Notification,
+) -> Option<notif::Notification> {
+    let source = notif.source();
+    let mut external = false;
+    for sample in notif.samples() {
+        if !symbol_versions.process(source, &sample) && !routes.process(source, &sample) {
+            external = true;
+        }
+    }
+    external.then_some(notif)
+}
+
+/// Notifications with client transmission modes, emulated by polling.
+///
+/// A single scheduler thread per client polls all due notifications, reading the data of each
+/// device with sum-up requests. The thread is started when a notification is added and exits when
+/// there are no notifications to poll or the client has been dropped.
+#[derive(Default)]
+struct ClientNotifs {
+    state: Mutex<ClientNotifsState>,
+    wakeup: Condvar,
+}
+
+#[derive(Default)]
+struct ClientNotifsState {
+    next_handle: u32,
+    polls: BTreeMap<(AmsAddr, notif::Handle), ClientPoll>,
+    /// If the scheduler thread is running
+    scheduler: bool,
+}
+
+struct ClientPoll {
+    index_group: u32,
+    index_offset: u32,
+    length: usize,
+    trans_mode: notif::TransmissionMode,
+    cycle_time: Duration,
+    /// When the data is polled the next time, `None` if the notification has been finished
+    next: Option<Instant>,
+    /// The last data delivered (for `ClientOnChange` only)
+    last: Option<Vec<u8>>,
+}
+
+/// A poll of a due notification.
+struct PollRequest {
+    handle: notif::Handle,
+    index_group: u32,
+    index_offset: u32,
+    length: usize,
+}
+
+impl ClientNotifs {
+    fn add(
+        self: &Arc<Self>,
+        client: &Arc<ClientInner>,
+        addr: AmsAddr,
+        index_group: u32,
+        index_offset: u32,
+        attributes: &notif::Attributes,
+    ) -> Result<notif::Handle> {
+        let mut state = self.state.lock();
+        let handle = CLIENT_NOTIF_HANDLE_BASE.wrapping_add(state.next_handle);
+        state.next_handle = state.next_handle.wrapping_add(1);
+        state.polls.insert(
+            (addr, handle),
+            ClientPoll {
+                index_group,
+                index_offset,
+                length: attributes.length,
+                trans_mode: attributes.trans_mode,
+                cycle_time: attributes.cycle_time.max(MIN_POLL_CYCLE),
+                next: Some(Instant::now()),
+                last: None,
+            },
+        );
+        if state.scheduler {
+            self.wakeup.notify_one();
+        } else {
+            let notifs = self.clone();
+            let client = Arc::downgrade(client);
+            if let Err(error) = thread::Builder::new()
+                .name("ADSpoll".to_owned())
+                .spawn(move || notifs.run(&client))
+            {
+                state.polls.remove(&(addr, handle));
+                return Err(Error::io(error));
             }
+            state.scheduler = true;
         }
+        Ok(handle)
     }
+
+    /// Remove a notification, returns false if the notification is not a client one.
+    fn remove(&self, addr: AmsAddr, handle: notif::Handle) -> bool {
+        self.state.lock().polls.remove(&(addr, handle)).is_some()
+    }
+
+    /// The scheduler loop. The client is not kept alive by the scheduler.
+    fn run(&self, client: &Weak<ClientInner>) {
+        let mut state = self.state.lock();
+        loop {
+            let now = Instant::now();
+            let mut due: BTreeMap<AmsAddr, Vec<PollRequest>> = BTreeMap::new();
+            let mut next_due: Option<Instant> = None;
+            for (&(addr, handle), poll) in &mut state.polls {
+                let Some(mut next) = poll.next else {
+                    continue;
+                };
+                if next <= now {
+                    due.entry(addr).or_default().push(PollRequest {
+                        handle,
+                        index_group: poll.index_group,
+                        index_offset: poll.index_offset,
+                        length: poll.length,
+                    });
+                    // do not try to catch up if the polls are late
+                    next = (next + poll.cycle_time).max(now);
+                    poll.next = Some(next);
+                }
+                next_due = Some(next_due.map_or(next, |n| n.min(next)));
+            }
+            let Some(next_due) = next_due else {
+                break;
+            };
+            if due.is_empty() {
+                let timeout = next_due.saturating_duration_since(now);
+                self.wakeup
+                    .wait_for(&mut state, timeout.min(MAX_SCHEDULER_WAIT));
+                if client.strong_count() == 0 {
+                    break;
+                }
+                continue;
+            }
+            drop(state);
+            let Some(inner) = client.upgrade() else {
+                state = self.state.lock();
+                break;
+            };
+            let timestamp = Timestamp::now();
+            let polled = due
+                .into_iter()
+                .map(|(addr, requests)| {
+                    let device = Device {
+                        client: Client {
+                            inner: inner.clone(),
+                        },
+                        addr,
+                    };
+                    let data = poll_device(&device, &requests);
+                    (addr, requests, data)
+                })
+                .collect::<Vec<_>>();
+            state = self.state.lock();
+            let mut external = Vec::new();
+            for (addr, requests, data) in polled {
+                for (request, data) in requests.iter().zip(data) {
+                    // the notification may have been deleted while polling
+                    let Some(poll) = state.polls.get_mut(&(addr, request.handle)) else {
+                        continue;
+                    };
+                    let Some(data) = data else {
+                        continue;
+                    };
+                    match poll.trans_mode {
+                        notif::TransmissionMode::ClientOnChange => {
+                            if poll.last.as_ref() == Some(&data) {
+                                continue;
+                            }
+                            poll.last = Some(data.clone());
+                        }
+                        notif::TransmissionMode::Client1Req => poll.next = None,
+                        _ => {}
+                    }
+                    match notif::Notification::with_sample(addr, request.handle, timestamp, &data) {
+                        // routed samples are delivered while the notification is known to exist
+                        Ok(notif) => external.extend(route_notification(
+                            &inner.symbol_versions,
+                            &inner.routes,
+                            notif,
+                        )),
+                        Err(error) => warn!(%error, "unable to build a notification"),
+                    }
+                }
+            }
+            drop(state);
+            for notif in external {
+                inner.notif_send.send(notif).expect("never disconnects");
+            }
+            drop(inner);
+            state = self.state.lock();
+        }
+        state.scheduler = false;
+    }
+}
+
+/// Read the data of the due notifications of a device. Returns the data of each request, `None`
+/// if the read has failed.
+fn poll_device(device: &Device, requests: &[PollRequest]) -> Vec<Option<Vec<u8>>> {
+    let read = |request: &PollRequest| {
+        let mut buf = vec![0; request.length];
+        match device.read(request.index_group, request.index_offset, &mut buf) {
+            Ok(len) => {
+                buf.truncate(len);
+                Some(buf)
+            }
+            Err(error) => {
+                debug!(addr = %device.addr, handle = request.handle, %error, "notification poll failed");
+                None
+            }
+        }
+    };
+    if requests.len() == 1 {
+        return requests.iter().map(read).collect();
+    }
+    let mut result = Vec::with_capacity(requests.len());
+    for chunk in requests.chunks(MAX_SUMUP_REQUESTS) {
+        let mut bufs = chunk
+            .iter()
+            .map(|request| vec![0; request.length])
+            .collect::<Vec<_>>();
+        let multi = chunk
+            .iter()
+            .zip(&mut bufs)
+            .map(|(request, buf)| ReadRequest::new(request.index_group, request.index_offset, buf))
+            .collect::<Result<Vec<_>>>()
+            .and_then(|mut multi| device.read_multi(&mut multi).map(|()| multi));
+        match multi {
+            Ok(multi) => {
+                result.extend(chunk.iter().zip(&multi).map(|(request, read)| {
+                    read.data()
+                        .map_err(|error| {
+                            debug!(addr = %device.addr, handle = request.handle, %error, "notification poll failed");
+                        })
+                        .ok()
+                        .map(<[u8]>::to_vec)
+                }));
+            }
+            Err(error) => {
+                // the server may not support sum-up requests
+                debug!(addr = %device.addr, %error, "sum-up notification poll failed");
+                result.extend(chunk.iter().map(read));
+            }
+        }
+    }
+    result
 }
 
 /// A `Client` wrapper that talks to a specific ADS device.
@@ -616,6 +1122,11 @@ pub struct Device {
 }
 
 impl Device {
+    /// Return the address of the device.
+    pub fn addr(&self) -> AmsAddr {
+        self.addr
+    }
+
     /// Read the device's name + version.
     pub fn get_info(&self) -> Result<DeviceInfo> {
         let mut data = DeviceInfoRaw::new_zeroed();
@@ -908,6 +1419,30 @@ impl Device {
         Ok(())
     }
 
+    /// Read a single bit. The address is either a direct bit address (`%MX12.3`, `%IX0.1`,
+    /// `%QX2.7`) or a symbol name (see [`crate::bits`]).
+    pub fn read_bit(&self, address: &str) -> Result<bool> {
+        crate::bits::read_bits(self, address, 1)?
+            .pop()
+            .ok_or_else(|| Error::io("short read"))
+    }
+
+    /// Write a single bit (see [`Device::read_bit`]).
+    pub fn write_bit(&self, address: &str, value: bool) -> Result<()> {
+        crate::bits::write_bits(self, address, &[value])
+    }
+
+    /// Read consecutive bits, starting from the address (see [`Device::read_bit`]). Multiple
+    /// bit-addressed values are read with a single sum-up request.
+    pub fn read_bits(&self, address: &str, count: usize) -> Result<Vec<bool>> {
+        crate::bits::read_bits(self, address, count)
+    }
+
+    /// Write consecutive bits, starting from the address (see [`Device::read_bits`]).
+    pub fn write_bits(&self, address: &str, values: &[bool]) -> Result<()> {
+        crate::bits::write_bits(self, address, values)
+    }
+
     /// Return the ADS and device state of the device.
     pub fn get_state(&self) -> Result<(AdsState, u16)> {
         let mut state = ReadState::new_zeroed();
@@ -949,6 +1484,9 @@ impl Device {
     /// If the notification is not deleted explictly using `delete_notification`
     /// and the `Handle`, it is deleted when the `Client` object is dropped or shut down.
     ///
+    /// For client transmission modes (see [`notif::TransmissionMode`]) no request is sent, the
+    /// data is polled by a separate thread until the notification is deleted.
+    ///
     /// NOTE: Notifications are not restored automatically if the remote is restarted.
     pub fn add_notification(
         &self,
@@ -956,6 +1494,9 @@ impl Device {
         index_offset: u32,
         attributes: &notif::Attributes,
     ) -> Result<notif::Handle> {
+        if attributes.trans_mode.is_client() {
+            return self.add_client_notification(index_group, index_offset, attributes);
+        }
         let data = AddNotif {
             index_group: U32::new(index_group),
             index_offset: U32::new(index_offset),
@@ -992,6 +1533,21 @@ impl Device {
         Ok(handle.get())
     }
 
+    fn add_client_notification(
+        &self,
+        index_group: u32,
+        index_offset: u32,
+        attributes: &notif::Attributes,
+    ) -> Result<notif::Handle> {
+        self.client.inner.client_notifs.add(
+            &self.client.inner,
+            self.addr,
+            index_group,
+            index_offset,
+            attributes,
+        )
+    }
+
     /// Add a notification handle for a symbol.
     ///
     /// NOTE: Notifications are not restored automatically if the remote is restarted.
@@ -1012,8 +1568,46 @@ impl Device {
     /// own error.  The [`AddNotifRequest::handle`] method will return either
     /// the returned handle or the error for each read.
     ///
+    /// Notifications with client transmission modes are added locally, the rest are sent to the
+    /// server.
+    ///
     /// NOTE: Notifications are not restored automatically if the remote has been restarted
     pub fn add_notification_multi(&self, requests: &mut [AddNotifRequest]) -> Result<()> {
+        let mut server_requests = Vec::with_capacity(requests.len());
+        let mut client_handles = Vec::new();
+        for req in requests.iter_mut() {
+            if !req.trans_mode.is_client() {
+                server_requests.push(req);
+                continue;
+            }
+            let attributes = notif::Attributes::new(
+                req.req.length.get() as usize,
+                req.trans_mode,
+                Duration::from_millis(req.req.max_delay.get().into()),
+                Duration::from_millis(req.req.cycle_time.get().into()),
+            );
+            match self.add_client_notification(
+                req.req.index_group.get(),
+                req.req.index_offset.get(),
+                &attributes,
+            ) {
+                Ok(handle) => {
+                    req.res.result.set(0);
+                    req.res.length.set(handle);
+                    client_handles.push(handle);
+                }
+                Err(e) => {
+                    for handle in client_handles {
+                        self.client.inner.client_notifs.remove(self.addr, handle);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        if server_requests.is_empty() {
+            return Ok(());
+        }
+        let mut requests = server_requests;
         let nreq = requests.len();
         let read_len = size_of::<ResultLength>() * nreq;
         let write_len = size_of::<AddNotif>() * nreq;
@@ -1026,7 +1620,7 @@ impl Device {
         let mut read_len = U32::<LE>::new(0);
         let mut w_buffers = vec![header.as_bytes()];
         let mut r_buffers = vec![read_len.as_bytes_mut()];
-        for req in requests.iter_mut() {
+        for req in &mut requests {
             w_buffers.push(req.req.as_bytes());
             r_buffers.push(req.res.as_bytes_mut());
         }
@@ -1046,6 +1640,9 @@ impl Device {
 
     /// Delete a notification with given handle.
     pub fn delete_notification(&self, handle: notif::Handle) -> Result<()> {
+        if self.client.inner.client_notifs.remove(self.addr, handle) {
+            return Ok(());
+        }
         self.client.communicate(
             Command::DeleteNotification,
             self.addr,
@@ -1068,6 +1665,23 @@ impl Device {
     /// own error.  The [`DelNotifRequest::ensure`] method will return either the
     /// returned data or the error for each read.
     pub fn delete_notification_multi(&self, requests: &mut [DelNotifRequest]) -> Result<()> {
+        let mut server_requests = Vec::with_capacity(requests.len());
+        for req in requests.iter_mut() {
+            if self
+                .client
+                .inner
+                .client_notifs
+                .remove(self.addr, req.req.get())
+            {
+                req.res.set(0);
+            } else {
+                server_requests.push(req);
+            }
+        }
+        if server_requests.is_empty() {
+            return Ok(());
+        }
+        let mut requests = server_requests;
         let nreq = requests.len();
         let read_len = size_of::<u32>() * nreq;
         let write_len = size_of::<u32>() * nreq;
@@ -1080,7 +1694,7 @@ impl Device {
         let mut read_len = U32::<LE>::new(0);
         let mut w_buffers = vec![header.as_bytes()];
         let mut r_buffers = vec![read_len.as_bytes_mut()];
-        for req in requests.iter_mut() {
+        for req in &mut requests {
             w_buffers.push(req.req.as_bytes());
             r_buffers.push(req.res.as_bytes_mut());
         }
@@ -1098,12 +1712,358 @@ impl Device {
         Ok(())
     }
 
+    /// Add a notification which samples are routed to a dedicated channel of the given capacity
+    /// instead of the common notification channel.
+    ///
+    /// If the channel is full, new samples are dropped. The notification is deleted when the
+    /// returned [`notif::Subscription`] is dropped.
+    pub fn add_notification_channel(
+        &self,
+        index_group: u32,
+        index_offset: u32,
+        attributes: &notif::Attributes,
+        capacity: usize,
+    ) -> Result<notif::Subscription> {
+        let (tx, rx) = policy_channel::bounded(capacity);
+        let session_id = self.client.session_id();
+        let handle = self.add_routed_notification(
+            index_group,
+            index_offset,
+            attributes,
+            Route::Channel(tx),
+        )?;
+        Ok(notif::Subscription::new(self, handle, session_id, Some(rx)))
+    }
+
+    /// Add a notification which samples are passed to the callback instead of the common
+    /// notification channel.
+    ///
+    /// The callback is called by the reader thread, so it must return quickly and must not call
+    /// any device methods (which would wait for the reader forever). The notification is deleted
+    /// when the returned [`notif::Subscription`] is dropped.
+    pub fn add_notification_callback<F>(
+        &self,
+        index_group: u32,
+        index_offset: u32,
+        attributes: &notif::Attributes,
+        callback: F,
+    ) -> Result<notif::Subscription>
+    where
+        F: Fn(&notif::Sample) + Send + Sync + 'static,
+    {
+        let session_id = self.client.session_id();
+        let handle = self.add_routed_notification(
+            index_group,
+            index_offset,
+            attributes,
+            Route::Callback(Box::new(callback)),
+        )?;
+        Ok(notif::Subscription::new(self, handle, session_id, None))
+    }
+
+    /// Add a notification for the symbol, which samples are decoded into values of `T` with
+    /// `binrw` (see [`notif::TypedSubscription`]).
+    ///
+    /// The notification is deleted when the returned subscription is dropped.
+    pub fn subscribe<T>(
+        &self,
+        symbol: &str,
+        attributes: &notif::Attributes,
+    ) -> Result<notif::TypedSubscription<T>>
+    where
+        T: for<'a> roboplc::io::binrw::BinRead<Args<'a> = ()> + Send + 'static,
+    {
+        notif::TypedSubscription::new(self, symbol, attributes, notif::decode_binrw::<T>)
+    }
+
+    /// Add a notification for the symbol, which samples are decoded into values of `T` with the
+    /// given function (e.g. [`notif::decode_from_bytes`] for `zerocopy` types).
+    ///
+    /// The notification is deleted when the returned subscription is dropped.
+    pub fn subscribe_with<T, F>(
+        &self,
+        symbol: &str,
+        attributes: &notif::Attributes,
+        decode: F,
+    ) -> Result<notif::TypedSubscription<T>>
+    where
+        T: Send + 'static,
+        F: Fn(&[u8]) -> Result<T> + Send + Sync + 'static,
+    {
+        notif::TypedSubscription::new(self, symbol, attributes, decode)
+    }
+
+    /// Add a notification which samples are routed instead of being sent to the notification
+    /// channel.
+    pub(crate) fn add_routed_notification(
+        &self,
+        index_group: u32,
+        index_offset: u32,
+        attributes: &notif::Attributes,
+        route: Route,
+    ) -> Result<notif::Handle> {
+        let routes = &self.client.inner.routes;
+        let session_id = self.client.session_id();
+        routes.begin(self.addr);
+        let result = self.add_notification(index_group, index_offset, attributes);
+        let unrouted = routes.end(
+            self.addr,
+            result
+                .as_ref()
+                .ok()
+                .map(|handle| (*handle, session_id, route)),
+        );
+        self.dispatch_samples(unrouted);
+        result
+    }
+
+    /// Dispatch samples which have been kept while notifications were being added.
+    fn dispatch_samples(&self, samples: Vec<notif::OwnedSample>) {
+        let inner = &self.client.inner;
+        for sample in samples {
+            match notif::Notification::with_sample(
+                sample.source,
+                sample.handle,
+                sample.timestamp,
+                &sample.data,
+            ) {
+                Ok(notif) => dispatch_notification(
+                    &inner.symbol_versions,
+                    &inner.routes,
+                    &inner.notif_send,
+                    notif,
+                ),
+                Err(error) => warn!(%error, "unable to dispatch a notification sample"),
+            }
+        }
+    }
+
+    /// Add a notification which samples are kept in the returned slot (the latest one only)
+    /// instead of being sent to the notification channel.
+    pub(crate) fn add_cached_notification(
+        &self,
+        index_group: u32,
+        index_offset: u32,
+        attributes: &notif::Attributes,
+    ) -> Result<(notif::Handle, SampleSlot)> {
+        let slot = SampleSlot::default();
+        let handle = self.add_routed_notification(
+            index_group,
+            index_offset,
+            attributes,
+            Route::Cache(slot.clone()),
+        )?;
+        Ok((handle, slot))
+    }
+
+    /// Delete a routed notification. If the session has been changed, the notification is only
+    /// unregistered.
+    pub(crate) fn delete_routed_notification(
+        &self,
+        handle: notif::Handle,
+        session_id: usize,
+    ) -> Result<()> {
+        // client notifications do not depend on the session, the poll is removed before the route
+        // as routed samples are delivered only while the poll exists
+        let client = self.client.inner.client_notifs.remove(self.addr, handle);
+        self.client
+            .inner
+            .routes
+            .remove(self.addr, handle, session_id);
+        if !client && session_id == self.client.session_id() {
+            self.delete_notification(handle)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Watch the symbol version of the device.
+    ///
+    /// TwinCAT increments the symbol version on online changes, which invalidates all symbol
+    /// handles without dropping the TCP session. When the version changes, the generation returned
+    /// by [`Device::symbol_generation`] is incremented, so [`AdsMapping`] and
+    /// [`crate::Handle`] objects can detect that they need to be re-created, and an event is sent
+    /// to the channel returned by [`Client::get_symbol_version_channel`].
+    ///
+    /// The watch is implemented as a device notification. If the session has been changed, the
+    /// method must be called again to re-subscribe ([`AdsMapping`] does this automatically). The
+    /// method does nothing if the watch is already active in the current session.
+    pub fn watch_symbol_version(&self) -> Result<()> {
+        let session_id = self.client.session_id();
+        let symbol_versions = &self.client.inner.symbol_versions;
+        if symbol_versions
+            .watches
+            .lock()
+            .get(&self.addr)
+            .map_or(false, |w| w.session_id == session_id)
+        {
+            return Ok(());
+        }
+        let version = crate::symbol::get_symbol_version(self)?;
+        let attributes = notif::Attributes::new(
+            1,
+            notif::TransmissionMode::ServerOnChange,
+            Duration::ZERO,
+            Duration::ZERO,
+        );
+        // the samples received before the handle is stored are kept and dispatched again after
+        let routes = &self.client.inner.routes;
+        routes.begin(self.addr);
+        let result = self.add_notification(crate::index::GET_SYMVERSION, 0, &attributes);
+        if let Ok(handle) = result {
+            // the lock must not be held while communicating as the reader needs it
+            let mut watches = symbol_versions.watches.lock();
+            let watch = watches.entry(self.addr).or_default();
+            watch.handle = handle;
+            watch.session_id = session_id;
+            symbol_versions.set_version(self.addr, watch, version);
+        }
+        self.dispatch_samples(routes.end(self.addr, None));
+        result.map(|_| ())
+    }
+
+    /// Stop watching the symbol version of the device.
+    pub fn unwatch_symbol_version(&self) -> Result<()> {
+        let watch = self
+            .client
+            .inner
+            .symbol_versions
+            .watches
+            .lock()
+            .remove(&self.addr);
+        if let Some(watch) = watch {
+            if watch.session_id == self.client.session_id() {
+                self.delete_notification(watch.handle)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the symbol generation of the device, which is incremented each time the symbol
+    /// version is changed. Always zero if the symbol version is not watched.
+    pub fn symbol_generation(&self) -> usize {
+        self.client
+            .inner
+            .symbol_versions
+            .watches
+            .lock()
+            .get(&self.addr)
+            .map_or(0, |w| w.generation)
+    }
+
+    /// Re-subscribe the symbol version watch if it is active and the session has been changed.
+    pub(crate) fn refresh_symbol_version_watch(&self) -> Result<()> {
+        let watched = self
+            .client
+            .inner
+            .symbol_versions
+            .watches
+            .lock()
+            .contains_key(&self.addr);
+        if watched {
+            self.watch_symbol_version()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Creates [`AdsMapping`] objects for the given symbols and buffer sizes, resolving all
+    /// handles at once with [`crate::symbol::HandleSet`].
+    ///
+    /// The handles are shared by the mappings (including ones for the same symbol) and released
+    /// with a single request when the last mapping is dropped. Mappings for symbols which have
+    /// failed to resolve are still returned and try to resolve the symbol again on the first
+    /// access. Handles are re-created individually on reconnects and online changes.
+    pub fn mappings<S: AsRef<str>>(&self, symbols: &[(S, usize)]) -> Result<Vec<AdsMapping>> {
+        let mut seen = BTreeSet::new();
+        let names = symbols
+            .iter()
+            .map(|(s, _)| s.as_ref())
+            .filter(|s| seen.insert(*s))
+            .collect::<Vec<_>>();
+        let set = Arc::new(crate::symbol::HandleSet::new(self, &names)?);
+        Ok(symbols
+            .iter()
+            .map(|(symbol, buf_size)| {
+                let mut mapping = AdsMapping::new(self, symbol.as_ref(), *buf_size);
+                mapping.set_shared_handle(&set);
+                mapping
+            })
+            .collect())
+    }
+
     /// Creates [`AdsMapping`] for the given symbol. The buffer size MUST be greater or equal to
     /// the target structure size (for reading). For writing the buffer size can be any, however it
     /// is still recommended to use the target structure size for the buffer pre-allocation.
     pub fn mapping(&self, symbol: &str, buf_size: usize) -> AdsMapping {
         AdsMapping::new(self, symbol, buf_size)
     }
+
+    /// Creates [`AdsIndexMapping`] for the given index group and index offset (see
+    /// [`AdsIndexMapping::new`]).
+    pub fn index_mapping(
+        &self,
+        index_group: u32,
+        index_offset: u32,
+        buf_size: usize,
+    ) -> AdsIndexMapping {
+        AdsIndexMapping::new(self, index_group, index_offset, buf_size)
+    }
+
+    /// Creates [`crate::AdsCachedMapping`] for the given symbol, adding a device notification
+    /// (see [`crate::AdsCachedMapping::new`]).
+    pub fn cached_mapping(&self, symbol: &str, buf_size: usize) -> Result<crate::AdsCachedMapping> {
+        crate::AdsCachedMapping::new(self, symbol, buf_size)
+    }
+
+    /// Creates an empty [`crate::AdsProcessImage`], to read and write many symbols with single
+    /// sum-up requests.
+    pub fn process_image(&self) -> crate::AdsProcessImage {
+        crate::AdsProcessImage::new(self)
+    }
+
+    /// Creates [`AdsMapping`] for the given symbol with the buffer sized automatically (see
+    /// [`AdsMapping::new_auto_sized`]).
+    pub fn mapping_auto_sized(&self, symbol: &str) -> AdsMapping {
+        AdsMapping::new_auto_sized(self, symbol)
+    }
+
+    /// Creates [`AdsMapping`] for the given symbol, verifying that the layout of `T` matches the
+    /// PLC symbol type (see [`Device::verify_layout`]). The buffer is sized to fit `T`.
+    pub fn mapping_verified<T: PlcLayout>(&self, symbol: &str) -> Result<AdsMapping> {
+        AdsMapping::new_verified::<T>(self, symbol)
+    }
+
+    /// Verify that the layout of `T` matches the type of the PLC symbol.
+    ///
+    /// The symbol and type information is uploaded from the PLC on each call, so the method is
+    /// supposed to be called once, e.g. at startup. If the information is already available, use
+    /// [`crate::layout::verify_symbol`] instead.
+    ///
+    /// Returns an error which lists all field differences found.
+    pub fn verify_layout<T: PlcLayout>(&self, symbol: &str) -> Result<()> {
+        let (symbols, types) = crate::symbol::get_symbol_info(self)?;
+        crate::layout::verify_symbol::<T>(&symbols, &types, symbol)
+    }
+
+    /// Call a method of a function block instance (see [`crate::rpc`]). `args` contain raw data
+    /// of the input parameters, in the declaration order.
+    ///
+    /// The instance and its type information are looked up on each call. If the type
+    /// information is already available, use [`crate::rpc::call`] instead.
+    pub fn call_method(
+        &self,
+        path: &str,
+        method: &str,
+        args: &[&[u8]],
+    ) -> Result<crate::rpc::MethodResult> {
+        let symbol = crate::symbol::get_info(self, path)?;
+        let typ = crate::symbol::get_type_info(self, &symbol.typ)?;
+        let method = crate::rpc::find_method(&typ, method).ok_or_else(|| {
+            Error::invalid_data(format!("{}: no such method {}", symbol.typ, method))
+        })?;
+        crate::rpc::call(self, path, method, args)
+    }
 }
 
 /// Device info returned from an ADS server.
@@ -1330,6 +2290,11 @@ impl<'buf> ReadRequest<'buf> {
             ads_error("multi-read data", self.res.result.get())
         }
     }
+
+    /// The ADS result code of the request.
+    pub(crate) fn result(&self) -> u32 {
+        self.res.result.get()
+    }
 }
 
 /// A single request for a [`Device::write_multi`] request.
@@ -1363,6 +2328,11 @@ impl<'buf> WriteRequest<'buf> {
             ads_error("multi-write data", self.res.get())
         }
     }
+
+    /// The ADS result code of the request.
+    pub(crate) fn result(&self) -> u32 {
+        self.res.get()
+    }
 }
 
 /// A single request for a [`Device::write_read_multi`] request.
@@ -1415,6 +2385,7 @@ impl<'buf> WriteReadRequest<'buf> {
 pub struct AddNotifRequest {
     req: AddNotif,
     res: ResultLength, // length is the handle
+    trans_mode: notif::TransmissionMode,
 }
 
 impl AddNotifRequest {
@@ -1441,6 +2412,7 @@ impl AddNotifRequest {
                 reserved: [0; 16],
             },
             res: ResultLength::new_zeroed(),
+            trans_mode: attributes.trans_mode,
         })
     }
 
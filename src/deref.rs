@@ -0,0 +1,188 @@
+//! Dereferencing of PLC pointers (`POINTER TO`) and references (`REFERENCE TO`).
+//!
+//! Reading a pointer symbol returns the raw address, which is useless on the client side. The
+//! target is read by the symbol path instead: TwinCAT resolves handles to dereferenced paths
+//! (`MAIN.pNode^`, `MAIN.pNode^.pNext^`) and automatically dereferences references, so the
+//! target is read with [`crate::index::RW_SYMVAL_BYHANDLE`] from wherever it is located.
+//!
+//! Example, walking a linked list:
+//!
+//! ```rust,no_run
+//! use roboplc_io_ads::deref;
+//! use roboplc_io_ads::symbol;
+//! # fn example(device: &roboplc_io_ads::Device) -> roboplc::Result<()> {
+//! let (_, types) = symbol::get_symbol_info(device)?;
+//! let mut node = deref::deref(device, &types, "MAIN.pHead", "POINTER TO ST_Node")?;
+//! while let Some(value) = node {
+//!     println!("{}: {:?}", value.path, value.field_data("nValue"));
+//!     node = value.deref_field(device, &types, "pNext")?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use roboplc::{Error, Result};
+
+use crate::symbol::{self, Field, Handle, Symbol, Type, TypeMap};
+use crate::Device;
+
+/// Kind of an indirection.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PointerKind {
+    /// `POINTER TO`, dereferenced explicitly with `^`.
+    Pointer,
+    /// `REFERENCE TO`, dereferenced automatically by the PLC.
+    Reference,
+}
+
+/// Parse a pointer or reference type name and return its kind and the pointee type name.
+pub fn pointee_type(typ: &str) -> Option<(PointerKind, &str)> {
+    let typ = typ.trim();
+    for (prefix, kind) in [
+        ("POINTER TO ", PointerKind::Pointer),
+        ("REFERENCE TO ", PointerKind::Reference),
+    ] {
+        if let Some((head, pointee)) = typ.get(..prefix.len()).zip(typ.get(prefix.len()..)) {
+            if head.eq_ignore_ascii_case(prefix) && !pointee.trim().is_empty() {
+                return Some((kind, pointee.trim()));
+            }
+        }
+    }
+    None
+}
+
+/// A value read through a pointer or a reference.
+#[derive(Clone, Debug)]
+pub struct Pointee {
+    /// Symbol path of the value (e.g. `MAIN.pNode^`).
+    pub path: String,
+    /// Type name of the value.
+    pub typ: String,
+    /// Type information, if the type is found in the type map.
+    pub type_info: Option<Type>,
+    /// Raw value data.
+    pub data: Vec<u8>,
+}
+
+impl Pointee {
+    /// Get a structure field of the value and its data.
+    pub fn field(&self, name: &str) -> Option<(&Field, &[u8])> {
+        let field = self
+            .type_info
+            .as_ref()?
+            .fields
+            .iter()
+            .find(|f| f.name.eq_ignore_ascii_case(name))?;
+        let offset = usize::try_from(field.offset?).ok()?;
+        let data = self.data.get(offset..offset + field.size)?;
+        Some((field, data))
+    }
+
+    /// Get raw data of a structure field of the value.
+    pub fn field_data(&self, name: &str) -> Option<&[u8]> {
+        self.field(name).map(|(_, data)| data)
+    }
+
+    /// Follow a pointer or a reference field of the value. Returns `None` if the pointer is
+    /// null.
+    pub fn deref_field(
+        &self,
+        device: &Device,
+        types: &TypeMap,
+        name: &str,
+    ) -> Result<Option<Pointee>> {
+        let (field, data) = self
+            .field(name)
+            .ok_or_else(|| Error::invalid_data(format!("{}: no such field {}", self.path, name)))?;
+        let (kind, pointee) = pointee_type(&field.typ).ok_or_else(|| {
+            Error::invalid_data(format!("{}.{} is not a pointer", self.path, field.name))
+        })?;
+        if is_null(data) {
+            return Ok(None);
+        }
+        let path = format!("{}.{}", self.path, field.name);
+        read_pointee(device, types, &target_path(&path, kind), pointee).map(Some)
+    }
+}
+
+/// Dereference a pointer or a reference symbol of the given type. Returns `None` if the pointer
+/// is null.
+pub fn deref(device: &Device, types: &TypeMap, path: &str, typ: &str) -> Result<Option<Pointee>> {
+    let (kind, pointee) = pointee_type(typ)
+        .ok_or_else(|| Error::invalid_data(format!("{} is not a pointer", path)))?;
+    if kind == PointerKind::Pointer {
+        // the pointer size depends on the PLC platform
+        let mut buf = vec![0; symbol::get_size(device, path)?];
+        Handle::new(device, path)?.read(&mut buf)?;
+        if is_null(&buf) {
+            return Ok(None);
+        }
+    }
+    read_pointee(device, types, &target_path(path, kind), pointee).map(Some)
+}
+
+/// Dereference a pointer or a reference symbol (see [`deref()`]).
+#[allow(clippy::module_name_repetitions)]
+pub fn deref_symbol(device: &Device, types: &TypeMap, symbol: &Symbol) -> Result<Option<Pointee>> {
+    deref(device, types, &symbol.name, &symbol.typ)
+}
+
+fn target_path(path: &str, kind: PointerKind) -> String {
+    match kind {
+        PointerKind::Pointer => format!("{}^", path),
+        PointerKind::Reference => path.to_owned(),
+    }
+}
+
+fn is_null(data: &[u8]) -> bool {
+    data.iter().all(|b| *b == 0)
+}
+
+fn read_pointee(device: &Device, types: &TypeMap, path: &str, typ: &str) -> Result<Pointee> {
+    let type_info = types.get(typ).cloned();
+    let size = match type_info {
+        Some(ref t) => t.size,
+        None => match primitive_size(typ) {
+            Some(size) => size,
+            None => symbol::get_size(device, path)?,
+        },
+    };
+    let mut data = vec![0; size];
+    Handle::new(device, path)?.read(&mut data)?;
+    Ok(Pointee {
+        path: path.to_owned(),
+        typ: typ.to_owned(),
+        type_info,
+        data,
+    })
+}
+
+// Size of IEC 61131-3 elementary types, which are not listed in the type upload.
+fn primitive_size(typ: &str) -> Option<usize> {
+    let typ = typ.trim().to_ascii_uppercase();
+    let string_len = |prefix: &str| -> Option<usize> {
+        let rest = typ.strip_prefix(prefix)?;
+        if rest.is_empty() {
+            return Some(80);
+        }
+        rest.strip_prefix('(')?
+            .strip_suffix(')')?
+            .trim()
+            .parse()
+            .ok()
+    };
+    if let Some(len) = string_len("WSTRING") {
+        return Some((len + 1) * 2);
+    }
+    if let Some(len) = string_len("STRING") {
+        return Some(len + 1);
+    }
+    Some(match typ.as_str() {
+        "BOOL" | "BIT" | "BYTE" | "SINT" | "USINT" => 1,
+        "INT" | "UINT" | "WORD" | "WCHAR" => 2,
+        "DINT" | "UDINT" | "DWORD" | "REAL" | "TIME" | "DATE" | "TIME_OF_DAY" | "TOD"
+        | "DATE_AND_TIME" | "DT" => 4,
+        "LINT" | "ULINT" | "LWORD" | "LREAL" | "LTIME" => 8,
+        _ => return None,
+    })
+}
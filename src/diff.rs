@@ -0,0 +1,400 @@
+//! Comparison of symbol and type information, e.g. before and after an online change.
+//!
+//! ```rust,no_run
+//! use roboplc_io_ads::{diff, symbol};
+//! # fn example(device: &roboplc_io_ads::Device) -> roboplc::Result<()> {
+//! let (old_symbols, old_types) = symbol::get_symbol_info(device)?;
+//! // ... online change ...
+//! let (new_symbols, new_types) = symbol::get_symbol_info(device)?;
+//! let changes = diff::diff(&old_symbols, &old_types, &new_symbols, &new_types);
+//! print!("{}", changes);
+//! if changes.affects("MAIN.stDrive") {
+//!     // recreate mappings of MAIN.stDrive, check Rust structures
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Symbols and types are matched by name, case-insensitively (as IEC 61131-3 identifiers). Only
+//! properties which affect the data access are compared: comments, attributes and method infos
+//! are ignored.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use crate::symbol::{Field, Symbol, Type, TypeMap};
+
+/// Maximum nesting depth of types checked by [`Diff::affects`].
+const MAX_TYPE_DEPTH: usize = 32;
+
+/// The result of a comparison.
+#[derive(Clone, Debug, Default)]
+pub struct Diff {
+    /// Symbols which exist in the new set only.
+    pub added_symbols: Vec<Symbol>,
+    /// Symbols which exist in the old set only.
+    pub removed_symbols: Vec<Symbol>,
+    /// Symbols which have got their type, size, location or flags changed.
+    pub changed_symbols: Vec<SymbolChange>,
+    /// Types which exist in the new set only.
+    pub added_types: Vec<Type>,
+    /// Types which exist in the old set only.
+    pub removed_types: Vec<Type>,
+    /// Types which have got their layout changed.
+    pub changed_types: Vec<TypeChange>,
+    // upper-case names of symbols affected by the changes
+    affected: HashSet<String>,
+}
+
+/// A changed symbol.
+#[derive(Clone, Debug)]
+pub struct SymbolChange {
+    /// The symbol in the old set.
+    pub old: Symbol,
+    /// The symbol in the new set.
+    pub new: Symbol,
+}
+
+impl SymbolChange {
+    /// The type name has been changed.
+    pub fn type_changed(&self) -> bool {
+        !self.old.typ.eq_ignore_ascii_case(&self.new.typ)
+    }
+    /// The size has been changed.
+    pub fn size_changed(&self) -> bool {
+        self.old.size != self.new.size
+    }
+    /// The index group or the index offset has been changed.
+    pub fn location_changed(&self) -> bool {
+        (self.old.ix_group, self.old.ix_offset) != (self.new.ix_group, self.new.ix_offset)
+    }
+    /// The base type has been changed.
+    pub fn base_type_changed(&self) -> bool {
+        self.old.base_type != self.new.base_type
+    }
+    /// The flags have been changed.
+    pub fn flags_changed(&self) -> bool {
+        self.old.flags != self.new.flags
+    }
+}
+
+/// A type with a changed layout.
+#[derive(Clone, Debug)]
+pub struct TypeChange {
+    /// The type in the old set.
+    pub old: Type,
+    /// The type in the new set.
+    pub new: Type,
+    /// Changes of structure fields, in the order of the new type (removed fields follow).
+    pub fields: Vec<FieldChange>,
+}
+
+impl TypeChange {
+    /// The size has been changed.
+    pub fn size_changed(&self) -> bool {
+        self.old.size != self.new.size
+    }
+    /// The base type has been changed.
+    pub fn base_type_changed(&self) -> bool {
+        self.old.base_type != self.new.base_type
+    }
+    /// The array dimensions have been changed.
+    pub fn array_changed(&self) -> bool {
+        self.old.array != self.new.array
+    }
+    /// Enum values have been changed.
+    pub fn enums_changed(&self) -> bool {
+        self.old.enums != self.new.enums
+    }
+}
+
+/// A change of a structure field.
+#[derive(Clone, Debug)]
+pub enum FieldChange {
+    /// The field exists in the new type only.
+    Added(Field),
+    /// The field exists in the old type only.
+    Removed(Field),
+    /// The type, offset, size or array dimensions of the field have been changed.
+    Changed { old: Field, new: Field },
+}
+
+impl Diff {
+    /// Returns `true` if there are no changes.
+    pub fn is_empty(&self) -> bool {
+        self.added_symbols.is_empty()
+            && self.removed_symbols.is_empty()
+            && self.changed_symbols.is_empty()
+            && self.added_types.is_empty()
+            && self.removed_types.is_empty()
+            && self.changed_types.is_empty()
+    }
+
+    /// Check if access to a symbol is affected by the changes: the symbol has been removed or
+    /// changed, or its type or any of the types it contains (structure fields, array elements)
+    /// has been changed. If `true`, handles and mappings of the symbol must be recreated and the
+    /// Rust structures used to access the symbol must be verified.
+    ///
+    /// Added symbols and symbols which are not present in either set are considered unaffected.
+    pub fn affects(&self, symbol: &str) -> bool {
+        self.affected.contains(&symbol.to_ascii_uppercase())
+    }
+}
+
+/// Compare two sets of symbols and types (e.g. obtained with
+/// [`crate::symbol::get_symbol_info`]). Changes are reported in the order of the new set, removed
+/// symbols and types in the order of the old one (types are sorted by name).
+pub fn diff(
+    old_symbols: &[Symbol],
+    old_types: &TypeMap,
+    new_symbols: &[Symbol],
+    new_types: &TypeMap,
+) -> Diff {
+    let mut result = Diff::default();
+
+    let old_type_map = by_name(old_types.values(), |t| &t.name);
+    let new_type_map = by_name(new_types.values(), |t| &t.name);
+    for (key, new) in &new_type_map {
+        match old_type_map.get(key) {
+            None => result.added_types.push((*new).clone()),
+            Some(old) => {
+                if let Some(change) = compare_types(old, new) {
+                    result.changed_types.push(change);
+                }
+            }
+        }
+    }
+    for (key, old) in &old_type_map {
+        if !new_type_map.contains_key(key) {
+            result.removed_types.push((*old).clone());
+        }
+    }
+
+    let old_symbol_map = by_name(old_symbols.iter(), |s| &s.name);
+    let mut seen = HashSet::new();
+    for new in new_symbols {
+        let key = new.name.to_ascii_uppercase();
+        seen.insert(key.clone());
+        match old_symbol_map.get(&key) {
+            None => result.added_symbols.push(new.clone()),
+            Some(old) => {
+                let change = SymbolChange {
+                    old: (*old).clone(),
+                    new: new.clone(),
+                };
+                if change.type_changed()
+                    || change.size_changed()
+                    || change.location_changed()
+                    || change.base_type_changed()
+                    || change.flags_changed()
+                {
+                    result.affected.insert(key);
+                    result.changed_symbols.push(change);
+                }
+            }
+        }
+    }
+    for old in old_symbols {
+        let key = old.name.to_ascii_uppercase();
+        if !seen.contains(&key) {
+            result.affected.insert(key);
+            result.removed_symbols.push(old.clone());
+        }
+    }
+
+    // unchanged symbols are affected if their types have been changed
+    let changed_types = result
+        .changed_types
+        .iter()
+        .map(|c| c.new.name.to_ascii_uppercase())
+        .chain(
+            result
+                .removed_types
+                .iter()
+                .map(|t| t.name.to_ascii_uppercase()),
+        )
+        .collect::<HashSet<_>>();
+    if !changed_types.is_empty() {
+        for symbol in new_symbols {
+            let key = symbol.name.to_ascii_uppercase();
+            if old_symbol_map.contains_key(&key)
+                && type_affected(&symbol.typ, new_types, &changed_types, 0)
+            {
+                result.affected.insert(key);
+            }
+        }
+    }
+
+    result
+}
+
+fn by_name<'a, T: 'a>(
+    items: impl Iterator<Item = &'a T>,
+    name: impl Fn(&T) -> &str,
+) -> BTreeMap<String, &'a T> {
+    items.map(|i| (name(i).to_ascii_uppercase(), i)).collect()
+}
+
+fn compare_types(old: &Type, new: &Type) -> Option<TypeChange> {
+    let mut fields = Vec::new();
+    for new_field in &new.fields {
+        match old
+            .fields
+            .iter()
+            .find(|f| f.name.eq_ignore_ascii_case(&new_field.name))
+        {
+            None => fields.push(FieldChange::Added(new_field.clone())),
+            Some(old_field) => {
+                if !old_field.typ.eq_ignore_ascii_case(&new_field.typ)
+                    || old_field.offset != new_field.offset
+                    || old_field.size != new_field.size
+                    || old_field.array != new_field.array
+                {
+                    fields.push(FieldChange::Changed {
+                        old: old_field.clone(),
+                        new: new_field.clone(),
+                    });
+                }
+            }
+        }
+    }
+    for old_field in &old.fields {
+        if !new
+            .fields
+            .iter()
+            .any(|f| f.name.eq_ignore_ascii_case(&old_field.name))
+        {
+            fields.push(FieldChange::Removed(old_field.clone()));
+        }
+    }
+    let change = TypeChange {
+        old: old.clone(),
+        new: new.clone(),
+        fields,
+    };
+    if change.fields.is_empty()
+        && !change.size_changed()
+        && !change.base_type_changed()
+        && !change.array_changed()
+        && !change.enums_changed()
+    {
+        None
+    } else {
+        Some(change)
+    }
+}
+
+// Checks if a type or any of the types it contains has been changed.
+fn type_affected(typ: &str, types: &TypeMap, changed: &HashSet<String>, depth: usize) -> bool {
+    if depth > MAX_TYPE_DEPTH {
+        return false;
+    }
+    if changed.contains(&typ.to_ascii_uppercase()) {
+        return true;
+    }
+    if let Some((_, element_type)) = crate::query::array_info(typ, types) {
+        return type_affected(element_type, types, changed, depth + 1);
+    }
+    types.get(typ).map_or(false, |t| {
+        t.fields
+            .iter()
+            .any(|f| type_affected(&f.typ, types, changed, depth + 1))
+    })
+}
+
+impl fmt::Display for Diff {
+    /// Human-readable report, one change per line: `+` added, `-` removed, `~` changed.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for symbol in &self.added_symbols {
+            writeln!(f, "+ {} : {}", symbol.name, symbol.typ)?;
+        }
+        for symbol in &self.removed_symbols {
+            writeln!(f, "- {} : {}", symbol.name, symbol.typ)?;
+        }
+        for change in &self.changed_symbols {
+            let (old, new) = (&change.old, &change.new);
+            let mut details = Vec::new();
+            if change.type_changed() {
+                details.push(format!("type {} -> {}", old.typ, new.typ));
+            }
+            if change.size_changed() {
+                details.push(format!("size {} -> {}", old.size, new.size));
+            }
+            if change.location_changed() {
+                details.push(format!(
+                    "location 16#{:X}:{} -> 16#{:X}:{}",
+                    old.ix_group, old.ix_offset, new.ix_group, new.ix_offset
+                ));
+            }
+            if change.base_type_changed() {
+                details.push(format!("base type {} -> {}", old.base_type, new.base_type));
+            }
+            if change.flags_changed() {
+                details.push(format!("flags 16#{:X} -> 16#{:X}", old.flags, new.flags));
+            }
+            writeln!(f, "~ {}: {}", new.name, details.join(", "))?;
+        }
+        for typ in &self.added_types {
+            writeln!(f, "+ type {}", typ.name)?;
+        }
+        for typ in &self.removed_types {
+            writeln!(f, "- type {}", typ.name)?;
+        }
+        for change in &self.changed_types {
+            let (old, new) = (&change.old, &change.new);
+            let mut details = Vec::new();
+            if change.size_changed() {
+                details.push(format!("size {} -> {}", old.size, new.size));
+            }
+            if change.base_type_changed() {
+                details.push(format!("base type {} -> {}", old.base_type, new.base_type));
+            }
+            if change.array_changed() {
+                details.push("array dimensions".to_owned());
+            }
+            if change.enums_changed() {
+                details.push("enum values".to_owned());
+            }
+            if details.is_empty() {
+                writeln!(f, "~ type {}", new.name)?;
+            } else {
+                writeln!(f, "~ type {}: {}", new.name, details.join(", "))?;
+            }
+            for field in &change.fields {
+                match field {
+                    FieldChange::Added(field) => writeln!(
+                        f,
+                        "  + {} : {} @ {}",
+                        field.name,
+                        field.typ,
+                        FieldOffset(field.offset)
+                    )?,
+                    FieldChange::Removed(field) => {
+                        writeln!(f, "  - {} : {}", field.name, field.typ)?;
+                    }
+                    FieldChange::Changed { old, new } => writeln!(
+                        f,
+                        "  ~ {} : {} @ {} -> {} @ {}",
+                        new.name,
+                        old.typ,
+                        FieldOffset(old.offset),
+                        new.typ,
+                        FieldOffset(new.offset)
+                    )?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct FieldOffset(Option<u32>);
+
+impl fmt::Display for FieldOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(offset) => write!(f, "{}", offset),
+            None => f.write_str("?"),
+        }
+    }
+}
@@ -0,0 +1,285 @@
+//! PLC enum types.
+//!
+//! Enum values are transferred as plain integers. [`EnumType`] maps them to names at runtime,
+//! using the type information uploaded from the PLC (see [`crate::symbol::Type::enums`]), and
+//! can generate Rust enum definitions, so the enums can be used as typed values in the code.
+
+use std::collections::BTreeSet;
+use std::fmt::{self, Write as _};
+
+use roboplc::{Error, Result};
+
+use crate::symbol::{
+    Handle, Type, TypeMap, BASE_TYPE_INT16, BASE_TYPE_INT32, BASE_TYPE_INT64, BASE_TYPE_INT8,
+};
+
+/// A PLC enum type.
+#[derive(Clone, Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct EnumType {
+    name: String,
+    size: usize,
+    signed: bool,
+    values: Vec<(String, i64)>,
+}
+
+impl EnumType {
+    /// Create an enum type from the PLC type information. Returns `None` if the type is not an
+    /// enum or its size is not supported.
+    pub fn from_type(typ: &Type) -> Option<Self> {
+        if typ.enums.is_empty() || !matches!(typ.size, 1 | 2 | 4 | 8) {
+            return None;
+        }
+        Some(Self {
+            name: typ.name.clone(),
+            size: typ.size,
+            signed: matches!(
+                typ.base_type,
+                BASE_TYPE_INT8 | BASE_TYPE_INT16 | BASE_TYPE_INT32 | BASE_TYPE_INT64
+            ),
+            values: typ.enums.clone(),
+        })
+    }
+
+    /// Type name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Size of the underlying integer type, in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// All values of the enum, as (name, value).
+    pub fn values(&self) -> &[(String, i64)] {
+        &self.values
+    }
+
+    /// Get the name of a value. If several names share the value, the first one is returned.
+    pub fn name_of(&self, value: i64) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(_, v)| *v == value)
+            .map(|(n, _)| n.as_str())
+    }
+
+    /// Get the value of a name. The name can be either plain (`Running`) or qualified
+    /// (`E_State.Running`), and is case-insensitive.
+    pub fn value_of(&self, name: &str) -> Option<i64> {
+        let name = match name.split_once('.') {
+            Some((typ, name)) if typ.eq_ignore_ascii_case(&self.name) => name,
+            _ => name,
+        };
+        self.values
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| *v)
+    }
+
+    /// Create an enum value.
+    pub fn value(&self, value: i64) -> PlcEnum {
+        PlcEnum {
+            type_name: self.name.clone(),
+            name: self.name_of(value).map(ToOwned::to_owned),
+            value,
+        }
+    }
+
+    /// Decode a value from raw bytes.
+    pub fn decode(&self, data: &[u8]) -> Result<PlcEnum> {
+        if data.len() != self.size {
+            return Err(Error::invalid_data(format!(
+                "invalid {} data length: {}",
+                self.name,
+                data.len()
+            )));
+        }
+        let mut buf = [0; 8];
+        buf[..self.size].copy_from_slice(data);
+        let mut value = i64::from_le_bytes(buf);
+        if self.signed && self.size < 8 {
+            // sign-extend
+            let shift = 64 - self.size * 8;
+            value = (value << shift) >> shift;
+        }
+        Ok(self.value(value))
+    }
+
+    /// Encode a value into raw bytes. Values out of the underlying integer range are rejected.
+    pub fn encode(&self, value: i64) -> Result<Vec<u8>> {
+        let bits = self.size * 8;
+        let in_range = if bits == 64 {
+            true
+        } else if self.signed {
+            let limit = 1i64 << (bits - 1);
+            (-limit..limit).contains(&value)
+        } else {
+            (0..1i64 << bits).contains(&value)
+        };
+        if !in_range {
+            return Err(Error::invalid_data(format!(
+                "value {} is out of range for {}",
+                value, self.name
+            )));
+        }
+        Ok(value.to_le_bytes()[..self.size].to_vec())
+    }
+
+    /// Read a value using a symbol handle.
+    pub fn read(&self, handle: &Handle) -> Result<PlcEnum> {
+        let mut buf = [0; 8];
+        handle.read(&mut buf[..self.size])?;
+        self.decode(&buf[..self.size])
+    }
+
+    /// Write a value using a symbol handle.
+    pub fn write(&self, handle: &Handle, value: i64) -> Result<()> {
+        handle.write(&self.encode(value)?)
+    }
+
+    /// Write a value by its name (see [`EnumType::value_of`]) using a symbol handle.
+    pub fn write_name(&self, handle: &Handle, name: &str) -> Result<()> {
+        let value = self
+            .value_of(name)
+            .ok_or_else(|| Error::invalid_data(format!("{}: unknown value {}", self.name, name)))?;
+        self.write(handle, value)
+    }
+
+    /// Generate a Rust enum definition for the type.
+    ///
+    /// The generated enum can be read and written with binrw (the `binrw` attribute macro must be
+    /// in scope, e.g. with `use roboplc::prelude::*;`) and displays values as
+    /// `E_State.Running`. Names which share a value with a previous one are omitted.
+    pub fn to_rust(&self) -> String {
+        let ident = rust_ident(&self.name, true);
+        let repr = format!("{}{}", if self.signed { 'i' } else { 'u' }, self.size * 8);
+        let mut seen = BTreeSet::new();
+        let variants = self
+            .values
+            .iter()
+            .filter(|(_, value)| seen.insert(*value))
+            .map(|(name, value)| (rust_ident(name, false), name, value))
+            .collect::<Vec<_>>();
+        let mut out = String::new();
+        writeln!(out, "/// PLC enum `{}`", self.name).unwrap();
+        writeln!(out, "#[binrw]").unwrap();
+        writeln!(out, "#[brw(little, repr = {})]", repr).unwrap();
+        writeln!(out, "#[derive(Clone, Copy, Debug, PartialEq, Eq)]").unwrap();
+        writeln!(out, "#[repr({})]", repr).unwrap();
+        writeln!(out, "pub enum {} {{", ident).unwrap();
+        for (variant, _, value) in &variants {
+            if self.signed {
+                writeln!(out, "    {} = {},", variant, value).unwrap();
+            } else {
+                // unsigned 64-bit values above i64::MAX are stored wrapped
+                #[allow(clippy::cast_sign_loss)]
+                let value = **value as u64;
+                writeln!(out, "    {} = {},", variant, value).unwrap();
+            }
+        }
+        writeln!(out, "}}").unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "impl {} {{", ident).unwrap();
+        writeln!(out, "    /// Qualified PLC name of the value.").unwrap();
+        writeln!(out, "    pub fn plc_name(self) -> &'static str {{").unwrap();
+        writeln!(out, "        match self {{").unwrap();
+        for (variant, name, _) in &variants {
+            writeln!(
+                out,
+                "            {}::{} => \"{}.{}\",",
+                ident, variant, self.name, name
+            )
+            .unwrap();
+        }
+        writeln!(out, "        }}").unwrap();
+        writeln!(out, "    }}").unwrap();
+        writeln!(out, "}}").unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "impl std::fmt::Display for {} {{", ident).unwrap();
+        writeln!(
+            out,
+            "    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{"
+        )
+        .unwrap();
+        writeln!(out, "        f.write_str(self.plc_name())").unwrap();
+        writeln!(out, "    }}").unwrap();
+        writeln!(out, "}}").unwrap();
+        out
+    }
+}
+
+/// Generate Rust enum definitions for all enum types (see [`EnumType::to_rust`]), sorted by the
+/// type name.
+pub fn generate_rust(types: &TypeMap) -> String {
+    let mut enums = types
+        .values()
+        .filter_map(EnumType::from_type)
+        .collect::<Vec<_>>();
+    enums.sort_by(|a, b| a.name.cmp(&b.name));
+    enums
+        .iter()
+        .map(EnumType::to_rust)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A value of a PLC enum, mapped to its name at runtime.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlcEnum {
+    /// Enum type name.
+    pub type_name: String,
+    /// Value name, `None` if the value is not defined in the enum type.
+    pub name: Option<String>,
+    /// Raw value.
+    pub value: i64,
+}
+
+impl fmt::Display for PlcEnum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name {
+            Some(ref name) => write!(f, "{}.{}", self.type_name, name),
+            None => write!(f, "{}({})", self.type_name, self.value),
+        }
+    }
+}
+
+// Rust keywords (including reserved ones), which can not be used as plain identifiers.
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+// Convert a PLC identifier into a Rust one: type names are converted into CamelCase
+// (`E_State` -> `EState`), value names get their first letter capitalized. Keywords are escaped
+// as raw identifiers, the ones which can not be raw get a trailing underscore.
+fn rust_ident(name: &str, camel_case: bool) -> String {
+    let mut ident = String::with_capacity(name.len());
+    let mut capitalize = true;
+    for c in name.chars() {
+        if c == '_' && camel_case {
+            capitalize = true;
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            if capitalize {
+                ident.push(c.to_ascii_uppercase());
+                capitalize = false;
+            } else {
+                ident.push(c);
+            }
+        } else {
+            ident.push('_');
+        }
+    }
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, 'V');
+    }
+    if matches!(ident.as_str(), "_" | "crate" | "self" | "Self" | "super") {
+        ident.push('_');
+    } else if RUST_KEYWORDS.contains(&ident.as_str()) {
+        ident.insert_str(0, "r#");
+    }
+    ident
+}
@@ -0,0 +1,297 @@
+//! Export of symbol and type information to JSON and CSV.
+//!
+//! The output is deterministic (types are sorted by name, one symbol or type per line), so
+//! exported files of different PLC project versions can be compared with common diff tools.
+//!
+//! ```rust,no_run
+//! use roboplc_io_ads::{export, symbol};
+//! # fn example(device: &roboplc_io_ads::Device) -> roboplc::Result<()> {
+//! let (symbols, types) = symbol::get_symbol_info(device)?;
+//! std::fs::write("plc.json", export::to_json(&symbols, &types))?;
+//! std::fs::write("symbols.csv", export::symbols_to_csv(&symbols))?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt::Write as _;
+
+use crate::symbol::{Field, Method, MethodParam, Symbol, Type, TypeMap};
+
+/// Export symbols and types to a JSON document: `{"symbols": [...], "types": [...]}`.
+pub fn to_json(symbols: &[Symbol], types: &TypeMap) -> String {
+    let mut out = String::from("{\n  \"symbols\": [");
+    for (i, symbol) in symbols.iter().enumerate() {
+        out.push_str(if i == 0 { "\n    " } else { ",\n    " });
+        out.push_str(&symbol_to_json(symbol));
+    }
+    out.push_str(if symbols.is_empty() {
+        "],\n"
+    } else {
+        "\n  ],\n"
+    });
+    out.push_str("  \"types\": [");
+    for (i, typ) in sorted_types(types).into_iter().enumerate() {
+        out.push_str(if i == 0 { "\n    " } else { ",\n    " });
+        out.push_str(&type_to_json(typ));
+    }
+    out.push_str(if types.is_empty() {
+        "]\n}\n"
+    } else {
+        "\n  ]\n}\n"
+    });
+    out
+}
+
+/// Export a single symbol to a JSON object.
+pub fn symbol_to_json(symbol: &Symbol) -> String {
+    let mut out = String::from("{");
+    push_key(&mut out, "name", true);
+    push_str(&mut out, &symbol.name);
+    push_key(&mut out, "type", false);
+    push_str(&mut out, &symbol.typ);
+    let _ = write!(
+        out,
+        ",\"ix_group\":{},\"ix_offset\":{},\"size\":{},\"base_type\":{},\"flags\":{}",
+        symbol.ix_group, symbol.ix_offset, symbol.size, symbol.base_type, symbol.flags
+    );
+    push_key(&mut out, "comment", false);
+    push_str(&mut out, &symbol.comment);
+    push_key(&mut out, "attributes", false);
+    push_attributes(&mut out, &symbol.attributes);
+    out.push('}');
+    out
+}
+
+/// Export a single type to a JSON object.
+pub fn type_to_json(typ: &Type) -> String {
+    let mut out = String::from("{");
+    push_key(&mut out, "name", true);
+    push_str(&mut out, &typ.name);
+    let _ = write!(
+        out,
+        ",\"size\":{},\"base_type\":{},\"flags\":{}",
+        typ.size, typ.base_type, typ.flags
+    );
+    push_key(&mut out, "array", false);
+    push_array(&mut out, &typ.array);
+    push_key(&mut out, "fields", false);
+    push_list(&mut out, &typ.fields, push_field);
+    push_key(&mut out, "attributes", false);
+    push_attributes(&mut out, &typ.attributes);
+    push_key(&mut out, "enums", false);
+    push_list(&mut out, &typ.enums, |out, (name, value)| {
+        out.push('{');
+        push_key(out, "name", true);
+        push_str(out, name);
+        let _ = write!(out, ",\"value\":{}}}", value);
+    });
+    push_key(&mut out, "methods", false);
+    push_list(&mut out, &typ.methods, push_method);
+    out.push('}');
+    out
+}
+
+/// Export symbols to CSV (RFC 4180), with a header line. Attributes are exported as
+/// `name=value` pairs, separated by semicolons.
+pub fn symbols_to_csv(symbols: &[Symbol]) -> String {
+    let mut out =
+        String::from("name,type,ix_group,ix_offset,size,base_type,flags,comment,attributes\r\n");
+    for symbol in symbols {
+        push_csv_row(
+            &mut out,
+            &[
+                &symbol.name,
+                &symbol.typ,
+                &symbol.ix_group.to_string(),
+                &symbol.ix_offset.to_string(),
+                &symbol.size.to_string(),
+                &symbol.base_type.to_string(),
+                &symbol.flags.to_string(),
+                &symbol.comment,
+                &csv_attributes(&symbol.attributes),
+            ],
+        );
+    }
+    out
+}
+
+/// Export types to CSV (RFC 4180), with a header line. Structures are exported with one line per
+/// field, other types with a single line and empty field columns. Fields located outside of the
+/// structure have got an empty offset.
+pub fn types_to_csv(types: &TypeMap) -> String {
+    let mut out = String::from(
+        "type,size,base_type,flags,field,field_type,field_offset,field_size,field_base_type\r\n",
+    );
+    for typ in sorted_types(types) {
+        let type_columns = [
+            typ.name.clone(),
+            typ.size.to_string(),
+            typ.base_type.to_string(),
+            typ.flags.to_string(),
+        ];
+        if typ.fields.is_empty() {
+            let row = type_columns.iter().map(String::as_str).collect::<Vec<_>>();
+            push_csv_row(&mut out, &[&row[..], &[""; 5]].concat());
+            continue;
+        }
+        for field in &typ.fields {
+            let field_columns = [
+                field.name.clone(),
+                field.typ.clone(),
+                field.offset.map(|o| o.to_string()).unwrap_or_default(),
+                field.size.to_string(),
+                field.base_type.to_string(),
+            ];
+            let row = type_columns
+                .iter()
+                .chain(field_columns.iter())
+                .map(String::as_str)
+                .collect::<Vec<_>>();
+            push_csv_row(&mut out, &row);
+        }
+    }
+    out
+}
+
+fn sorted_types(types: &TypeMap) -> Vec<&Type> {
+    let mut sorted = types.values().collect::<Vec<_>>();
+    sorted.sort_by(|a, b| a.name.cmp(&b.name));
+    sorted
+}
+
+fn push_field(out: &mut String, field: &Field) {
+    out.push('{');
+    push_key(out, "name", true);
+    push_str(out, &field.name);
+    push_key(out, "type", false);
+    push_str(out, &field.typ);
+    out.push_str(",\"offset\":");
+    match field.offset {
+        Some(offset) => {
+            let _ = write!(out, "{}", offset);
+        }
+        None => out.push_str("null"),
+    }
+    let _ = write!(
+        out,
+        ",\"size\":{},\"base_type\":{},\"flags\":{}",
+        field.size, field.base_type, field.flags
+    );
+    push_key(out, "array", false);
+    push_array(out, &field.array);
+    out.push('}');
+}
+
+fn push_method(out: &mut String, method: &Method) {
+    out.push('{');
+    push_key(out, "name", true);
+    push_str(out, &method.name);
+    push_key(out, "return_type", false);
+    push_str(out, &method.return_type);
+    let _ = write!(
+        out,
+        ",\"return_size\":{},\"return_base_type\":{},\"flags\":{}",
+        method.return_size, method.return_base_type, method.flags
+    );
+    push_key(out, "comment", false);
+    push_str(out, &method.comment);
+    push_key(out, "params", false);
+    push_list(out, &method.params, push_param);
+    push_key(out, "attributes", false);
+    push_attributes(out, &method.attributes);
+    out.push('}');
+}
+
+fn push_param(out: &mut String, param: &MethodParam) {
+    out.push('{');
+    push_key(out, "name", true);
+    push_str(out, &param.name);
+    push_key(out, "type", false);
+    push_str(out, &param.typ);
+    let _ = write!(
+        out,
+        ",\"size\":{},\"base_type\":{},\"flags\":{}",
+        param.size, param.base_type, param.flags
+    );
+    push_key(out, "comment", false);
+    push_str(out, &param.comment);
+    out.push('}');
+}
+
+fn push_list<T>(out: &mut String, items: &[T], f: impl Fn(&mut String, &T)) {
+    out.push('[');
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        f(out, item);
+    }
+    out.push(']');
+}
+
+fn push_array(out: &mut String, dims: &[(i32, i32)]) {
+    push_list(out, dims, |out, (lower, upper)| {
+        let _ = write!(out, "[{},{}]", lower, upper);
+    });
+}
+
+fn push_attributes(out: &mut String, attributes: &[(String, String)]) {
+    push_list(out, attributes, |out, (name, value)| {
+        out.push('{');
+        push_key(out, "name", true);
+        push_str(out, name);
+        push_key(out, "value", false);
+        push_str(out, value);
+        out.push('}');
+    });
+}
+
+fn push_key(out: &mut String, key: &str, first: bool) {
+    if !first {
+        out.push(',');
+    }
+    push_str(out, key);
+    out.push(':');
+}
+
+fn push_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if u32::from(c) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn csv_attributes(attributes: &[(String, String)]) -> String {
+    attributes
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+fn push_csv_row(out: &mut String, columns: &[&str]) {
+    for (i, column) in columns.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        if column.contains([',', '"', '\r', '\n']) {
+            out.push('"');
+            out.push_str(&column.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(column);
+        }
+    }
+    out.push_str("\r\n");
+}
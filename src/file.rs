@@ -18,6 +18,8 @@ pub struct File {
 
 impl File {
     /// Open a file.  `flags` must be combined from the constants in this module.
+    ///
+    /// The file name is passed as is, use [`File::open_1252`] to open a file by a Rust string.
     pub fn open(device: &Device, filename: impl AsRef<[u8]>, flags: u32) -> Result<Self> {
         let mut hdl = [0; 4];
         device.write_read_exact(index::FILE_OPEN, flags, filename.as_ref(), &mut hdl)?;
@@ -27,18 +29,55 @@ impl File {
         })
     }
 
+    /// Open a file, the name is encoded in Windows-1252 (see [`crate::strings::encode_1252`]).
+    pub fn open_1252(device: &Device, filename: &str, flags: u32) -> Result<Self> {
+        Self::open(device, crate::strings::encode_1252(filename)?, flags)
+    }
+
     /// Delete a file.  `flags` must be combined from the constants in this module.
     pub fn delete(device: &Device, filename: impl AsRef<[u8]>, flags: u32) -> Result<()> {
         device
             .write_read(index::FILE_DELETE, flags, filename.as_ref(), &mut [])
             .map(drop)
     }
+
+    /// Delete a file, the name is encoded in Windows-1252 (see [`crate::strings::encode_1252`]).
+    pub fn delete_1252(device: &Device, filename: &str, flags: u32) -> Result<()> {
+        Self::delete(device, crate::strings::encode_1252(filename)?, flags)
+    }
+}
+
+/// A directory entry returned by [`read_dir`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DirEntry {
+    /// File name, decoded from Windows-1252.
+    pub name: String,
+    /// Raw file name, as returned by the device.
+    pub raw_name: Vec<u8>,
+    /// File attributes.
+    pub attributes: u32,
+    /// File size, in bytes.
+    pub size: u64,
+}
+
+/// Return a list of files in the named directory. The directory name is encoded in and the file
+/// names are decoded from Windows-1252.
+pub fn read_dir(device: &Device, dirname: &str) -> Result<Vec<DirEntry>> {
+    Ok(listdir(device, crate::strings::encode_1252(dirname)?)?
+        .into_iter()
+        .map(|(raw_name, attributes, size)| DirEntry {
+            name: crate::strings::decode_1252_lossy(&raw_name),
+            raw_name,
+            attributes,
+            size,
+        })
+        .collect())
 }
 
 /// Return a list of files in the named directory.
 ///
 /// Returned tuples are (name, attributes, size).  Returned filenames are not String
-/// since they are likely encoded in Windows-1252.
+/// since they are likely encoded in Windows-1252 (see [`read_dir`]).
 pub fn listdir(device: &Device, dirname: impl AsRef<[u8]>) -> Result<Vec<(Vec<u8>, u32, u64)>> {
     let mut files = Vec::new();
     let mut buf = [0; 324];
@@ -0,0 +1,318 @@
+//! Process images: many symbols read or written with a single round trip.
+//!
+//! Each [`crate::AdsMapping`] reads its symbol with a separate request. A process image
+//! registers many symbols and transfers all of them with one `SUMUP_READ_EX` (reading) or
+//! `SUMUP_WRITE` (writing) request of `RW_SYMVAL_BYHANDLE` sub-requests (at most
+//! [`crate::client::MAX_SUMUP_REQUESTS`] symbols per request, larger images are split).
+//!
+//! ```rust,no_run
+//! use roboplc_io_ads::AdsProcessImage;
+//! # fn example(device: &roboplc_io_ads::Device) -> roboplc::Result<()> {
+//! let mut image = AdsProcessImage::new(device);
+//! let speed = image.add_type::<f64>("MAIN.fSpeed")?;
+//! let counter = image.add_type::<u32>("MAIN.nCounter")?;
+//! let setpoint = image.add_type::<f64>("MAIN.fSetpoint")?;
+//! loop {
+//!     image.read()?;
+//!     let speed: f64 = image.get(speed)?;
+//!     let counter: u32 = image.get(counter)?;
+//!     image.set(setpoint, speed * 1.1)?;
+//!     image.write()?;
+//! #   break;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::Cursor;
+
+use roboplc::io::binrw::{BinRead, BinWrite};
+use roboplc::{Error, Result};
+
+use crate::client::{ReadRequest, WriteRequest, MAX_SUMUP_REQUESTS};
+use crate::errors::ads_error;
+use crate::layout::PlcLayout;
+use crate::symbol::HandleSet;
+use crate::{index, Device};
+
+/// A set of symbols, read and written with sum-up requests.
+///
+/// Symbol handles are created with a single sum-up request on the first access, re-created
+/// after reconnects and after online changes (if the symbol version is watched, see
+/// [`Device::watch_symbol_version`]), in the same way as for [`crate::AdsMapping`]. Symbols which
+/// have failed to resolve are retried after reconnects and online changes only.
+///
+/// Errors of individual symbols do not fail the whole transfer: they are reported by
+/// [`AdsProcessImage::get`] / [`AdsProcessImage::data`] after reading and by
+/// [`AdsProcessImage::write`] after writing.
+#[allow(clippy::module_name_repetitions)]
+pub struct AdsProcessImage {
+    device: Device,
+    entries: Vec<Entry>,
+    // the sets own the handles and release them with a single request each
+    handle_sets: Vec<HandleSet>,
+    session_id: usize,
+    generation: usize,
+}
+
+struct Entry {
+    symbol: String,
+    handle: Option<u32>,
+    // the handle has failed to resolve in the current session and generation
+    unresolved: bool,
+    buf: Vec<u8>,
+    status: Status,
+    // data to write, if set
+    pending: Option<Vec<u8>>,
+}
+
+enum Status {
+    // not read yet
+    Empty,
+    // the length of the data read
+    Ok(usize),
+    Ads(u32),
+    Failed(String),
+}
+
+impl AdsProcessImage {
+    /// Create a new empty process image.
+    pub fn new(device: &Device) -> Self {
+        Self {
+            device: device.clone(),
+            entries: Vec::new(),
+            handle_sets: Vec::new(),
+            session_id: 0,
+            generation: 0,
+        }
+    }
+
+    /// Register a symbol with the buffer size given (the buffer must fit the symbol data for
+    /// reading). Returns the index of the symbol in the image. Each symbol can be registered only
+    /// once.
+    pub fn add(&mut self, symbol: &str, buf_size: usize) -> Result<usize> {
+        if self.entries.iter().any(|e| e.symbol == symbol) {
+            return Err(Error::invalid_data(format!(
+                "symbol {} is already registered",
+                symbol
+            )));
+        }
+        self.entries.push(Entry {
+            symbol: symbol.to_owned(),
+            handle: None,
+            unresolved: false,
+            buf: vec![0; buf_size],
+            status: Status::Empty,
+            pending: None,
+        });
+        Ok(self.entries.len() - 1)
+    }
+
+    /// Register a symbol, the buffer is sized to fit `T`.
+    pub fn add_type<T: PlcLayout>(&mut self, symbol: &str) -> Result<usize> {
+        self.add(symbol, T::plc_size())
+    }
+
+    /// Number of symbols registered.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Return the name of a registered symbol.
+    pub fn symbol(&self, index: usize) -> Option<&str> {
+        self.entries.get(index).map(|e| e.symbol.as_str())
+    }
+
+    /// Read all symbols. Returns an error only if the transfer itself has failed.
+    pub fn read(&mut self) -> Result<()> {
+        self.ensure_handles()?;
+        let mut readable = Vec::with_capacity(self.entries.len());
+        for entry in &mut self.entries {
+            // entries without a handle keep the resolve error
+            if let Some(handle) = entry.handle {
+                readable.push((handle, entry));
+            }
+        }
+        for chunk in readable.chunks_mut(MAX_SUMUP_REQUESTS) {
+            let mut requests = chunk
+                .iter_mut()
+                .map(|(handle, entry)| {
+                    ReadRequest::new(index::RW_SYMVAL_BYHANDLE, *handle, &mut entry.buf)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            self.device.read_multi(&mut requests)?;
+            let statuses = requests
+                .iter()
+                .map(|request| match request.data() {
+                    Ok(data) => Status::Ok(data.len()),
+                    Err(_) => Status::Ads(request.result()),
+                })
+                .collect::<Vec<_>>();
+            drop(requests);
+            for ((_, entry), status) in chunk.iter_mut().zip(statuses) {
+                entry.status = status;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get raw data of a symbol, as read by the last [`AdsProcessImage::read`]. Returns the error
+    /// of the symbol if it has failed to resolve or to read.
+    pub fn data(&self, index: usize) -> Result<&[u8]> {
+        let entry = self.entry(index)?;
+        match entry.status {
+            Status::Ok(len) => entry
+                .buf
+                .get(..len)
+                .ok_or_else(|| Error::io("buffer overflow")),
+            Status::Empty => Err(Error::failed(format!("{}: not read", entry.symbol))),
+            Status::Ads(code) => Err(symbol_error(&entry.symbol, "process image read", code)),
+            Status::Failed(ref msg) => Err(Error::failed(format!("{}: {}", entry.symbol, msg))),
+        }
+    }
+
+    /// Decode a symbol value, as read by the last [`AdsProcessImage::read`] (see
+    /// [`AdsProcessImage::data`]).
+    pub fn get<T>(&self, index: usize) -> Result<T>
+    where
+        T: for<'a> BinRead<Args<'a> = ()>,
+    {
+        let mut c = Cursor::new(self.data(index)?);
+        Ok(T::read_le(&mut c)?)
+    }
+
+    /// Set raw data of a symbol, to be written by the next [`AdsProcessImage::write`].
+    pub fn set_data(&mut self, index: usize, data: &[u8]) -> Result<()> {
+        self.entry_mut(index)?.pending = Some(data.to_vec());
+        Ok(())
+    }
+
+    /// Set a symbol value, to be written by the next [`AdsProcessImage::write`].
+    pub fn set<T>(&mut self, index: usize, value: T) -> Result<()>
+    where
+        T: for<'a> BinWrite<Args<'a> = ()>,
+    {
+        let entry = self.entry_mut(index)?;
+        let mut c = Cursor::new(entry.pending.take().unwrap_or_default());
+        c.get_mut().clear();
+        value.write_le(&mut c)?;
+        entry.pending = Some(c.into_inner());
+        Ok(())
+    }
+
+    /// Write all symbols which have been set since the previous write. If some of the symbols
+    /// have failed to resolve or to write, the error lists all of them. The values are not
+    /// retained: if required, they must be set again before the next write.
+    pub fn write(&mut self) -> Result<()> {
+        if self.entries.iter().all(|e| e.pending.is_none()) {
+            return Ok(());
+        }
+        self.ensure_handles()?;
+        let mut errors = Vec::new();
+        let mut writable = Vec::with_capacity(self.entries.len());
+        for entry in &mut self.entries {
+            let Some(data) = entry.pending.take() else {
+                continue;
+            };
+            match (&entry.handle, &entry.status) {
+                (Some(handle), _) => writable.push((*handle, entry.symbol.as_str(), data)),
+                (None, Status::Failed(msg)) => errors.push(format!("{}: {}", entry.symbol, msg)),
+                (None, _) => errors.push(format!("{}: no handle", entry.symbol)),
+            }
+        }
+        for chunk in writable.chunks(MAX_SUMUP_REQUESTS) {
+            let mut requests = chunk
+                .iter()
+                .map(|(handle, _, data)| {
+                    WriteRequest::new(index::RW_SYMVAL_BYHANDLE, *handle, data)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            self.device.write_multi(&mut requests)?;
+            for ((_, symbol, _), request) in chunk.iter().zip(&requests) {
+                if request.ensure().is_err() {
+                    errors.push(
+                        symbol_error(symbol, "process image write", request.result()).to_string(),
+                    );
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::failed(errors.join("; ")))
+        }
+    }
+
+    fn entry(&self, index: usize) -> Result<&Entry> {
+        self.entries
+            .get(index)
+            .ok_or_else(|| Error::invalid_data(format!("invalid process image index: {}", index)))
+    }
+
+    fn entry_mut(&mut self, index: usize) -> Result<&mut Entry> {
+        self.entries
+            .get_mut(index)
+            .ok_or_else(|| Error::invalid_data(format!("invalid process image index: {}", index)))
+    }
+
+    // Create missing handles, re-create all handles if the session or the symbol version has
+    // been changed.
+    fn ensure_handles(&mut self) -> Result<()> {
+        let session_id = self.device.client.session_id();
+        if self.session_id != session_id {
+            self.device.refresh_symbol_version_watch()?;
+        }
+        let generation = self.device.symbol_generation();
+        if self.session_id != session_id || self.generation != generation {
+            for entry in &mut self.entries {
+                entry.handle = None;
+                entry.unresolved = false;
+            }
+            self.handle_sets.clear();
+            self.session_id = session_id;
+            self.generation = generation;
+        }
+        let missing = self
+            .entries
+            .iter()
+            .filter(|e| e.handle.is_none() && !e.unresolved)
+            .map(|e| e.symbol.as_str())
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let set = HandleSet::new(&self.device, &missing)?;
+        for (symbol, error) in set.failed() {
+            if let Some(entry) = self.entries.iter_mut().find(|e| &e.symbol == symbol) {
+                entry.status = Status::Failed(error.to_string());
+                entry.unresolved = true;
+            }
+        }
+        for (symbol, handle) in set.iter() {
+            if let Some(entry) = self.entries.iter_mut().find(|e| e.symbol == symbol) {
+                entry.handle = Some(handle);
+            }
+        }
+        if !set.is_empty() {
+            self.handle_sets.push(set);
+        }
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub(crate) fn handle_set_count(&self) -> usize {
+        self.handle_sets.len()
+    }
+}
+
+fn symbol_error(symbol: &str, action: &'static str, code: u32) -> Error {
+    match ads_error::<()>(action, code) {
+        Err(Error::API(msg, code)) => Error::API(format!("{}: {}", symbol, msg), code),
+        Err(e) => e,
+        Ok(()) => Error::failed(format!("{}: {}", symbol, action)),
+    }
+}
@@ -0,0 +1,440 @@
+//! Import of symbol and type information from TwinCAT project files, without a connected PLC.
+//!
+//! Supported are `.tmc` (TwinCAT 3 module class) and `.tpy` (TwinCAT 2 PLC project info) files.
+//! Both produce the same `(Vec<Symbol>, TypeMap)` as [`crate::symbol::get_symbol_info`], so
+//! symbols can be resolved and code can be generated offline:
+//!
+//! ```rust,no_run
+//! use roboplc_io_ads::{import, query::Query};
+//! # fn example() -> roboplc::Result<()> {
+//! let (symbols, types) = import::load("PLC/PLC.tmc")?;
+//! for symbol in Query::new().name("MAIN.*")?.run(&symbols, &types) {
+//!     println!("{} : {}", symbol.name, symbol.typ);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Limitations:
+//!
+//! * `.tpy` files contain ADS index groups and offsets of symbols. `.tmc` files contain offsets
+//!   in data areas only, the index groups are derived from the data area types (process image
+//!   inputs/outputs or PLC data), so the location should be verified with
+//!   [`crate::symbol::get_location`] before accessing a PLC by address.
+//! * Symbol and type flags are not stored in the files, only the attributes flags are set (as
+//!   well as the enum infos flag for enum types).
+//! * Method infos are not imported.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+use roboplc::{Error, Result};
+use xml::reader::{EventReader, XmlEvent};
+
+use crate::index;
+use crate::symbol::{
+    Field, Symbol, Type, TypeMap, BASE_TYPE_BIGTYPE, BASE_TYPE_BIT, BASE_TYPE_INT16,
+    BASE_TYPE_INT32, BASE_TYPE_INT64, BASE_TYPE_INT8, BASE_TYPE_REAL32, BASE_TYPE_REAL64,
+    BASE_TYPE_REAL80, BASE_TYPE_STRING, BASE_TYPE_UINT16, BASE_TYPE_UINT32, BASE_TYPE_UINT64,
+    BASE_TYPE_UINT8, BASE_TYPE_WSTRING, SYMBOL_FLAG_ATTRIBUTES, TYPE_FLAG_ATTRIBUTES,
+    TYPE_FLAG_ENUM_INFOS,
+};
+
+/// Maximum depth of type alias chains, to protect against recursive definitions.
+const MAX_ALIAS_DEPTH: usize = 32;
+
+/// Load a `.tmc` or `.tpy` file, the format is chosen by the file extension.
+pub fn load<P: AsRef<Path>>(path: P) -> Result<(Vec<Symbol>, TypeMap)> {
+    let path = path.as_ref();
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let read = match ext.as_str() {
+        "tmc" => read_tmc::<std::io::BufReader<std::fs::File>>,
+        "tpy" => read_tpy::<std::io::BufReader<std::fs::File>>,
+        _ => {
+            return Err(Error::invalid_data(format!(
+                "unsupported file type: {}",
+                path.display()
+            )))
+        }
+    };
+    let file = std::fs::File::open(path).map_err(Error::io)?;
+    read(std::io::BufReader::new(file))
+}
+
+/// Read a TwinCAT 3 `.tmc` file.
+pub fn read_tmc<R: Read>(reader: R) -> Result<(Vec<Symbol>, TypeMap)> {
+    let root = parse_xml(reader)?;
+    let types = read_types(&root)?;
+    let mut symbols = Vec::new();
+    for area in root.descendants("DataArea") {
+        let ix_group = area
+            .child("AreaNo")
+            .and_then(|a| a.attribute("AreaType"))
+            .map_or(index::PLC_RW_DB, area_index_group);
+        for element in area.children("Symbol") {
+            let bit_offset: u32 = element.parse("BitOffs")?.unwrap_or_default();
+            symbols.push(read_symbol(
+                element,
+                "BaseType",
+                ix_group,
+                bit_offset / 8,
+                &types,
+            )?);
+        }
+    }
+    Ok((symbols, types))
+}
+
+/// Read a TwinCAT 2 `.tpy` file.
+pub fn read_tpy<R: Read>(reader: R) -> Result<(Vec<Symbol>, TypeMap)> {
+    let root = parse_xml(reader)?;
+    let types = read_types(&root)?;
+    let mut symbols = Vec::new();
+    for element in root.children("Symbols").flat_map(|s| s.children("Symbol")) {
+        let ix_group = element.parse("IGroup")?.unwrap_or(index::PLC_RW_M);
+        let ix_offset = element.parse("IOffset")?.unwrap_or_default();
+        symbols.push(read_symbol(element, "Type", ix_group, ix_offset, &types)?);
+    }
+    Ok((symbols, types))
+}
+
+// Index groups of TwinCAT 3 data areas.
+fn area_index_group(area_type: &str) -> u32 {
+    match area_type {
+        "InputDst" | "InputSrc" => index::IO_RW_I,
+        "OutputSrc" | "OutputDst" => index::IO_RW_Q,
+        "MArea" => index::PLC_RW_M,
+        _ => index::PLC_RW_DB,
+    }
+}
+
+fn read_symbol(
+    element: &Element,
+    type_tag: &str,
+    ix_group: u32,
+    ix_offset: u32,
+    types: &TypeMap,
+) -> Result<Symbol> {
+    let name = element.required("Name")?.to_owned();
+    let element_type = element
+        .text(type_tag)
+        .or_else(|| element.text("Type"))
+        .unwrap_or_default();
+    let typ = type_name(element_type, &read_dims(element)?);
+    let attributes = read_properties(element);
+    Ok(Symbol {
+        ix_group,
+        ix_offset,
+        size: read_size(element)?,
+        base_type: resolve_base_type(&typ, types, &HashMap::new(), 0),
+        flags: if attributes.is_empty() {
+            0
+        } else {
+            SYMBOL_FLAG_ATTRIBUTES
+        },
+        comment: element.text("Comment").unwrap_or_default().to_owned(),
+        attributes,
+        name,
+        typ,
+    })
+}
+
+fn read_types(root: &Element) -> Result<TypeMap> {
+    // base types are resolved after all types are read, as types may refer to types defined
+    // later in the file
+    let mut types = HashMap::new();
+    let mut base_names = HashMap::new();
+    for element in root
+        .children("DataTypes")
+        .flat_map(|t| t.children("DataType"))
+    {
+        let name = element.required("Name")?.to_owned();
+        let mut fields = Vec::new();
+        for sub_item in element.children("SubItem") {
+            let dims = read_dims(sub_item)?;
+            let bit_offset: Option<u32> = sub_item.parse("BitOffs")?;
+            fields.push(Field {
+                name: sub_item.required("Name")?.to_owned(),
+                typ: type_name(sub_item.text("Type").unwrap_or_default(), &dims),
+                offset: bit_offset.map(|o| o / 8),
+                size: read_size(sub_item)?,
+                array: dims,
+                base_type: 0,
+                flags: 0,
+            });
+        }
+        let mut enums = Vec::new();
+        for info in element.children("EnumInfo") {
+            let value = info
+                .parse("Enum")?
+                .ok_or_else(|| Error::invalid_data(format!("{}: no enum value", name)))?;
+            enums.push((info.required("Text")?.to_owned(), value));
+        }
+        let attributes = read_properties(element);
+        let mut flags = 0;
+        if !attributes.is_empty() {
+            flags |= TYPE_FLAG_ATTRIBUTES;
+        }
+        if !enums.is_empty() {
+            flags |= TYPE_FLAG_ENUM_INFOS;
+        }
+        let base_name = if fields.is_empty() {
+            element
+                .text("BaseType")
+                .or_else(|| element.text("Type"))
+                .map(ToOwned::to_owned)
+        } else {
+            None
+        };
+        base_names.insert(name.clone(), base_name);
+        types.insert(
+            name.clone(),
+            Type {
+                name,
+                size: read_size(element)?,
+                array: read_dims(element)?,
+                fields,
+                base_type: BASE_TYPE_BIGTYPE,
+                flags,
+                attributes,
+                enums,
+                methods: Vec::new(),
+            },
+        );
+    }
+    let base_types = base_names
+        .iter()
+        .map(|(name, base_name)| {
+            let base_type = base_name.as_ref().map_or(BASE_TYPE_BIGTYPE, |base_name| {
+                resolve_base_type(base_name, &types, &base_names, 1)
+            });
+            (name.clone(), base_type)
+        })
+        .collect::<Vec<_>>();
+    for (name, base_type) in base_types {
+        if let Some(typ) = types.get_mut(&name) {
+            typ.base_type = base_type;
+        }
+    }
+    let field_base_types = types
+        .values()
+        .map(|t| {
+            let base_types = t
+                .fields
+                .iter()
+                .map(|f| resolve_base_type(&f.typ, &types, &HashMap::new(), 0))
+                .collect::<Vec<_>>();
+            (t.name.clone(), base_types)
+        })
+        .collect::<Vec<_>>();
+    for (name, base_types) in field_base_types {
+        if let Some(typ) = types.get_mut(&name) {
+            for (field, base_type) in typ.fields.iter_mut().zip(base_types) {
+                field.base_type = base_type;
+            }
+        }
+    }
+    Ok(types)
+}
+
+// Resolves the ADS base type of a type name: elementary types are recognized by their names,
+// arrays have got the base type of their elements, aliases and enums are resolved by their base
+// type names if not resolved yet.
+fn resolve_base_type(
+    typ: &str,
+    types: &TypeMap,
+    base_names: &HashMap<String, Option<String>>,
+    depth: usize,
+) -> u32 {
+    if depth > MAX_ALIAS_DEPTH {
+        return BASE_TYPE_BIGTYPE;
+    }
+    if let Some(base_type) = elementary_base_type(typ) {
+        return base_type;
+    }
+    if let Some((_, element_type)) = crate::query::array_info(typ, types) {
+        return resolve_base_type(element_type, types, base_names, depth + 1);
+    }
+    if let Some(Some(base_name)) = base_names.get(typ) {
+        return resolve_base_type(base_name, types, base_names, depth + 1);
+    }
+    types.get(typ).map_or(BASE_TYPE_BIGTYPE, |t| t.base_type)
+}
+
+fn elementary_base_type(typ: &str) -> Option<u32> {
+    let typ = typ.trim().to_ascii_uppercase();
+    if typ == "STRING" || typ.starts_with("STRING(") {
+        return Some(BASE_TYPE_STRING);
+    }
+    if typ == "WSTRING" || typ.starts_with("WSTRING(") {
+        return Some(BASE_TYPE_WSTRING);
+    }
+    let base_type = match typ.as_str() {
+        "BOOL" | "BIT" => BASE_TYPE_BIT,
+        "SINT" => BASE_TYPE_INT8,
+        "INT" => BASE_TYPE_INT16,
+        "DINT" => BASE_TYPE_INT32,
+        "LINT" => BASE_TYPE_INT64,
+        "USINT" | "BYTE" => BASE_TYPE_UINT8,
+        "UINT" | "WORD" | "WCHAR" => BASE_TYPE_UINT16,
+        "UDINT" | "DWORD" | "TIME" | "TIME_OF_DAY" | "TOD" | "DATE" | "DATE_AND_TIME" | "DT" => {
+            BASE_TYPE_UINT32
+        }
+        "ULINT" | "LWORD" | "LTIME" | "LDATE" | "LDATE_AND_TIME" | "LDT" => BASE_TYPE_UINT64,
+        "REAL" => BASE_TYPE_REAL32,
+        "LREAL" => BASE_TYPE_REAL64,
+        "REAL80" => BASE_TYPE_REAL80,
+        _ => return None,
+    };
+    Some(base_type)
+}
+
+// The ADS type name of an element with array infos, e.g. `ARRAY [1..3] OF REAL`.
+fn type_name(typ: &str, dims: &[(i32, i32)]) -> String {
+    if dims.is_empty() || typ.trim_start().to_ascii_uppercase().starts_with("ARRAY") {
+        return typ.to_owned();
+    }
+    let dims = dims
+        .iter()
+        .map(|(lower, upper)| format!("{}..{}", lower, upper))
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("ARRAY [{}] OF {}", dims, typ)
+}
+
+fn read_size(element: &Element) -> Result<usize> {
+    let bits: usize = element.parse("BitSize")?.unwrap_or_default();
+    Ok(bits.div_ceil(8))
+}
+
+fn read_dims(element: &Element) -> Result<Vec<(i32, i32)>> {
+    element
+        .children("ArrayInfo")
+        .map(|info| {
+            let lower: i32 = info.parse("LBound")?.unwrap_or_default();
+            let elements: i32 = info.parse("Elements")?.unwrap_or_default();
+            let upper = (elements >= 1)
+                .then(|| lower.checked_add(elements - 1))
+                .flatten()
+                .ok_or_else(|| {
+                    Error::invalid_data(format!(
+                        "invalid array bounds: {} elements from {}",
+                        elements, lower
+                    ))
+                })?;
+            Ok((lower, upper))
+        })
+        .collect()
+}
+
+fn read_properties(element: &Element) -> Vec<(String, String)> {
+    element
+        .children("Properties")
+        .flat_map(|p| p.children("Property"))
+        .filter_map(|p| {
+            Some((
+                p.text("Name")?.to_owned(),
+                p.text("Value").unwrap_or_default().to_owned(),
+            ))
+        })
+        .collect()
+}
+
+// A minimal XML tree, namespaces are ignored.
+#[derive(Default)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    fn descendants<'a>(&'a self, name: &'a str) -> Vec<&'a Element> {
+        let mut result = Vec::new();
+        for child in &self.children {
+            if child.name == name {
+                result.push(child);
+            } else {
+                result.extend(child.descendants(name));
+            }
+        }
+        result
+    }
+
+    // Trimmed text of a child element.
+    fn text(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|c| c.text.trim())
+    }
+
+    fn required(&self, name: &str) -> Result<&str> {
+        self.text(name)
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| Error::invalid_data(format!("{}: no {}", self.name, name)))
+    }
+
+    fn parse<T: FromStr>(&self, name: &str) -> Result<Option<T>> {
+        self.text(name)
+            .map(|t| {
+                t.parse().map_err(|_| {
+                    Error::invalid_data(format!("{}: invalid {}: {}", self.name, name, t))
+                })
+            })
+            .transpose()
+    }
+}
+
+fn parse_xml<R: Read>(reader: R) -> Result<Element> {
+    let mut stack: Vec<Element> = Vec::new();
+    for event in EventReader::new(reader) {
+        match event.map_err(Error::invalid_data)? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => stack.push(Element {
+                name: name.local_name,
+                attributes: attributes
+                    .into_iter()
+                    .map(|a| (a.name.local_name, a.value))
+                    .collect(),
+                ..Element::default()
+            }),
+            XmlEvent::EndElement { .. } => {
+                let element = stack
+                    .pop()
+                    .ok_or_else(|| Error::invalid_data("unexpected end of element"))?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => return Ok(element),
+                }
+            }
+            XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&text);
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(Error::invalid_data("no root element"))
+}
@@ -0,0 +1,300 @@
+//! Runtime validation of Rust type layouts against PLC types.
+//!
+//! A Rust structure which is read from or written to a PLC symbol must match the PLC type
+//! byte-to-byte, otherwise the data is either rejected with a "buffer overflow" error or, worse,
+//! silently decoded as garbage. The [`PlcLayout`] trait describes the layout of a Rust type and
+//! can be derived for structures (see [`macro@PlcLayout`]). The layout is then compared with
+//! the type information uploaded from the PLC.
+
+use std::fmt;
+
+use roboplc::{Error, Result};
+
+use crate::strings::{String as PlcString, WString};
+use crate::symbol::{Field, Symbol, Type, TypeMap};
+
+#[allow(clippy::module_name_repetitions)]
+pub use roboplc_io_ads_derive::PlcLayout;
+
+/// A single field of a [`PlcLayout`] type.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayoutField {
+    /// Name of the field in the PLC type.
+    pub name: &'static str,
+    /// Offset of the field in the structure, in bytes.
+    pub offset: usize,
+    /// Size of the field, in bytes.
+    pub size: usize,
+    /// Fields of the field type, empty if it is not a structure.
+    pub fields: Vec<LayoutField>,
+}
+
+/// Describes the memory layout of a type as it is stored in the PLC.
+#[allow(clippy::module_name_repetitions)]
+pub trait PlcLayout {
+    /// Total size of the type, in bytes.
+    fn plc_size() -> usize;
+    /// Fields of the type. Empty for non-structure types.
+    fn plc_fields() -> Vec<LayoutField> {
+        Vec::new()
+    }
+}
+
+macro_rules! impl_primitive_layout {
+    ($($t: ty),*) => {
+        $(
+            impl PlcLayout for $t {
+                fn plc_size() -> usize {
+                    std::mem::size_of::<$t>()
+                }
+            }
+        )*
+    };
+}
+
+impl_primitive_layout!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64);
+impl_primitive_layout!(
+    crate::types::Time,
+    crate::types::LTime,
+    crate::types::TimeOfDay,
+    crate::types::Date,
+    crate::types::DateAndTime,
+    crate::types::LDate,
+    crate::types::LDateAndTime
+);
+
+impl PlcLayout for bool {
+    fn plc_size() -> usize {
+        1
+    }
+}
+
+impl<T: PlcLayout, const N: usize> PlcLayout for [T; N] {
+    fn plc_size() -> usize {
+        T::plc_size() * N
+    }
+}
+
+impl<const LEN: usize> PlcLayout for PlcString<LEN> {
+    fn plc_size() -> usize {
+        LEN + 1
+    }
+}
+
+impl<const LEN: usize> PlcLayout for WString<LEN> {
+    fn plc_size() -> usize {
+        (LEN + 1) * 2
+    }
+}
+
+/// A single difference between a Rust layout and a PLC type.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LayoutDiff {
+    /// Total sizes differ.
+    Size {
+        /// Size of the Rust type.
+        rust: usize,
+        /// Size of the PLC type.
+        plc: usize,
+    },
+    /// The field is defined in the Rust type only.
+    MissingInPlc {
+        /// Field name.
+        field: String,
+    },
+    /// The field is defined in the PLC type only.
+    MissingInRust {
+        /// Field name.
+        field: String,
+    },
+    /// Field offsets differ. The PLC offset is `None` if the field is not located inline in the
+    /// structure (e.g. `AT %M*`).
+    Offset {
+        /// Field name.
+        field: String,
+        /// Offset in the Rust type.
+        rust: usize,
+        /// Offset in the PLC type.
+        plc: Option<u32>,
+    },
+    /// Field sizes differ.
+    FieldSize {
+        /// Field name.
+        field: String,
+        /// Size in the Rust type.
+        rust: usize,
+        /// Size in the PLC type.
+        plc: usize,
+    },
+}
+
+impl fmt::Display for LayoutDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutDiff::Size { rust, plc } => write!(f, "size: rust {}, plc {}", rust, plc),
+            LayoutDiff::MissingInPlc { field } => write!(f, "{}: missing in the PLC type", field),
+            LayoutDiff::MissingInRust { field } => {
+                write!(f, "{}: missing in the Rust type", field)
+            }
+            LayoutDiff::Offset { field, rust, plc } => match plc {
+                Some(plc) => write!(f, "{}: offset rust {}, plc {}", field, rust, plc),
+                None => write!(f, "{}: offset rust {}, plc not inline", field, rust),
+            },
+            LayoutDiff::FieldSize { field, rust, plc } => {
+                write!(f, "{}: size rust {}, plc {}", field, rust, plc)
+            }
+        }
+    }
+}
+
+/// Compare the layout of `T` with a PLC type and return all differences found.
+///
+/// Fields of nested structures are compared by size only, use [`diff_with_types`] to compare them
+/// recursively.
+pub fn diff<T: PlcLayout>(typ: &Type) -> Vec<LayoutDiff> {
+    diff_fields::<T>(typ.size, &typ.fields, None)
+}
+
+/// Compare the layout of `T` with a PLC type and return all differences found, comparing fields of
+/// nested structures with their types from `types`. Differences of nested fields are reported
+/// with full paths (e.g. `stDrive.fSpeed`).
+pub fn diff_with_types<T: PlcLayout>(typ: &Type, types: &TypeMap) -> Vec<LayoutDiff> {
+    diff_fields::<T>(typ.size, &typ.fields, Some(types))
+}
+
+/// Verify that the layout of `T` matches a PLC type.
+///
+/// Returns an error which lists all differences found.
+pub fn verify<T: PlcLayout>(typ: &Type) -> Result<()> {
+    to_result(&typ.name, &diff::<T>(typ))
+}
+
+/// Verify that the layout of `T` matches a PLC symbol, using the symbol and type information
+/// previously obtained with [`crate::symbol::get_symbol_info`].
+///
+/// The symbol can be either a top-level one or a member of a structure (e.g. `MAIN.st.inner`).
+pub fn verify_symbol<T: PlcLayout>(
+    symbols: &[Symbol],
+    types: &TypeMap,
+    symbol: &str,
+) -> Result<()> {
+    let (size, fields) = find_symbol_layout(symbols, types, symbol)
+        .ok_or_else(|| Error::invalid_data(format!("symbol not found: {}", symbol)))?;
+    to_result(symbol, &diff_fields::<T>(size, fields, Some(types)))
+}
+
+fn to_result(name: &str, diffs: &[LayoutDiff]) -> Result<()> {
+    if diffs.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::invalid_data(format!(
+            "layout mismatch for {}: {}",
+            name,
+            diffs
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("; ")
+        )))
+    }
+}
+
+fn diff_fields<T: PlcLayout>(
+    plc_size: usize,
+    plc_fields: &[Field],
+    types: Option<&TypeMap>,
+) -> Vec<LayoutDiff> {
+    let mut diffs = Vec::new();
+    let rust_size = T::plc_size();
+    if rust_size != plc_size {
+        diffs.push(LayoutDiff::Size {
+            rust: rust_size,
+            plc: plc_size,
+        });
+    }
+    // A primitive or an opaque type has got no fields, only the size is compared.
+    diff_layout_fields(&T::plc_fields(), plc_fields, types, "", &mut diffs);
+    diffs
+}
+
+fn diff_layout_fields(
+    rust_fields: &[LayoutField],
+    plc_fields: &[Field],
+    types: Option<&TypeMap>,
+    prefix: &str,
+    diffs: &mut Vec<LayoutDiff>,
+) {
+    if rust_fields.is_empty() {
+        return;
+    }
+    let path = |name: &str| format!("{}{}", prefix, name);
+    for rust_field in rust_fields {
+        let Some(plc_field) = plc_fields
+            .iter()
+            .find(|f| f.name.eq_ignore_ascii_case(rust_field.name))
+        else {
+            diffs.push(LayoutDiff::MissingInPlc {
+                field: path(rust_field.name),
+            });
+            continue;
+        };
+        if plc_field.offset.map(|o| o as usize) != Some(rust_field.offset) {
+            diffs.push(LayoutDiff::Offset {
+                field: path(rust_field.name),
+                rust: rust_field.offset,
+                plc: plc_field.offset,
+            });
+        }
+        if plc_field.size != rust_field.size {
+            diffs.push(LayoutDiff::FieldSize {
+                field: path(rust_field.name),
+                rust: rust_field.size,
+                plc: plc_field.size,
+            });
+        }
+        if let Some(typ) = types.and_then(|types| types.get(&plc_field.typ)) {
+            diff_layout_fields(
+                &rust_field.fields,
+                &typ.fields,
+                types,
+                &format!("{}.", path(rust_field.name)),
+                diffs,
+            );
+        }
+    }
+    for plc_field in plc_fields {
+        if !rust_fields
+            .iter()
+            .any(|f| f.name.eq_ignore_ascii_case(&plc_field.name))
+        {
+            diffs.push(LayoutDiff::MissingInRust {
+                field: path(&plc_field.name),
+            });
+        }
+    }
+}
+
+// Find the size and the fields of a symbol, descending into structure members if required.
+fn find_symbol_layout<'a>(
+    symbols: &'a [Symbol],
+    types: &'a TypeMap,
+    path: &str,
+) -> Option<(usize, &'a [Field])> {
+    if let Some(symbol) = symbols.iter().find(|s| s.name.eq_ignore_ascii_case(path)) {
+        let fields = types
+            .get(&symbol.typ)
+            .map_or(&[][..], |t| t.fields.as_slice());
+        return Some((symbol.size, fields));
+    }
+    let (parent, member) = path.rsplit_once('.')?;
+    let (_, parent_fields) = find_symbol_layout(symbols, types, parent)?;
+    let field = parent_fields
+        .iter()
+        .find(|f| f.name.eq_ignore_ascii_case(member))?;
+    let fields = types
+        .get(&field.typ)
+        .map_or(&[][..], |t| t.fields.as_slice());
+    Some((field.size, fields))
+}
@@ -1,23 +1,41 @@
 #![ doc = include_str!( concat!( env!( "CARGO_MANIFEST_DIR" ), "/", "README.md" ) ) ]
+extern crate self as roboplc_io_ads;
+
+pub mod array;
+pub mod bits;
 pub mod client;
+pub mod deref;
+pub mod diff;
+pub mod enums;
 pub mod errors;
+pub mod export;
 pub mod file;
+pub mod image;
+pub mod import;
 pub mod index;
+pub mod layout;
 pub mod mapping;
 pub mod netid;
 pub mod notif;
 pub mod ports;
+pub mod query;
+pub mod rpc;
 pub mod strings;
 pub mod symbol;
+pub mod symcache;
 #[cfg(test)]
 mod test;
+pub mod types;
 pub mod udp;
+pub mod value;
+pub mod vars;
 
-pub use client::{AdsState, Client, Device, Reader, Source};
+pub use client::{AdsState, Client, Device, Reader, Source, SymbolVersionEvent};
 pub use file::File;
-pub use mapping::AdsMapping;
+pub use image::AdsProcessImage;
+pub use mapping::{AdsCachedMapping, AdsIndexMapping, AdsMapping, AdsMappingOptions};
 pub use netid::{AmsAddr, AmsNetId, AmsPort};
-pub use symbol::Handle;
+pub use symbol::{Handle, HandleSet};
 
 /// The default port for TCP communication.
 pub const PORT: u16 = 0xBF02;
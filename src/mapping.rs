@@ -1,10 +1,69 @@
 use std::io::Cursor;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use roboplc::io::binrw::BinWrite;
+use roboplc::io::binrw::{BinWrite, Endian};
 use roboplc::io::{binrw::BinRead, IoMapping};
 use roboplc::{Error, Result};
 
-use crate::{Device, Handle};
+use bma_ts::Timestamp;
+
+use crate::client::{CachedSample, SampleSlot, WriteRequest, MAX_SUMUP_REQUESTS};
+use crate::layout::PlcLayout;
+use crate::notif::{self, Attributes, TransmissionMode};
+use crate::{index, Device, Handle, HandleSet};
+
+/// Mapping options for [`AdsMapping`]
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug)]
+pub struct AdsMappingOptions {
+    range: Option<(u32, usize)>,
+    endian: Endian,
+    verify: bool,
+    fallback_by_name: bool,
+}
+
+impl AdsMappingOptions {
+    /// Creates new default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Access a part of the symbol only, starting at the byte `offset` and `len` bytes long. The
+    /// part is accessed by the symbol location (see [`crate::symbol::get_location`]), no handle is
+    /// created.
+    pub fn range(mut self, offset: u32, len: usize) -> Self {
+        self.range = Some((offset, len));
+        self
+    }
+    /// Byte order of the data (little-endian by default, as used by TwinCAT)
+    pub fn endian(mut self, endian: Endian) -> Self {
+        self.endian = endian;
+        self
+    }
+    /// Read the data back after each write and compare it with the data written
+    pub fn verify(mut self, value: bool) -> Self {
+        self.verify = value;
+        self
+    }
+    /// If handles can not be created (e.g. are not allowed by the server), read the symbol with
+    /// `GET_SYMVAL_BYNAME` and write it by the symbol location
+    pub fn fallback_by_name(mut self, value: bool) -> Self {
+        self.fallback_by_name = value;
+        self
+    }
+}
+
+impl Default for AdsMappingOptions {
+    fn default() -> Self {
+        Self {
+            range: None,
+            endian: Endian::Little,
+            verify: false,
+            fallback_by_name: false,
+        }
+    }
+}
 
 #[allow(clippy::module_name_repetitions)]
 pub struct AdsMapping {
@@ -12,7 +71,44 @@ pub struct AdsMapping {
     buf: Vec<u8>,
     symbol: String,
     session_id: usize,
-    handle: Option<Handle>,
+    generation: usize,
+    handle: Option<SymbolHandle>,
+    // the symbol location, resolved when required
+    location: Option<(u32, u32)>,
+    // handles have failed to be created in the current session, the fallback is used
+    by_name: bool,
+    options: AdsMappingOptions,
+    change_only: Option<ChangeOnly>,
+    auto_size: bool,
+    // the symbol size, queried when auto-sizing
+    symbol_size: Option<usize>,
+}
+
+// The symbol handle, either an own one or one of a handle set shared with other mappings, which
+// releases all its handles with a single request when the last mapping is dropped
+enum SymbolHandle {
+    Own(Handle),
+    Shared {
+        // keeps the set alive
+        _set: Arc<HandleSet>,
+        handle: u32,
+    },
+}
+
+impl SymbolHandle {
+    fn raw(&self) -> u32 {
+        match self {
+            SymbolHandle::Own(handle) => handle.raw(),
+            SymbolHandle::Shared { handle, .. } => *handle,
+        }
+    }
+}
+
+// Change-only writes state
+struct ChangeOnly {
+    max_ranges: usize,
+    // the last image read or written
+    image: Option<Vec<u8>>,
 }
 
 impl AdsMapping {
@@ -25,30 +121,388 @@ impl AdsMapping {
             buf: vec![0; buf_size],
             symbol: symbol.to_owned(),
             session_id: 0,
+            generation: 0,
             handle: None,
+            location: None,
+            by_name: false,
+            options: <_>::default(),
+            change_only: None,
+            auto_size: false,
+            symbol_size: None,
+        }
+    }
+    /// Creates a mapping with the buffer sized automatically: the symbol size is queried (see
+    /// [`crate::symbol::get_size`]) on the first access and after reconnects and online changes.
+    ///
+    /// Reading or writing a type which size does not match the symbol size (or the range length,
+    /// if set in the options) returns an error.
+    pub fn new_auto_sized(device: &Device, symbol: &str) -> Self {
+        let mut mapping = Self::new(device, symbol, 0);
+        mapping.auto_size = true;
+        mapping
+    }
+    /// Sets options for the mapping. If a range is set, the buffer is extended to fit it if
+    /// required.
+    pub fn with_options(mut self, options: AdsMappingOptions) -> Self {
+        if let Some((_, len)) = options.range {
+            if self.buf.len() < len {
+                self.buf.resize(len, 0);
+            }
         }
+        self.options = options;
+        self
+    }
+    /// Enables change-only writes: the data written is compared with the last image read or
+    /// written and only the changed byte ranges are sent (with a single `SUMUP_WRITE` request
+    /// if there are several ones), so fields modified by the PLC in the meantime are not
+    /// overwritten. If nothing has been changed, no request is sent.
+    ///
+    /// The ranges are written by the symbol location (see [`crate::symbol::get_location`]),
+    /// which is resolved on the first change-only write. If there is no previous image, the data
+    /// size differs from it or there are more than `max_ranges` changed ranges, the full data is
+    /// written.
+    pub fn with_change_only_writes(mut self, max_ranges: usize) -> Self {
+        self.change_only = Some(ChangeOnly {
+            max_ranges: max_ranges.min(MAX_SUMUP_REQUESTS),
+            image: None,
+        });
+        self
+    }
+    /// Creates a mapping after verifying that the layout of `T` matches the PLC symbol type (see
+    /// [`Device::verify_layout`]). The buffer is sized to fit `T`.
+    pub fn new_verified<T: PlcLayout>(device: &Device, symbol: &str) -> Result<Self> {
+        device.verify_layout::<T>(symbol)?;
+        Ok(Self::new(device, symbol, T::plc_size()))
+    }
+    /// Use a handle of a handle set, returns false if the symbol has not been resolved by the set.
+    pub(crate) fn set_shared_handle(&mut self, set: &Arc<HandleSet>) -> bool {
+        let Some(handle) = set.get(&self.symbol) else {
+            return false;
+        };
+        self.reset();
+        self.session_id = set.session_id();
+        self.generation = set.generation();
+        self.handle = Some(SymbolHandle::Shared {
+            _set: set.clone(),
+            handle,
+        });
+        true
     }
-    fn get_handle(&mut self) -> Result<&Handle> {
+    fn reset(&mut self) {
+        self.handle = None;
+        self.location = None;
+        self.by_name = false;
+        self.symbol_size = None;
+        if let Some(ref mut change_only) = self.change_only {
+            change_only.image = None;
+        }
+    }
+    // Drop the handle and the location if the session or the symbol version has been changed
+    fn refresh(&mut self) -> Result<()> {
         let session_id = self.device.client.session_id();
-        if self.handle.is_none() || self.session_id != session_id {
-            self.handle = Some(Handle::new(&self.device, &self.symbol)?);
+        if self.session_id != session_id {
+            self.device.refresh_symbol_version_watch()?;
+        }
+        let generation = self.device.symbol_generation();
+        if self.session_id != session_id || self.generation != generation {
+            self.reset();
             self.session_id = session_id;
+            self.generation = generation;
+        }
+        Ok(())
+    }
+    fn get_handle(&mut self) -> Result<u32> {
+        self.refresh()?;
+        if self.handle.is_none() {
+            self.handle = Some(SymbolHandle::Own(Handle::new(&self.device, &self.symbol)?));
+        }
+        Ok(self.handle.as_ref().unwrap().raw())
+    }
+    // Returns the size the data must have if auto-sizing, the buffer is extended to fit it
+    fn expected_size(&mut self) -> Result<Option<usize>> {
+        if !self.auto_size {
+            return Ok(None);
+        }
+        self.refresh()?;
+        if let Some((_, len)) = self.options.range {
+            return Ok(Some(len));
+        }
+        if let Some(size) = self.symbol_size {
+            return Ok(Some(size));
+        }
+        let size = crate::symbol::get_size(&self.device, &self.symbol)?;
+        if self.buf.len() < size {
+            self.buf.resize(size, 0);
+        }
+        self.symbol_size = Some(size);
+        Ok(Some(size))
+    }
+    // Returns the handle, None if the fallback must be used
+    fn get_handle_or_fallback(&mut self) -> Result<Option<u32>> {
+        self.refresh()?;
+        if self.by_name {
+            return Ok(None);
+        }
+        match self.get_handle() {
+            Ok(handle) => Ok(Some(handle)),
+            Err(error) if self.options.fallback_by_name => {
+                tracing::debug!(symbol = self.symbol, %error, "using the symbol name fallback");
+                self.by_name = true;
+                Ok(None)
+            }
+            Err(error) => Err(error),
+        }
+    }
+    // Returns the location of the data (considering the range)
+    fn get_location(&mut self) -> Result<(u32, u32)> {
+        self.refresh()?;
+        let (index_group, index_offset) = if let Some(location) = self.location {
+            location
+        } else {
+            let location = crate::symbol::get_location(&self.device, &self.symbol)?;
+            self.location = Some(location);
+            location
+        };
+        Ok((
+            index_group,
+            index_offset + self.options.range.map_or(0, |(offset, _)| offset),
+        ))
+    }
+    fn read_data(&mut self) -> Result<usize> {
+        let len = if let Some((_, len)) = self.options.range {
+            let (index_group, index_offset) = self.get_location()?;
+            self.device
+                .read(index_group, index_offset, &mut self.buf[..len])?
+        } else if let Some(handle_id) = self.get_handle_or_fallback()? {
+            self.device
+                .read(index::RW_SYMVAL_BYHANDLE, handle_id, &mut self.buf)?
+        } else {
+            self.device.write_read(
+                index::GET_SYMVAL_BYNAME,
+                0,
+                self.symbol.as_bytes(),
+                &mut self.buf,
+            )?
+        };
+        if len > self.buf.len() {
+            return Err(Error::io("buffer overflow"));
+        }
+        Ok(len)
+    }
+    fn write_data(&mut self, len: usize) -> Result<()> {
+        if let Some((_, range_len)) = self.options.range {
+            if len > range_len {
+                return Err(Error::invalid_data(format!(
+                    "{}: the data is larger than the range ({} > {})",
+                    self.symbol, len, range_len
+                )));
+            }
+        } else if let Some(handle_id) = self.get_handle_or_fallback()? {
+            return self
+                .device
+                .write(index::RW_SYMVAL_BYHANDLE, handle_id, &self.buf[..len]);
+        }
+        let (index_group, index_offset) = self.get_location()?;
+        self.device
+            .write(index_group, index_offset, &self.buf[..len])
+    }
+    // Returns false if the data must be written in full
+    fn write_changes(&mut self, len: usize) -> Result<bool> {
+        let Some(max_ranges) = self.change_only.as_ref().map(|c| c.max_ranges) else {
+            return Ok(false);
+        };
+        let ranges = {
+            let Some(image) = self.change_only.as_ref().and_then(|c| c.image.as_ref()) else {
+                return Ok(false);
+            };
+            if image.len() != len {
+                return Ok(false);
+            }
+            changed_ranges(image, &self.buf[..len])
+        };
+        if ranges.len() > max_ranges {
+            return Ok(false);
+        }
+        if !ranges.is_empty() {
+            let (index_group, index_offset) = self.get_location()?;
+            let data = &self.buf[..len];
+            let offset = |range: &Range<usize>| -> Result<u32> {
+                Ok(index_offset + u32::try_from(range.start).map_err(Error::invalid_data)?)
+            };
+            let result = if let [range] = &ranges[..] {
+                self.device
+                    .write(index_group, offset(range)?, &data[range.clone()])
+            } else {
+                let mut requests = ranges
+                    .iter()
+                    .map(|range| {
+                        WriteRequest::new(index_group, offset(range)?, &data[range.clone()])
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                self.device
+                    .write_multi(&mut requests)
+                    .and_then(|()| requests.iter().try_for_each(WriteRequest::ensure))
+            };
+            if let Err(e) = result {
+                // the PLC data is unknown
+                self.set_image(None);
+                return Err(e);
+            }
+        }
+        self.set_image(Some(len));
+        Ok(true)
+    }
+    fn set_image(&mut self, len: Option<usize>) {
+        if let Some(ref mut change_only) = self.change_only {
+            change_only.image = len.map(|len| self.buf[..len].to_vec());
+        }
+    }
+    fn verify_written(&mut self, written: &[u8]) -> Result<()> {
+        let len = self.read_data()?;
+        if self.buf[..len].starts_with(written) {
+            Ok(())
+        } else {
+            Err(Error::failed(format!(
+                "{}: the data read back differs from the data written",
+                self.symbol
+            )))
         }
-        Ok(self.handle.as_ref().unwrap())
     }
 }
 
+// Byte ranges which differ, the slices must be of the same length
+fn changed_ranges(old: &[u8], new: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < new.len() {
+        if old[i] == new[i] {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < new.len() && old[i] != new[i] {
+            i += 1;
+        }
+        ranges.push(start..i);
+    }
+    ranges
+}
+
 impl IoMapping for AdsMapping {
+    type Options = AdsMappingOptions;
+
+    fn read<T>(&mut self) -> Result<T>
+    where
+        T: for<'a> BinRead<Args<'a> = ()>,
+    {
+        let expected = self.expected_size()?;
+        let len = self.read_data()?;
+        self.set_image(Some(len));
+        let mut c = Cursor::new(&self.buf[..len]);
+        let res = T::read_options(&mut c, self.options.endian, ());
+        if let Some(size) = expected {
+            match res {
+                Err(e) if e.is_eof() => {
+                    return Err(Error::invalid_data(format!(
+                        "{}: the type requires more bytes than the symbol holds ({})",
+                        self.symbol, size
+                    )));
+                }
+                Ok(_) if c.position() != size as u64 => {
+                    return Err(Error::invalid_data(format!(
+                        "{}: the type has read {} bytes, the symbol holds {}",
+                        self.symbol,
+                        c.position(),
+                        size
+                    )));
+                }
+                _ => {}
+            }
+        }
+        Ok(res?)
+    }
+
+    fn write<T>(&mut self, value: T) -> Result<()>
+    where
+        T: for<'a> BinWrite<Args<'a> = ()>,
+    {
+        self.refresh()?;
+        let expected = self.expected_size()?;
+        let mut c = Cursor::new(&mut self.buf);
+        value.write_options(&mut c, self.options.endian, ())?;
+        let pos = usize::try_from(c.position()).map_err(Error::invalid_data)?;
+        if let Some(size) = expected {
+            if pos != size {
+                return Err(Error::invalid_data(format!(
+                    "{}: the type has written {} bytes, the symbol holds {}",
+                    self.symbol, pos, size
+                )));
+            }
+        }
+        let written = self.options.verify.then(|| self.buf[..pos].to_vec());
+        if !self.write_changes(pos)? {
+            let result = self.write_data(pos);
+            self.set_image(result.is_ok().then_some(pos));
+            result?;
+        }
+        if let Some(written) = written {
+            self.verify_written(&written)?;
+        }
+        Ok(())
+    }
+}
+
+/// A mapping of an index group/offset area, e.g. `%I`/`%Q`/`%M` process images
+/// ([`crate::index::IO_RW_I`], [`crate::index::IO_RW_Q`], [`crate::index::PLC_RW_M`]) or memory of
+/// ADS servers without a symbol table. No handles are used.
+#[allow(clippy::module_name_repetitions)]
+pub struct AdsIndexMapping {
+    device: Device,
+    buf: Vec<u8>,
+    index_group: u32,
+    index_offset: u32,
+}
+
+impl AdsIndexMapping {
+    /// The buffer size MUST be greater or equal to the target structure size (for reading), the
+    /// whole buffer is requested on each read. For writing the buffer size can be any, however it
+    /// is still recommended to use the target structure size for the buffer pre-allocation.
+    pub fn new(device: &Device, index_group: u32, index_offset: u32, buf_size: usize) -> Self {
+        Self {
+            device: device.clone(),
+            buf: vec![0; buf_size],
+            index_group,
+            index_offset,
+        }
+    }
+    /// Creates a mapping for the location of a symbol (see [`crate::symbol::get_location`]).
+    ///
+    /// The location is resolved once: after online changes the symbol may be moved, so the
+    /// mapping must be re-created.
+    pub fn for_symbol(device: &Device, symbol: &str, buf_size: usize) -> Result<Self> {
+        let (index_group, index_offset) = crate::symbol::get_location(device, symbol)?;
+        Ok(Self::new(device, index_group, index_offset, buf_size))
+    }
+    /// Index group of the mapped area.
+    pub fn index_group(&self) -> u32 {
+        self.index_group
+    }
+    /// Index offset of the mapped area.
+    pub fn index_offset(&self) -> u32 {
+        self.index_offset
+    }
+}
+
+impl IoMapping for AdsIndexMapping {
     type Options = ();
 
     fn read<T>(&mut self) -> Result<T>
     where
         T: for<'a> BinRead<Args<'a> = ()>,
     {
-        let handle_id = self.get_handle()?.raw();
         let len = self
             .device
-            .read(crate::index::RW_SYMVAL_BYHANDLE, handle_id, &mut self.buf)?;
+            .read(self.index_group, self.index_offset, &mut self.buf)?;
         if len > self.buf.len() {
             return Err(Error::io("buffer overflow"));
         }
@@ -61,15 +515,196 @@ impl IoMapping for AdsMapping {
     where
         T: for<'a> BinWrite<Args<'a> = ()>,
     {
-        let handle_id = self.get_handle()?.raw();
         let mut c = Cursor::new(&mut self.buf);
         value.write_le(&mut c)?;
         let pos = usize::try_from(c.position()).map_err(Error::invalid_data)?;
-        self.device.write(
-            crate::index::RW_SYMVAL_BYHANDLE,
-            handle_id,
-            &self.buf[..pos],
-        )?;
+        self.device
+            .write(self.index_group, self.index_offset, &self.buf[..pos])?;
+        Ok(())
+    }
+}
+
+/// A mapping which keeps the latest value of a symbol in memory, updated by a device notification
+/// ([`TransmissionMode::ServerOnChange`], or [`TransmissionMode::ServerCycle`] if the maximum age
+/// is set, see [`AdsCachedMapping::with_max_age`]). Reading returns the cached value with no network access
+/// (until the first sample is received, the value is read directly), writing is performed
+/// directly.
+///
+/// The notification is re-added automatically after reconnects and online changes (if the symbol
+/// version is watched, see [`Device::watch_symbol_version`]) and deleted when the mapping is
+/// dropped.
+#[allow(clippy::module_name_repetitions)]
+pub struct AdsCachedMapping {
+    device: Device,
+    buf: Vec<u8>,
+    symbol: String,
+    max_age: Option<Duration>,
+    session_id: usize,
+    generation: usize,
+    subscription: Option<Subscription>,
+}
+
+struct Subscription {
+    handle: notif::Handle,
+    index_group: u32,
+    index_offset: u32,
+    slot: SampleSlot,
+}
+
+impl AdsCachedMapping {
+    /// Creates the mapping and adds the notification. The buffer size MUST be equal to the
+    /// symbol size (the notification is added for the buffer size).
+    pub fn new(device: &Device, symbol: &str, buf_size: usize) -> Result<Self> {
+        let mut mapping = Self {
+            device: device.clone(),
+            buf: vec![0; buf_size],
+            symbol: symbol.to_owned(),
+            max_age: None,
+            session_id: 0,
+            generation: 0,
+            subscription: None,
+        };
+        mapping.subscribe()?;
+        Ok(mapping)
+    }
+    /// Set the maximum age of the cached value. Reading an older value returns
+    /// [`Error::Timeout`].
+    ///
+    /// With [`TransmissionMode::ServerOnChange`] the server sends no samples while the value is
+    /// not changed, so the notification is re-added with [`TransmissionMode::ServerCycle`] and
+    /// the cycle time of a half of the maximum age: an unchanged value is still refreshed and the
+    /// timeout means the server has stopped sending samples.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        // re-added on the next access
+        if let Err(error) = self.unsubscribe() {
+            tracing::warn!(symbol = self.symbol, %error, "unable to delete the notification");
+        }
+        self
+    }
+    /// The server timestamp of the cached value.
+    pub fn timestamp(&self) -> Option<Timestamp> {
+        self.cached(|sample| sample.timestamp)
+    }
+    /// Time since the cached value has been received.
+    pub fn age(&self) -> Option<Duration> {
+        self.cached(|sample| sample.received.elapsed())
+    }
+    fn cached<R>(&self, f: impl FnOnce(&CachedSample) -> R) -> Option<R> {
+        self.subscription
+            .as_ref()
+            .and_then(|sub| sub.slot.lock().as_ref().map(f))
+    }
+    fn subscribe(&mut self) -> Result<&Subscription> {
+        let session_id = self.device.client.session_id();
+        if self.session_id != session_id {
+            self.device.refresh_symbol_version_watch()?;
+        }
+        let generation = self.device.symbol_generation();
+        if self.subscription.is_none()
+            || self.session_id != session_id
+            || self.generation != generation
+        {
+            if let Err(error) = self.unsubscribe() {
+                tracing::warn!(symbol = self.symbol, %error, "unable to delete the notification");
+            }
+            // the symbol may be moved after online changes
+            let (index_group, index_offset) =
+                crate::symbol::get_location(&self.device, &self.symbol)?;
+            let attributes = if let Some(max_age) = self.max_age {
+                Attributes::new(
+                    self.buf.len(),
+                    TransmissionMode::ServerCycle,
+                    Duration::ZERO,
+                    max_age / 2,
+                )
+            } else {
+                Attributes::new(
+                    self.buf.len(),
+                    TransmissionMode::ServerOnChange,
+                    Duration::ZERO,
+                    Duration::ZERO,
+                )
+            };
+            let (handle, slot) =
+                self.device
+                    .add_cached_notification(index_group, index_offset, &attributes)?;
+            self.subscription = Some(Subscription {
+                handle,
+                index_group,
+                index_offset,
+                slot,
+            });
+            self.session_id = session_id;
+            self.generation = generation;
+        }
+        Ok(self.subscription.as_ref().unwrap())
+    }
+    fn unsubscribe(&mut self) -> Result<()> {
+        if let Some(sub) = self.subscription.take() {
+            self.device
+                .delete_routed_notification(sub.handle, self.session_id)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for AdsCachedMapping {
+    fn drop(&mut self) {
+        let _r = self.unsubscribe();
+    }
+}
+
+impl IoMapping for AdsCachedMapping {
+    type Options = ();
+
+    fn read<T>(&mut self) -> Result<T>
+    where
+        T: for<'a> BinRead<Args<'a> = ()>,
+    {
+        let sub = self.subscribe()?;
+        let (index_group, index_offset, slot) =
+            (sub.index_group, sub.index_offset, sub.slot.clone());
+        // the first sample may be sent before the notification handle is known, the slot must
+        // not be locked while communicating as the reader needs it
+        if slot.lock().is_none() {
+            let len = self.device.read(index_group, index_offset, &mut self.buf)?;
+            if len > self.buf.len() {
+                return Err(Error::io("buffer overflow"));
+            }
+            let mut cached = slot.lock();
+            if cached.is_none() {
+                cached.replace(CachedSample {
+                    timestamp: Timestamp::now(),
+                    received: Instant::now(),
+                    data: self.buf[..len].to_vec(),
+                });
+            }
+        }
+        let cached = slot.lock();
+        let sample = cached.as_ref().ok_or_else(|| Error::failed("no data"))?;
+        if self
+            .max_age
+            .map_or(false, |max_age| sample.received.elapsed() > max_age)
+        {
+            return Err(Error::Timeout);
+        }
+        let mut c = Cursor::new(&sample.data);
+        let res: T = T::read_le(&mut c)?;
+        Ok(res)
+    }
+
+    fn write<T>(&mut self, value: T) -> Result<()>
+    where
+        T: for<'a> BinWrite<Args<'a> = ()>,
+    {
+        let sub = self.subscribe()?;
+        let (index_group, index_offset) = (sub.index_group, sub.index_offset);
+        let mut c = Cursor::new(&mut self.buf);
+        value.write_le(&mut c)?;
+        let pos = usize::try_from(c.position()).map_err(Error::invalid_data)?;
+        self.device
+            .write(index_group, index_offset, &self.buf[..pos])?;
         Ok(())
     }
 }
@@ -4,10 +4,13 @@ use std::io::{self, Cursor};
 use std::time::Duration;
 
 use bma_ts::Timestamp;
-use byteorder::{ReadBytesExt, LE};
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use roboplc::policy_channel::Receiver;
 use roboplc::{io::IoMapping, DataDeliveryPolicy, Error, Result};
+use zerocopy::FromBytes;
 
 use crate::client::AMS_HEADER_SIZE;
+use crate::{AmsAddr, Device};
 
 /// A handle to the notification; this can be used to delete the notification later.
 pub type Handle = u32;
@@ -42,27 +45,47 @@ impl Attributes {
 }
 
 /// When notifications should be generated.
+///
+/// The client modes are emulated by the client for servers which do not support device
+/// notifications: the data is polled with the cycle time of the notification attributes, the
+/// samples are delivered in the same way as for the server modes.
 #[repr(u32)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TransmissionMode {
     /// No transmission.
     NoTrans = 0,
+    /// Poll the data each cycle.
+    ClientCycle = 1,
+    /// Poll the data each cycle, notify when the content changes.
+    ClientOnChange = 2,
     /// Notify each server cycle.
     ServerCycle = 3,
     /// Notify when the content changes.
     ServerOnChange = 4,
+    /// Read the data once.
+    Client1Req = 10,
     // Other constants from the C++ library:
-    // ClientCycle = 1,
-    // ClientOnChange = 2,
     // ServerCycle2 = 5,
     // ServerOnChange2 = 6,
-    // Client1Req = 10,
+}
+
+impl TransmissionMode {
+    /// Return true if the mode is emulated by the client.
+    pub fn is_client(self) -> bool {
+        matches!(
+            self,
+            TransmissionMode::ClientCycle
+                | TransmissionMode::ClientOnChange
+                | TransmissionMode::Client1Req
+        )
+    }
 }
 
 /// A notification message from the ADS server.
 pub struct Notification {
     data: Vec<u8>,
     nstamps: u32,
+    source: AmsAddr,
 }
 
 impl DataDeliveryPolicy for Notification {}
@@ -86,6 +109,7 @@ impl Notification {
             // header + length + #stamps
             return Err(Error::io(io::ErrorKind::UnexpectedEof));
         }
+        let source = AmsAddr::read_from(&mut &data[14..22])?;
         let mut ptr = &data[AMS_HEADER_SIZE + 4..];
         let nstamps = ptr.read_u32::<LE>()?;
         for _ in 0..nstamps {
@@ -103,12 +127,49 @@ impl Notification {
             }
         }
         if ptr.is_empty() {
-            Ok(Self { data, nstamps })
+            Ok(Self {
+                data,
+                nstamps,
+                source,
+            })
         } else {
             Err(Error::io(io::ErrorKind::UnexpectedEof))
         }
     }
 
+    /// Build a notification message with a single sample, as sent by the server.
+    pub(crate) fn with_sample(
+        source: AmsAddr,
+        handle: Handle,
+        timestamp: Timestamp,
+        data: &[u8],
+    ) -> Result<Self> {
+        let mut buf = vec![0; AMS_HEADER_SIZE];
+        source.write_to(&mut &mut buf[14..22])?;
+        let length = u32::try_from(data.len()).map_err(Error::invalid_data)?;
+        let stamp = u64::try_from(
+            timestamp
+                .try_from_unix_to_ansi()
+                .map_err(Error::invalid_data)?
+                .as_nanos()
+                / 100,
+        )
+        .map_err(Error::invalid_data)?;
+        buf.write_u32::<LE>(length + 28)?;
+        buf.write_u32::<LE>(1)?;
+        buf.write_u64::<LE>(stamp)?;
+        buf.write_u32::<LE>(1)?;
+        buf.write_u32::<LE>(handle)?;
+        buf.write_u32::<LE>(length)?;
+        buf.extend(data);
+        Self::new(buf)
+    }
+
+    /// Return the address of the device which has sent the notification.
+    pub fn source(&self) -> AmsAddr {
+        self.source
+    }
+
     /// Return an iterator over all data samples in this notification.
     pub fn samples(&self) -> SampleIter<'_> {
         SampleIter {
@@ -151,6 +212,246 @@ impl<'d> IoMapping for Sample<'d> {
     }
 }
 
+/// A single sample, delivered to a [`Subscription`] channel.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OwnedSample {
+    /// The address of the device which has sent the sample.
+    pub source: AmsAddr,
+    /// The notification handle associated with the data.
+    pub handle: Handle,
+    /// Timestamp of generation (already converted to UNIX)
+    pub timestamp: Timestamp,
+    /// Data of the handle at the specified time.
+    pub data: Vec<u8>,
+}
+
+impl DataDeliveryPolicy for OwnedSample {}
+
+impl OwnedSample {
+    pub(crate) fn new(source: AmsAddr, sample: &Sample) -> Self {
+        Self {
+            source,
+            handle: sample.handle,
+            timestamp: sample.timestamp,
+            data: sample.data.to_vec(),
+        }
+    }
+
+    /// Return the sample as a borrowed one.
+    pub fn as_sample(&self) -> Sample<'_> {
+        Sample {
+            handle: self.handle,
+            timestamp: self.timestamp,
+            data: &self.data,
+        }
+    }
+}
+
+impl IoMapping for OwnedSample {
+    type Options = ();
+
+    fn read<T>(&mut self) -> Result<T>
+    where
+        T: for<'a> roboplc::prelude::BinRead<Args<'a> = ()>,
+    {
+        let mut c = Cursor::new(&self.data);
+        let res: T = T::read_le(&mut c)?;
+        Ok(res)
+    }
+
+    fn write<T>(&mut self, _value: T) -> Result<()>
+    where
+        T: for<'a> roboplc::prelude::BinWrite<Args<'a> = ()>,
+    {
+        Err(Error::Unimplemented)
+    }
+}
+
+/// A notification which samples are routed to a dedicated channel or callback, see
+/// [`Device::add_notification_channel`] and [`Device::add_notification_callback`]. The samples are
+/// not sent to the common notification channel.
+///
+/// The notification is deleted when the subscription is dropped.
+///
+/// NOTE: Notifications are not restored automatically if the remote is restarted.
+pub struct Subscription {
+    device: Device,
+    handle: Handle,
+    session_id: usize,
+    rx: Option<Receiver<OwnedSample>>,
+}
+
+impl Subscription {
+    pub(crate) fn new(
+        device: &Device,
+        handle: Handle,
+        session_id: usize,
+        rx: Option<Receiver<OwnedSample>>,
+    ) -> Self {
+        Self {
+            device: device.clone(),
+            handle,
+            session_id,
+            rx,
+        }
+    }
+
+    /// Return the notification handle.
+    pub fn handle(&self) -> Handle {
+        self.handle
+    }
+
+    /// Return the address of the device the notification has been added to.
+    pub fn source(&self) -> AmsAddr {
+        self.device.addr()
+    }
+
+    /// Return the receiver of the samples, `None` for callback subscriptions.
+    pub fn receiver(&self) -> Option<&Receiver<OwnedSample>> {
+        self.rx.as_ref()
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        let _r = self
+            .device
+            .delete_routed_notification(self.handle, self.session_id);
+    }
+}
+
+const TYPED_QUEUE: usize = 1024;
+const TYPED_ERROR_QUEUE: usize = 64;
+
+struct TypedSample<T>(Timestamp, T);
+
+impl<T> DataDeliveryPolicy for TypedSample<T> {}
+
+/// A sample of a [`TypedSubscription`] which has failed to decode.
+#[derive(Debug)]
+pub struct DecodeError {
+    /// Timestamp of generation (already converted to UNIX)
+    pub timestamp: Timestamp,
+    /// Data of the sample.
+    pub data: Vec<u8>,
+    /// The decoding error.
+    pub error: Error,
+}
+
+impl DataDeliveryPolicy for DecodeError {}
+
+/// A symbol notification which samples are decoded into values of `T`, see [`Device::subscribe`]
+/// and [`Device::subscribe_with`].
+///
+/// Samples which have failed to decode are sent to a separate error channel. If a channel is full,
+/// new samples are dropped. The notification is deleted when the subscription is dropped.
+///
+/// NOTE: Notifications are not restored automatically if the remote is restarted.
+#[allow(clippy::module_name_repetitions)]
+pub struct TypedSubscription<T> {
+    subscription: Subscription,
+    rx: Receiver<TypedSample<T>>,
+    errors: Receiver<DecodeError>,
+}
+
+impl<T: Send + 'static> TypedSubscription<T> {
+    pub(crate) fn new<F>(
+        device: &Device,
+        symbol: &str,
+        attributes: &Attributes,
+        decode: F,
+    ) -> Result<Self>
+    where
+        F: Fn(&[u8]) -> Result<T> + Send + Sync + 'static,
+    {
+        let (index_group, index_offset) = crate::symbol::get_location(device, symbol)?;
+        let (tx, rx) = roboplc::policy_channel::bounded(TYPED_QUEUE);
+        let (errors_tx, errors) = roboplc::policy_channel::bounded(TYPED_ERROR_QUEUE);
+        let subscription = device.add_notification_callback(
+            index_group,
+            index_offset,
+            attributes,
+            move |sample| match decode(sample.data) {
+                Ok(value) => {
+                    if tx.try_send(TypedSample(sample.timestamp, value)).is_err() {
+                        tracing::warn!(
+                            handle = sample.handle,
+                            "subscription channel full, sample dropped"
+                        );
+                    }
+                }
+                Err(error) => {
+                    let _r = errors_tx.try_send(DecodeError {
+                        timestamp: sample.timestamp,
+                        data: sample.data.to_vec(),
+                        error,
+                    });
+                }
+            },
+        )?;
+        Ok(Self {
+            subscription,
+            rx,
+            errors,
+        })
+    }
+}
+
+impl<T> TypedSubscription<T> {
+    /// Return the notification handle.
+    pub fn handle(&self) -> Handle {
+        self.subscription.handle()
+    }
+
+    /// Return the address of the device the notification has been added to.
+    pub fn source(&self) -> AmsAddr {
+        self.subscription.source()
+    }
+
+    /// Receive the next value, waiting for it.
+    pub fn recv(&self) -> Result<(Timestamp, T)> {
+        let TypedSample(timestamp, value) = self.rx.recv()?;
+        Ok((timestamp, value))
+    }
+
+    /// Receive the next value, waiting for it not longer than the timeout.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<(Timestamp, T)> {
+        let TypedSample(timestamp, value) = self.rx.recv_timeout(timeout)?;
+        Ok((timestamp, value))
+    }
+
+    /// Receive the next value if there is one.
+    pub fn try_recv(&self) -> Result<(Timestamp, T)> {
+        let TypedSample(timestamp, value) = self.rx.try_recv()?;
+        Ok((timestamp, value))
+    }
+
+    /// Return the receiver of the samples which have failed to decode.
+    pub fn errors(&self) -> &Receiver<DecodeError> {
+        &self.errors
+    }
+}
+
+/// Decode sample data with `binrw` (little-endian), as used by [`Device::subscribe`].
+pub fn decode_binrw<T>(data: &[u8]) -> Result<T>
+where
+    T: for<'a> roboplc::prelude::BinRead<Args<'a> = ()>,
+{
+    Ok(T::read_le(&mut Cursor::new(data))?)
+}
+
+/// Decode sample data with `zerocopy`, the data size must match the size of `T`. Can be used
+/// with [`Device::subscribe_with`].
+pub fn decode_from_bytes<T: FromBytes>(data: &[u8]) -> Result<T> {
+    T::read_from(data).ok_or_else(|| {
+        Error::invalid_data(format!(
+            "the sample size {} does not match the type size {}",
+            data.len(),
+            std::mem::size_of::<T>()
+        ))
+    })
+}
+
 /// An iterator over all samples within a notification message.
 pub struct SampleIter<'a> {
     data: &'a [u8],
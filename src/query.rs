@@ -0,0 +1,306 @@
+//! Queries over the uploaded symbol table.
+//!
+//! Example: all persistent REALs under `GVL_Recipe`, including structure members and array
+//! elements:
+//!
+//! ```rust,no_run
+//! use roboplc_io_ads::query::Query;
+//! use roboplc_io_ads::symbol::{self, SYMBOL_FLAG_PERSISTENT};
+//! # fn example(device: &roboplc_io_ads::Device) -> roboplc::Result<()> {
+//! let (symbols, types) = symbol::get_symbol_info(device)?;
+//! let watch_list = Query::new()
+//!     .name("GVL_Recipe.*")?
+//!     .type_name("REAL")?
+//!     .with_flags(SYMBOL_FLAG_PERSISTENT)
+//!     .expand(true)
+//!     .run(&symbols, &types);
+//! for symbol in watch_list {
+//!     println!("{} {}", symbol.name, symbol.typ);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::ops::{Bound, RangeBounds};
+
+use regex::{Regex, RegexBuilder};
+use roboplc::{Error, Result};
+
+use crate::symbol::{Symbol, TypeMap};
+
+/// A query over symbols obtained with [`crate::symbol::get_symbol_info`]. A symbol matches if it
+/// matches all the conditions specified.
+///
+/// Glob patterns are case-insensitive (same as IEC 61131-3 identifiers) and support `*` (any
+/// number of any characters, including dots) and `?` (any single character) wildcards only, so
+/// array indexes (e.g. `arr[1]`) can be matched literally. Regular expressions are used as-is
+/// (prefix them with `(?i)` for case-insensitive matching).
+#[derive(Clone, Debug, Default)]
+pub struct Query {
+    name: Option<Regex>,
+    type_name: Option<Regex>,
+    base_types: Vec<u32>,
+    flags_set: u32,
+    flags_clear: u32,
+    min_size: Option<usize>,
+    max_size: Option<usize>,
+    attributes: Vec<(String, Option<String>)>,
+    expand: bool,
+}
+
+impl Query {
+    /// Create a new query which matches all symbols.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match symbol names with a glob pattern.
+    pub fn name(mut self, pattern: &str) -> Result<Self> {
+        self.name = Some(glob_to_regex(pattern)?);
+        Ok(self)
+    }
+
+    /// Match symbol names with a regular expression.
+    pub fn name_regex(mut self, pattern: &str) -> Result<Self> {
+        self.name = Some(Regex::new(pattern).map_err(Error::invalid_data)?);
+        Ok(self)
+    }
+
+    /// Match type names with a glob pattern.
+    pub fn type_name(mut self, pattern: &str) -> Result<Self> {
+        self.type_name = Some(glob_to_regex(pattern)?);
+        Ok(self)
+    }
+
+    /// Match type names with a regular expression.
+    pub fn type_name_regex(mut self, pattern: &str) -> Result<Self> {
+        self.type_name = Some(Regex::new(pattern).map_err(Error::invalid_data)?);
+        Ok(self)
+    }
+
+    /// Match the base type (see [`Symbol::base_type`]). If called several times, any of the
+    /// base types given matches.
+    pub fn base_type(mut self, base_type: u32) -> Self {
+        self.base_types.push(base_type);
+        self
+    }
+
+    /// Match symbols which have got all the given flags set (see
+    /// [`crate::symbol::SYMBOL_FLAG_PERSISTENT`] and other constants).
+    pub fn with_flags(mut self, flags: u32) -> Self {
+        self.flags_set |= flags;
+        self
+    }
+
+    /// Match symbols which have got none of the given flags set.
+    pub fn without_flags(mut self, flags: u32) -> Self {
+        self.flags_clear |= flags;
+        self
+    }
+
+    /// Match symbol sizes, in bytes (e.g. `..=8`, `4..`).
+    pub fn size(mut self, range: impl RangeBounds<usize>) -> Self {
+        self.min_size = match range.start_bound() {
+            Bound::Included(v) => Some(*v),
+            Bound::Excluded(v) => Some(v.saturating_add(1)),
+            Bound::Unbounded => None,
+        };
+        self.max_size = match range.end_bound() {
+            Bound::Included(v) => Some(*v),
+            Bound::Excluded(v) => Some(v.saturating_sub(1)),
+            Bound::Unbounded => None,
+        };
+        self
+    }
+
+    /// Match symbols which have got the attribute (the name is case-insensitive).
+    pub fn attribute(mut self, name: &str) -> Self {
+        self.attributes.push((name.to_owned(), None));
+        self
+    }
+
+    /// Match symbols which have got the attribute with the given value.
+    pub fn attribute_value(mut self, name: &str, value: &str) -> Self {
+        self.attributes
+            .push((name.to_owned(), Some(value.to_owned())));
+        self
+    }
+
+    /// Expand structure members and array elements into leaf paths (e.g. `MAIN.st.arr[2]`).
+    ///
+    /// The conditions are then checked for the leaves only. Leaves inherit flags and attributes
+    /// of the top-level symbol, members which are not located inline in the structure (e.g.
+    /// `AT %M*`) are skipped.
+    pub fn expand(mut self, expand: bool) -> Self {
+        self.expand = expand;
+        self
+    }
+
+    /// Check if a single symbol matches the query (no expansion is performed).
+    pub fn matches(&self, symbol: &Symbol) -> bool {
+        if let Some(ref name) = self.name {
+            if !name.is_match(&symbol.name) {
+                return false;
+            }
+        }
+        if let Some(ref type_name) = self.type_name {
+            if !type_name.is_match(&symbol.typ) {
+                return false;
+            }
+        }
+        if !self.base_types.is_empty() && !self.base_types.contains(&symbol.base_type) {
+            return false;
+        }
+        if symbol.flags & self.flags_set != self.flags_set || symbol.flags & self.flags_clear != 0 {
+            return false;
+        }
+        if self.min_size.map_or(false, |min| symbol.size < min)
+            || self.max_size.map_or(false, |max| symbol.size > max)
+        {
+            return false;
+        }
+        self.attributes.iter().all(|(name, value)| {
+            symbol.attributes.iter().any(|(n, v)| {
+                n.eq_ignore_ascii_case(name) && value.as_ref().map_or(true, |value| v == value)
+            })
+        })
+    }
+
+    /// Run the query and return matching symbols (leaves, if expansion is enabled).
+    pub fn run(&self, symbols: &[Symbol], types: &TypeMap) -> Vec<Symbol> {
+        let mut result = Vec::new();
+        for symbol in symbols {
+            if self.expand {
+                self.expand_symbol(symbol.clone(), types, &mut result);
+            } else if self.matches(symbol) {
+                result.push(symbol.clone());
+            }
+        }
+        result
+    }
+
+    fn expand_symbol(&self, symbol: Symbol, types: &TypeMap, result: &mut Vec<Symbol>) {
+        if let Some((dims, element_type)) = array_info(&symbol.typ, types) {
+            let count = array_len(&dims).unwrap_or_default();
+            // the bounds do not match the symbol size
+            if count == 0 || count > symbol.size {
+                return;
+            }
+            let element_size = symbol.size / count;
+            let base_type = types
+                .get(element_type)
+                .map_or(symbol.base_type, |t| t.base_type);
+            for (i, index) in array_indexes(&dims).into_iter().enumerate() {
+                let Ok(offset) = u32::try_from(i * element_size) else {
+                    return;
+                };
+                let element = Symbol {
+                    name: format!("{}[{}]", symbol.name, index),
+                    typ: element_type.to_owned(),
+                    ix_offset: symbol.ix_offset.wrapping_add(offset),
+                    size: element_size,
+                    base_type,
+                    comment: String::new(),
+                    ..symbol.clone()
+                };
+                self.expand_symbol(element, types, result);
+            }
+            return;
+        }
+        if let Some(typ) = types.get(&symbol.typ).filter(|t| !t.fields.is_empty()) {
+            for field in &typ.fields {
+                let Some(offset) = field.offset else {
+                    continue;
+                };
+                let member = Symbol {
+                    name: format!("{}.{}", symbol.name, field.name),
+                    typ: field.typ.clone(),
+                    ix_offset: symbol.ix_offset.wrapping_add(offset),
+                    size: field.size,
+                    base_type: field.base_type,
+                    comment: String::new(),
+                    ..symbol.clone()
+                };
+                self.expand_symbol(member, types, result);
+            }
+            return;
+        }
+        if self.matches(&symbol) {
+            result.push(symbol);
+        }
+    }
+}
+
+fn glob_to_regex(pattern: &str) -> Result<Regex> {
+    let mut re = String::with_capacity(pattern.len() + 2);
+    re.push('^');
+    for c in pattern.chars() {
+        match c {
+            '*' => re.push_str(".*"),
+            '?' => re.push('.'),
+            _ => re.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    re.push('$');
+    RegexBuilder::new(&re)
+        .case_insensitive(true)
+        .build()
+        .map_err(Error::invalid_data)
+}
+
+// Returns array dimensions and the element type name for array types
+// (e.g. `ARRAY [1..3, 0..1] OF REAL`).
+pub(crate) fn array_info<'a>(typ: &'a str, types: &TypeMap) -> Option<(Vec<(i32, i32)>, &'a str)> {
+    let (head, element_type) = typ.split_once(" OF ")?;
+    let head = head.trim();
+    if !head.get(..5)?.eq_ignore_ascii_case("ARRAY") {
+        return None;
+    }
+    // the bounds may be constants, so prefer the dimensions from the type info
+    let dims = types
+        .get(typ)
+        .filter(|t| !t.array.is_empty())
+        .map(|t| t.array.clone())
+        .or_else(|| parse_dims(&head[5..]))?;
+    Some((dims, element_type.trim()))
+}
+
+// Returns the number of array elements, `None` if the bounds are invalid or the count overflows.
+pub(crate) fn array_len(dims: &[(i32, i32)]) -> Option<usize> {
+    dims.iter().try_fold(1usize, |count, (lower, upper)| {
+        let len = usize::try_from(i64::from(*upper) - i64::from(*lower) + 1).ok()?;
+        count.checked_mul(len)
+    })
+}
+
+fn parse_dims(s: &str) -> Option<Vec<(i32, i32)>> {
+    s.trim()
+        .strip_prefix('[')?
+        .strip_suffix(']')?
+        .split(',')
+        .map(|dim| {
+            let (lower, upper) = dim.split_once("..")?;
+            Some((lower.trim().parse().ok()?, upper.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+// Returns all indexes of an array in memory order (the last dimension changes fastest).
+fn array_indexes(dims: &[(i32, i32)]) -> Vec<String> {
+    let mut indexes = vec![String::new()];
+    for (lower, upper) in dims {
+        indexes = indexes
+            .iter()
+            .flat_map(|prefix| {
+                (*lower..=*upper).map(move |i| {
+                    if prefix.is_empty() {
+                        i.to_string()
+                    } else {
+                        format!("{},{}", prefix, i)
+                    }
+                })
+            })
+            .collect();
+    }
+    indexes
+}
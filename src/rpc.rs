@@ -0,0 +1,119 @@
+//! Calling methods of function blocks over ADS (RPC).
+//!
+//! Methods marked with `{attribute 'TcRpcEnable'}` can be called from the client: a handle is
+//! created for `<instance path>#<method name>` and the call is a single write-read on it. The
+//! write data contains the input parameters, the read data contains the return value followed by
+//! the output parameters. Parameters are packed in the declaration order, without padding. The
+//! method is executed atomically, in the context of the ADS server.
+//!
+//! Parameter and return layouts are taken from the method infos of the function block type (see
+//! [`crate::symbol::Type::methods`]).
+
+use roboplc::{Error, Result};
+
+use crate::symbol::{
+    Handle, Method, MethodParam, Type, METHOD_FLAG_NOT_CALLABLE, METHOD_PARAM_FLAG_BY_REFERENCE,
+    METHOD_PARAM_FLAG_IN, METHOD_PARAM_FLAG_IN_OUT, METHOD_PARAM_FLAG_OUT,
+};
+use crate::Device;
+
+/// Result of a method call.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MethodResult {
+    /// Raw return value (empty if the method does not return a value).
+    pub value: Vec<u8>,
+    /// Raw output parameters, as (name, data), in the declaration order.
+    pub outputs: Vec<(String, Vec<u8>)>,
+}
+
+impl MethodResult {
+    /// Get raw data of an output parameter.
+    pub fn output(&self, name: &str) -> Option<&[u8]> {
+        self.outputs
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, data)| data.as_slice())
+    }
+}
+
+/// Find a method of a function block type by name (case-insensitive).
+pub fn find_method<'a>(typ: &'a Type, method: &str) -> Option<&'a Method> {
+    typ.methods
+        .iter()
+        .find(|m| m.name.eq_ignore_ascii_case(method))
+}
+
+/// Call a method of a function block instance. `args` contain raw data of the input parameters,
+/// in the declaration order. The size of each argument must match the parameter size.
+pub fn call(device: &Device, path: &str, method: &Method, args: &[&[u8]]) -> Result<MethodResult> {
+    if method.flags & METHOD_FLAG_NOT_CALLABLE != 0 {
+        return Err(Error::invalid_data(format!(
+            "method {} is not callable",
+            method.name
+        )));
+    }
+    let write_data = pack_inputs(method, args)?;
+    let outputs = method
+        .params
+        .iter()
+        .filter(|p| p.flags & METHOD_PARAM_FLAG_OUT != 0)
+        .collect::<Vec<_>>();
+    let read_len = method.return_size + outputs.iter().map(|p| p.size).sum::<usize>();
+    let mut read_data = vec![0; read_len];
+    Handle::new(device, &format!("{}#{}", path, method.name))?
+        .write_read(&write_data, &mut read_data)?;
+    let (value, mut rest) = read_data.split_at(method.return_size);
+    let mut result = MethodResult {
+        value: value.to_vec(),
+        outputs: Vec::with_capacity(outputs.len()),
+    };
+    for param in outputs {
+        let (data, tail) = rest.split_at(param.size);
+        result.outputs.push((param.name.clone(), data.to_vec()));
+        rest = tail;
+    }
+    Ok(result)
+}
+
+fn pack_inputs(method: &Method, args: &[&[u8]]) -> Result<Vec<u8>> {
+    let mut inputs = Vec::new();
+    for param in &method.params {
+        check_param(method, param)?;
+        if param.flags & METHOD_PARAM_FLAG_IN != 0 {
+            inputs.push(param);
+        }
+    }
+    if inputs.len() != args.len() {
+        return Err(Error::invalid_data(format!(
+            "method {} expects {} arguments, {} given",
+            method.name,
+            inputs.len(),
+            args.len()
+        )));
+    }
+    let mut data = Vec::with_capacity(inputs.iter().map(|p| p.size).sum());
+    for (param, arg) in inputs.into_iter().zip(args) {
+        if arg.len() != param.size {
+            return Err(Error::invalid_data(format!(
+                "method {}: invalid size of {}: {} (expected {})",
+                method.name,
+                param.name,
+                arg.len(),
+                param.size
+            )));
+        }
+        data.extend_from_slice(arg);
+    }
+    Ok(data)
+}
+
+// Parameters passed by reference can not be transferred over ADS.
+fn check_param(method: &Method, param: &MethodParam) -> Result<()> {
+    if param.flags & (METHOD_PARAM_FLAG_IN_OUT | METHOD_PARAM_FLAG_BY_REFERENCE) != 0 {
+        return Err(Error::invalid_data(format!(
+            "method {}: parameter {} is passed by reference, which is not supported",
+            method.name, param.name
+        )));
+    }
+    Ok(())
+}
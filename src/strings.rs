@@ -1,4 +1,8 @@
 //! Const-generic string types for representing fixed-length strings.
+//!
+//! TwinCAT stores `STRING` values (as well as symbol names, comments and file names) in the
+//! Windows-1252 code page. Use [`decode_1252`]/[`encode_1252`] and the `*_1252` methods of
+//! [`String`] to convert them, the UTF-8 conversions work for ASCII-only strings only.
 
 /// Represents a fixed-length byte string.
 ///
@@ -37,6 +41,33 @@ impl<const LEN: usize> String<LEN> {
     pub fn backing_array(&mut self) -> &mut [u8; LEN] {
         &mut self.0
     }
+
+    /// Create a string from a Rust string, encoded in Windows-1252. Characters which can not be
+    /// encoded are rejected.
+    pub fn from_str_1252(s: &str) -> Result<Self, Cp1252Error> {
+        Self::from_encoded(&encode_1252(s)?)
+    }
+
+    /// Create a string from a Rust string, encoded in Windows-1252. Characters which can not be
+    /// encoded are replaced with `?`.
+    pub fn from_str_1252_lossy(s: &str) -> Result<Self, Cp1252Error> {
+        Self::from_encoded(&encode_1252_lossy(s))
+    }
+
+    fn from_encoded(bytes: &[u8]) -> Result<Self, Cp1252Error> {
+        Self::try_from(bytes).map_err(|()| Cp1252Error::TooLong)
+    }
+
+    /// Convert to a Rust string, decoding Windows-1252. Bytes which are not defined in
+    /// Windows-1252 are rejected.
+    pub fn to_string_1252(&self) -> Result<std::string::String, Cp1252Error> {
+        decode_1252(self.as_bytes())
+    }
+
+    /// Convert to a Rust string, decoding Windows-1252 (see [`decode_1252_lossy`]).
+    pub fn to_string_1252_lossy(&self) -> std::string::String {
+        decode_1252_lossy(self.as_bytes())
+    }
 }
 
 // standard traits
@@ -273,6 +304,410 @@ unsafe impl<const LEN: usize> zerocopy::FromBytes for WString<LEN> {
     fn only_derive_is_allowed_to_implement_this_trait() {}
 }
 
+// runtime-sized strings
+
+/// Represents a `STRING(n)` value, with the length known at runtime only.
+///
+/// The string can be sized from the symbol info (see [`PlcString::for_symbol`]) and read or
+/// written with a [`crate::symbol::Handle`]. It also implements binrw traits, so it can be read
+/// with [`crate::AdsMapping`] as well (the mapping buffer must be large enough to fit the symbol).
+///
+/// Reading with binrw consumes all the data available, so the string can be used as the last
+/// field of a binrw structure only.
+#[derive(Clone, PartialEq, Eq)]
+pub struct PlcString {
+    data: std::vec::Vec<u8>, // includes the NULL byte
+}
+
+impl PlcString {
+    /// Create a new empty string which can hold `len` bytes.
+    pub fn with_capacity(len: usize) -> Self {
+        Self {
+            data: std::vec![0; len + 1],
+        }
+    }
+
+    /// Create a new empty string, sized to fit the symbol.
+    pub fn for_symbol(device: &crate::Device, symbol: &str) -> roboplc::Result<Self> {
+        let size = crate::symbol::get_size(device, symbol)?;
+        Ok(Self::with_capacity(size.saturating_sub(1)))
+    }
+
+    /// Maximum length of the string, in bytes.
+    pub fn capacity(&self) -> usize {
+        self.data.len() - 1
+    }
+
+    /// Size of the PLC value (the capacity plus the NULL byte).
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Return the number of bytes up to the first null byte.
+    pub fn len(&self) -> usize {
+        self.data
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(self.data.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data[0] == 0
+    }
+
+    /// Get the slice up to the first null byte.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data[..self.len()]
+    }
+
+    /// Set the contents. Returns an error if the data exceeds the capacity.
+    pub fn set_bytes(&mut self, data: &[u8]) -> roboplc::Result<()> {
+        if data.len() > self.capacity() {
+            return Err(roboplc::Error::invalid_data(format!(
+                "string length {} exceeds the capacity {}",
+                data.len(),
+                self.capacity()
+            )));
+        }
+        self.data.fill(0);
+        self.data[..data.len()].copy_from_slice(data);
+        Ok(())
+    }
+
+    /// Set the contents from a Rust string, encoded in Windows-1252 (see [`encode_1252`]).
+    pub fn set_str_1252(&mut self, s: &str) -> roboplc::Result<()> {
+        self.set_bytes(&encode_1252(s)?)
+    }
+
+    /// Convert to a Rust string, decoding Windows-1252 (see [`decode_1252`]).
+    pub fn to_string_1252(&self) -> Result<std::string::String, Cp1252Error> {
+        decode_1252(self.as_bytes())
+    }
+
+    /// Convert to a Rust string, decoding Windows-1252 (see [`decode_1252_lossy`]).
+    pub fn to_string_1252_lossy(&self) -> std::string::String {
+        decode_1252_lossy(self.as_bytes())
+    }
+
+    /// Read the value using a symbol handle.
+    pub fn read(&mut self, handle: &crate::symbol::Handle) -> roboplc::Result<()> {
+        handle.read(&mut self.data)?;
+        // make sure the string is terminated
+        if let Some(last) = self.data.last_mut() {
+            *last = 0;
+        }
+        Ok(())
+    }
+
+    /// Write the value using a symbol handle.
+    pub fn write(&self, handle: &crate::symbol::Handle) -> roboplc::Result<()> {
+        handle.write(&self.data)
+    }
+}
+
+impl std::fmt::Debug for PlcString {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&self.to_string_1252_lossy(), fmt)
+    }
+}
+
+impl std::fmt::Display for PlcString {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.write_str(&self.to_string_1252_lossy())
+    }
+}
+
+impl roboplc::io::binrw::BinRead for PlcString {
+    type Args<'a> = ();
+
+    fn read_options<R: std::io::Read + std::io::Seek>(
+        reader: &mut R,
+        _endian: roboplc::io::binrw::Endian,
+        _args: Self::Args<'_>,
+    ) -> roboplc::io::binrw::BinResult<Self> {
+        let mut data = std::vec::Vec::new();
+        reader.read_to_end(&mut data)?;
+        match data.last_mut() {
+            Some(last) => *last = 0,
+            None => data.push(0),
+        }
+        Ok(Self { data })
+    }
+}
+
+impl roboplc::io::binrw::BinWrite for PlcString {
+    type Args<'a> = ();
+
+    fn write_options<W: std::io::Write + std::io::Seek>(
+        &self,
+        writer: &mut W,
+        _endian: roboplc::io::binrw::Endian,
+        _args: Self::Args<'_>,
+    ) -> roboplc::io::binrw::BinResult<()> {
+        writer.write_all(&self.data)?;
+        Ok(())
+    }
+}
+
+/// Represents a `WSTRING(n)` value, with the length known at runtime only (see [`PlcString`]).
+///
+/// Reading with binrw consumes all the data available, so the string can be used as the last
+/// field of a binrw structure only.
+#[derive(Clone, PartialEq, Eq)]
+pub struct PlcWString {
+    data: std::vec::Vec<u16>, // includes the NULL code unit
+}
+
+impl PlcWString {
+    /// Create a new empty string which can hold `len` code units.
+    pub fn with_capacity(len: usize) -> Self {
+        Self {
+            data: std::vec![0; len + 1],
+        }
+    }
+
+    /// Create a new empty string, sized to fit the symbol.
+    pub fn for_symbol(device: &crate::Device, symbol: &str) -> roboplc::Result<Self> {
+        let size = crate::symbol::get_size(device, symbol)?;
+        Ok(Self::with_capacity((size / 2).saturating_sub(1)))
+    }
+
+    /// Maximum length of the string, in code units.
+    pub fn capacity(&self) -> usize {
+        self.data.len() - 1
+    }
+
+    /// Size of the PLC value, in bytes (including the NULL code unit).
+    pub fn size(&self) -> usize {
+        self.data.len() * 2
+    }
+
+    /// Return the number of code units up to the first null.
+    pub fn len(&self) -> usize {
+        self.data
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(self.data.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data[0] == 0
+    }
+
+    /// Get the slice up to the first null code unit.
+    pub fn as_slice(&self) -> &[u16] {
+        &self.data[..self.len()]
+    }
+
+    /// Set the contents from a Rust string. Returns an error if the string, encoded in UTF16,
+    /// exceeds the capacity.
+    pub fn set_str(&mut self, s: &str) -> roboplc::Result<()> {
+        let len = s.encode_utf16().count();
+        if len > self.capacity() {
+            return Err(roboplc::Error::invalid_data(format!(
+                "string length {} exceeds the capacity {}",
+                len,
+                self.capacity()
+            )));
+        }
+        self.data.fill(0);
+        for (unit, c) in self.data.iter_mut().zip(s.encode_utf16()) {
+            *unit = c;
+        }
+        Ok(())
+    }
+
+    /// Convert to a Rust string, replacing invalid UTF16 sequences.
+    pub fn to_string_lossy(&self) -> std::string::String {
+        std::string::String::from_utf16_lossy(self.as_slice())
+    }
+
+    /// Read the value using a symbol handle.
+    pub fn read(&mut self, handle: &crate::symbol::Handle) -> roboplc::Result<()> {
+        let mut buf = std::vec![0; self.size()];
+        handle.read(&mut buf)?;
+        for (unit, b) in self.data.iter_mut().zip(buf.chunks_exact(2)) {
+            *unit = u16::from_le_bytes([b[0], b[1]]);
+        }
+        if let Some(last) = self.data.last_mut() {
+            *last = 0;
+        }
+        Ok(())
+    }
+
+    /// Write the value using a symbol handle.
+    pub fn write(&self, handle: &crate::symbol::Handle) -> roboplc::Result<()> {
+        let buf = self
+            .data
+            .iter()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect::<std::vec::Vec<_>>();
+        handle.write(&buf)
+    }
+}
+
+impl std::fmt::Debug for PlcWString {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&self.to_string_lossy(), fmt)
+    }
+}
+
+impl std::fmt::Display for PlcWString {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.write_str(&self.to_string_lossy())
+    }
+}
+
+impl roboplc::io::binrw::BinRead for PlcWString {
+    type Args<'a> = ();
+
+    fn read_options<R: std::io::Read + std::io::Seek>(
+        reader: &mut R,
+        endian: roboplc::io::binrw::Endian,
+        _args: Self::Args<'_>,
+    ) -> roboplc::io::binrw::BinResult<Self> {
+        let pos = reader.stream_position()?;
+        let mut buf = std::vec::Vec::new();
+        reader.read_to_end(&mut buf)?;
+        if buf.len() % 2 != 0 {
+            return Err(roboplc::io::binrw::Error::AssertFail {
+                pos,
+                message: "odd WSTRING data length".to_owned(),
+            });
+        }
+        let mut data = buf
+            .chunks_exact(2)
+            .map(|b| match endian {
+                roboplc::io::binrw::Endian::Little => u16::from_le_bytes([b[0], b[1]]),
+                roboplc::io::binrw::Endian::Big => u16::from_be_bytes([b[0], b[1]]),
+            })
+            .collect::<std::vec::Vec<_>>();
+        match data.last_mut() {
+            Some(last) => *last = 0,
+            None => data.push(0),
+        }
+        Ok(Self { data })
+    }
+}
+
+impl roboplc::io::binrw::BinWrite for PlcWString {
+    type Args<'a> = ();
+
+    fn write_options<W: std::io::Write + std::io::Seek>(
+        &self,
+        writer: &mut W,
+        endian: roboplc::io::binrw::Endian,
+        args: Self::Args<'_>,
+    ) -> roboplc::io::binrw::BinResult<()> {
+        self.data.write_options(writer, endian, args)
+    }
+}
+
+// Windows-1252
+
+/// Windows-1252 encoding or decoding error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cp1252Error {
+    /// The character at the given byte position of the input can not be encoded.
+    Unmappable(usize),
+    /// The byte at the given position of the input is not defined in Windows-1252.
+    Undefined(usize),
+    /// The encoded string does not fit into the fixed-length string.
+    TooLong,
+}
+
+impl std::fmt::Display for Cp1252Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Cp1252Error::Unmappable(pos) => write!(
+                f,
+                "character at position {} can not be encoded in Windows-1252",
+                pos
+            ),
+            Cp1252Error::Undefined(pos) => {
+                write!(f, "byte at position {} is not defined in Windows-1252", pos)
+            }
+            Cp1252Error::TooLong => write!(f, "string is too long"),
+        }
+    }
+}
+
+impl std::error::Error for Cp1252Error {}
+
+impl From<Cp1252Error> for roboplc::Error {
+    fn from(error: Cp1252Error) -> Self {
+        roboplc::Error::invalid_data(error)
+    }
+}
+
+// Code points of the bytes 0x80..=0x9F, 0 marks bytes not defined in Windows-1252. All other
+// bytes map to the same code points as in ISO-8859-1.
+const CP1252_HIGH: [u16; 32] = [
+    0x20AC, 0, 0x201A, 0x0192, 0x201E, 0x2026, 0x2020, 0x2021, 0x02C6, 0x2030, 0x0160, 0x2039,
+    0x0152, 0, 0x017D, 0, 0, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014, 0x02DC,
+    0x2122, 0x0161, 0x203A, 0x0153, 0, 0x017E, 0x0178,
+];
+
+fn decode_1252_byte(b: u8) -> Option<char> {
+    match b {
+        0x80..=0x9F => match CP1252_HIGH[usize::from(b - 0x80)] {
+            0 => None,
+            c => char::from_u32(u32::from(c)),
+        },
+        _ => Some(char::from(b)),
+    }
+}
+
+fn encode_1252_char(c: char) -> Option<u8> {
+    match u32::from(c) {
+        0x80..=0x9F => None,
+        // ASCII and ISO-8859-1
+        c @ 0..=0xFF => u8::try_from(c).ok(),
+        c => CP1252_HIGH
+            .iter()
+            .position(|h| u32::from(*h) == c)
+            .and_then(|i| u8::try_from(i + 0x80).ok()),
+    }
+}
+
+/// Decode a Windows-1252 string. Bytes which are not defined in Windows-1252 are rejected.
+pub fn decode_1252(bytes: &[u8]) -> Result<std::string::String, Cp1252Error> {
+    bytes
+        .iter()
+        .enumerate()
+        .map(|(i, b)| decode_1252_byte(*b).ok_or(Cp1252Error::Undefined(i)))
+        .collect()
+}
+
+/// Decode a Windows-1252 string. Bytes which are not defined in Windows-1252 (0x81, 0x8D, 0x8F,
+/// 0x90 and 0x9D) are decoded into the C1 control characters with the same codes, as Windows
+/// does, so the result can be encoded back with [`encode_1252_lossy`] without losses.
+pub fn decode_1252_lossy(bytes: &[u8]) -> std::string::String {
+    bytes
+        .iter()
+        .map(|b| decode_1252_byte(*b).unwrap_or(char::from(*b)))
+        .collect()
+}
+
+/// Encode a string in Windows-1252. Characters which can not be encoded are rejected.
+pub fn encode_1252(s: &str) -> Result<std::vec::Vec<u8>, Cp1252Error> {
+    s.char_indices()
+        .map(|(i, c)| encode_1252_char(c).ok_or(Cp1252Error::Unmappable(i)))
+        .collect()
+}
+
+/// Encode a string in Windows-1252. Characters which can not be encoded are replaced with `?`,
+/// C1 control characters are encoded as is (see [`decode_1252_lossy`]).
+pub fn encode_1252_lossy(s: &str) -> std::vec::Vec<u8> {
+    s.chars()
+        .map(|c| {
+            encode_1252_char(c)
+                .or_else(|| u8::try_from(u32::from(c)).ok())
+                .unwrap_or(b'?')
+        })
+        .collect()
+}
+
 // compatibility aliases
 
 /// Alias for `String<80>`.
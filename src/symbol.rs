@@ -2,21 +2,108 @@
 
 use std::collections::HashMap;
 use std::convert::TryInto;
-use std::io::Read;
+use std::mem;
 
 use byteorder::{ByteOrder, ReadBytesExt, LE};
+use tracing::debug;
 use zerocopy::{AsBytes, FromBytes};
 
+use crate::client::{WriteReadRequest, WriteRequest};
 use crate::index;
+use crate::strings::decode_1252_lossy;
 use crate::Device;
 use roboplc::{Error, Result};
 
+/// Buffer size for a single symbol entry returned by `GET_SYMINFO_BYNAME_EX`.
+const SYMBOL_INFO_BUF_SIZE: usize = 0x4000;
+
+/// Symbol flag: persistent.
+pub const SYMBOL_FLAG_PERSISTENT: u32 = 0x01;
+/// Symbol flag: bit value.
+pub const SYMBOL_FLAG_BIT_VALUE: u32 = 0x02;
+/// Symbol flag: reference to.
+pub const SYMBOL_FLAG_REFERENCE: u32 = 0x04;
+/// Symbol flag: type GUID present.
+pub const SYMBOL_FLAG_TYPE_GUID: u32 = 0x08;
+/// Symbol flag: read only.
+pub const SYMBOL_FLAG_READ_ONLY: u32 = 0x20;
+/// Symbol flag: ITF method access (methods can be called over ADS).
+pub const SYMBOL_FLAG_ITF_METHOD_ACCESS: u32 = 0x40;
+/// Symbol flag: attributes present.
+pub const SYMBOL_FLAG_ATTRIBUTES: u32 = 0x1000;
+/// Symbol flag: static.
+pub const SYMBOL_FLAG_STATIC: u32 = 0x2000;
+/// Symbol flag: init on reset.
+pub const SYMBOL_FLAG_INIT_ON_RESET: u32 = 0x4000;
+/// Symbol flag: extended flags present.
+pub const SYMBOL_FLAG_EXTENDED_FLAGS: u32 = 0x8000;
+
+/// Type flag: type GUID present.
+pub const TYPE_FLAG_TYPE_GUID: u32 = 0x80;
+/// Type flag: copy mask present.
+pub const TYPE_FLAG_COPY_MASK: u32 = 0x0200;
+/// Type flag: method infos present.
+pub const TYPE_FLAG_METHOD_INFOS: u32 = 0x0800;
+/// Type flag: attributes present.
+pub const TYPE_FLAG_ATTRIBUTES: u32 = 0x1000;
+/// Type flag: enum infos present.
+pub const TYPE_FLAG_ENUM_INFOS: u32 = 0x2000;
+
+/// Method flag: the method can not be called over ADS.
+pub const METHOD_FLAG_NOT_CALLABLE: u32 = 0x04;
+/// Method flag: attributes present.
+pub const METHOD_FLAG_ATTRIBUTES: u32 = 0x08;
+
+/// Method parameter flag: input (`VAR_INPUT`).
+pub const METHOD_PARAM_FLAG_IN: u32 = 0x01;
+/// Method parameter flag: output (`VAR_OUTPUT`).
+pub const METHOD_PARAM_FLAG_OUT: u32 = 0x02;
+/// Method parameter flag: in/out (`VAR_IN_OUT`).
+pub const METHOD_PARAM_FLAG_IN_OUT: u32 = 0x04;
+/// Method parameter flag: passed by reference.
+pub const METHOD_PARAM_FLAG_BY_REFERENCE: u32 = 0x08;
+
+/// Base type: void.
+pub const BASE_TYPE_VOID: u32 = 0;
+/// Base type: INT (i16).
+pub const BASE_TYPE_INT16: u32 = 2;
+/// Base type: DINT (i32).
+pub const BASE_TYPE_INT32: u32 = 3;
+/// Base type: REAL (f32).
+pub const BASE_TYPE_REAL32: u32 = 4;
+/// Base type: LREAL (f64).
+pub const BASE_TYPE_REAL64: u32 = 5;
+/// Base type: SINT (i8).
+pub const BASE_TYPE_INT8: u32 = 16;
+/// Base type: USINT/BYTE (u8).
+pub const BASE_TYPE_UINT8: u32 = 17;
+/// Base type: UINT/WORD (u16).
+pub const BASE_TYPE_UINT16: u32 = 18;
+/// Base type: UDINT/DWORD (u32).
+pub const BASE_TYPE_UINT32: u32 = 19;
+/// Base type: LINT (i64).
+pub const BASE_TYPE_INT64: u32 = 20;
+/// Base type: ULINT/LWORD (u64).
+pub const BASE_TYPE_UINT64: u32 = 21;
+/// Base type: STRING.
+pub const BASE_TYPE_STRING: u32 = 30;
+/// Base type: WSTRING.
+pub const BASE_TYPE_WSTRING: u32 = 31;
+/// Base type: REAL80 (f80).
+pub const BASE_TYPE_REAL80: u32 = 32;
+/// Base type: BOOL (u1).
+pub const BASE_TYPE_BIT: u32 = 33;
+/// Base type: other/compound type.
+pub const BASE_TYPE_BIGTYPE: u32 = 65;
+
 /// A handle to a variable within the ADS device.
 ///
 /// The handle is released automatically on drop.
 pub struct Handle {
     device: Device,
     handle: u32,
+    session_id: usize,
+    generation: usize,
 }
 
 impl Handle {
@@ -32,14 +119,33 @@ impl Handle {
         Ok(Self {
             device: device.clone(),
             handle: u32::from_le_bytes(handle_bytes),
+            session_id: device.client.session_id(),
+            generation: device.symbol_generation(),
         })
     }
 
+    fn from_raw(device: &Device, handle: u32, session_id: usize, generation: usize) -> Self {
+        Self {
+            device: device.clone(),
+            handle,
+            session_id,
+            generation,
+        }
+    }
+
     /// Return the raw handle.
     pub fn raw(&self) -> u32 {
         self.handle
     }
 
+    /// Check if the handle is still valid: the session has not been changed and the symbol
+    /// version has not been changed since the handle was created (the latter is detected only if
+    /// the symbol version is watched, see [`Device::watch_symbol_version`]).
+    pub fn is_valid(&self) -> bool {
+        self.session_id == self.device.client.session_id()
+            && self.generation == self.device.symbol_generation()
+    }
+
     /// Read data from the variable (returned data must match size of buffer).
     pub fn read(&self, buf: &mut [u8]) -> Result<()> {
         self.device
@@ -52,6 +158,17 @@ impl Handle {
             .write(index::RW_SYMVAL_BYHANDLE, self.handle, buf)
     }
 
+    /// Write data and read the reply in one transaction (returned data must match size of
+    /// buffer). Used to call methods (see [`crate::rpc`]).
+    pub fn write_read(&self, write_data: &[u8], read_data: &mut [u8]) -> Result<()> {
+        self.device.write_read_exact(
+            index::RW_SYMVAL_BYHANDLE,
+            self.handle,
+            write_data,
+            read_data,
+        )
+    }
+
     /// Read data of given type.
     ///
     /// Any type that supports `zerocopy::FromBytes` can be read.  You can also
@@ -82,6 +199,189 @@ impl Drop for Handle {
     }
 }
 
+/// A set of handles to variables within the ADS device, created with sum-up requests.
+///
+/// Resolving many symbols with [`Handle::new`] costs a round trip per symbol. The handle set
+/// resolves all of them with `SUMUP_READWRITE` requests of `GET_SYMHANDLE_BYNAME` and releases
+/// them with `SUMUP_WRITE` requests of `RELEASE_SYMHANDLE` (at most
+/// [`crate::client::MAX_SUMUP_REQUESTS`] symbols per request).
+///
+/// Symbols which have failed to resolve do not fail the whole set, see [`HandleSet::failed`].
+///
+/// The handles are released automatically on drop.
+#[allow(clippy::module_name_repetitions)]
+pub struct HandleSet {
+    device: Device,
+    handles: Vec<(String, u32)>,
+    failed: Vec<(String, Error)>,
+    session_id: usize,
+    generation: usize,
+}
+
+impl HandleSet {
+    /// Create handles for the given symbols.
+    ///
+    /// Returns an error only if a sum-up request itself has failed.
+    pub fn new<S: AsRef<str>>(device: &Device, symbols: &[S]) -> Result<Self> {
+        let session_id = device.client.session_id();
+        let generation = device.symbol_generation();
+        let mut set = Self {
+            device: device.clone(),
+            handles: Vec::with_capacity(symbols.len()),
+            failed: Vec::new(),
+            session_id,
+            generation,
+        };
+        for chunk in symbols.chunks(crate::client::MAX_SUMUP_REQUESTS) {
+            let mut buffers = vec![[0; 4]; chunk.len()];
+            let mut requests = chunk
+                .iter()
+                .zip(buffers.iter_mut())
+                .map(|(symbol, buf)| {
+                    WriteReadRequest::new(
+                        index::GET_SYMHANDLE_BYNAME,
+                        0,
+                        symbol.as_ref().as_bytes(),
+                        buf,
+                    )
+                })
+                .collect::<Result<Vec<_>>>()?;
+            device.write_read_multi(&mut requests)?;
+            for (symbol, request) in chunk.iter().zip(requests) {
+                let symbol = symbol.as_ref().to_owned();
+                match request.data() {
+                    Ok(data) => match data.try_into() {
+                        Ok(handle) => set.handles.push((symbol, u32::from_le_bytes(handle))),
+                        Err(_) => set
+                            .failed
+                            .push((symbol, Error::invalid_data("invalid handle length"))),
+                    },
+                    Err(e) => set.failed.push((symbol, e)),
+                }
+            }
+        }
+        Ok(set)
+    }
+
+    /// Return the number of resolved handles.
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Return true if no handles have been resolved.
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Return the raw handle of a symbol, if resolved.
+    pub fn get(&self, symbol: &str) -> Option<u32> {
+        self.handles
+            .iter()
+            .find(|(s, _)| s == symbol)
+            .map(|(_, h)| *h)
+    }
+
+    /// Iterate over resolved symbols and their raw handles.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.handles.iter().map(|(s, h)| (s.as_str(), *h))
+    }
+
+    /// Return symbols which have failed to resolve, together with the errors.
+    pub fn failed(&self) -> &[(String, Error)] {
+        &self.failed
+    }
+
+    pub(crate) fn session_id(&self) -> usize {
+        self.session_id
+    }
+
+    pub(crate) fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Check if the handles are still valid (see [`Handle::is_valid`]).
+    pub fn is_valid(&self) -> bool {
+        self.session_id == self.device.client.session_id()
+            && self.generation == self.device.symbol_generation()
+    }
+
+    /// Convert the set into individual handles. The handles are then released one-by-one on
+    /// drop.
+    pub fn into_handles(mut self) -> Vec<(String, Handle)> {
+        let device = self.device.clone();
+        let (session_id, generation) = (self.session_id, self.generation);
+        mem::take(&mut self.handles)
+            .into_iter()
+            .map(|(symbol, handle)| {
+                let handle = Handle::from_raw(&device, handle, session_id, generation);
+                (symbol, handle)
+            })
+            .collect()
+    }
+
+    /// Release all handles explicitly, reporting errors (which are ignored on drop).
+    pub fn release(mut self) -> Result<()> {
+        self.release_handles()
+    }
+
+    fn release_handles(&mut self) -> Result<()> {
+        let handles = mem::take(&mut self.handles);
+        // handles of a previous session are released by the device automatically
+        if handles.is_empty() || self.session_id != self.device.client.session_id() {
+            return Ok(());
+        }
+        let buffers = handles
+            .iter()
+            .map(|(_, h)| h.to_le_bytes())
+            .collect::<Vec<_>>();
+        for chunk in buffers.chunks(crate::client::MAX_SUMUP_REQUESTS) {
+            let mut requests = chunk
+                .iter()
+                .map(|buf| WriteRequest::new(index::RELEASE_SYMHANDLE, 0, buf))
+                .collect::<Result<Vec<_>>>()?;
+            self.device.write_multi(&mut requests)?;
+            for request in &requests {
+                request.ensure()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for HandleSet {
+    fn drop(&mut self) {
+        let _r = self.release_handles();
+    }
+}
+
+/// Get full symbol information by name, without uploading the whole symbol table.
+pub fn get_info(device: &Device, symbol: &str) -> Result<Symbol> {
+    let mut buf = vec![0; SYMBOL_INFO_BUF_SIZE];
+    let len = device.write_read(index::GET_SYMINFO_BYNAME_EX, 0, symbol.as_bytes(), &mut buf)?;
+    let mut ptr = buf.get(..len).ok_or_else(|| Error::io("buffer overflow"))?;
+    let entry_size = usize::try_from(ptr.read_u32::<LE>().map_err(Error::invalid_data)?)
+        .map_err(Error::invalid_data)?;
+    let entry_ptr = entry_size
+        .checked_sub(4)
+        .and_then(|size| ptr.get(..size))
+        .ok_or_else(|| Error::invalid_data("invalid symbol entry size"))?;
+    decode_symbol_entry(entry_ptr)
+}
+
+/// Get type information by name, without uploading the whole type inventory.
+pub fn get_type_info(device: &Device, typ: &str) -> Result<Type> {
+    let mut buf = vec![0; SYMBOL_INFO_BUF_SIZE];
+    let len = device.write_read(index::GET_DT_INFO_BYNAME_EX, 0, typ.as_bytes(), &mut buf)?;
+    let mut ptr = buf.get(..len).ok_or_else(|| Error::io("buffer overflow"))?;
+    let entry_size = usize::try_from(ptr.read_u32::<LE>().map_err(Error::invalid_data)?)
+        .map_err(Error::invalid_data)?;
+    let entry_ptr = entry_size
+        .checked_sub(4)
+        .and_then(|size| ptr.get(..size))
+        .ok_or_else(|| Error::invalid_data("invalid type entry size"))?;
+    decode_type_info(entry_ptr, None)?.ok_or_else(|| Error::invalid_data("no type"))
+}
+
 /// Get symbol size by name.
 pub fn get_size(device: &Device, symbol: &str) -> Result<usize> {
     let mut buf = [0; 12];
@@ -103,6 +403,7 @@ pub fn get_location(device: &Device, symbol: &str) -> Result<(u32, u32)> {
 }
 
 /// Represents a symbol in the PLC memory.
+#[derive(Clone, Debug)]
 pub struct Symbol {
     /// Hierarchical name of the symbol.
     pub name: String,
@@ -147,9 +448,14 @@ pub struct Symbol {
     /// - 0x4000 - Init on reset
     /// - 0x8000 - Extended flags present
     pub flags: u32,
+    /// Symbol comment.
+    pub comment: String,
+    /// Symbol attributes (pragmas, e.g. `{attribute 'OPC.UA.DA' := '1'}`), as (name, value).
+    pub attributes: Vec<(String, String)>,
 }
 
 /// Represents a type in the PLC's type inventory.
+#[derive(Clone, Debug)]
 pub struct Type {
     /// Name of the type.
     pub name: String,
@@ -186,9 +492,19 @@ pub struct Type {
     /// - 0x800000 - Is/Contains PLC pointer type
     /// - 0x01000000 - Refactor infos present
     pub flags: u32,
+    /// Type attributes, as (name, value).
+    pub attributes: Vec<(String, String)>,
+    /// If the type is an enum, all its values as (name, value). The values are stored in the
+    /// underlying integer type of the enum, which is specified by the type size and
+    /// [`Type::base_type`].
+    pub enums: Vec<(String, i64)>,
+    /// If the type is a function block, its methods which have type information (e.g. marked
+    /// with `{attribute 'TcRpcEnable'}`).
+    pub methods: Vec<Method>,
 }
 
 /// Represents a field of a structure type.
+#[derive(Clone, Debug)]
 pub struct Field {
     /// Name of the field.
     pub name: String,
@@ -207,16 +523,103 @@ pub struct Field {
     pub flags: u32,
 }
 
+/// Represents a method of a function block type.
+#[derive(Clone, Debug)]
+pub struct Method {
+    /// Name of the method.
+    pub name: String,
+    /// Return type name, empty if the method does not return a value.
+    pub return_type: String,
+    /// Size of the return value, in bytes.
+    pub return_size: usize,
+    /// Base type of the return value (see [`Symbol::base_type`]).
+    pub return_base_type: u32,
+    /// Method flags:
+    /// - 0x01 - PLC calling convention
+    /// - 0x02 - Call unlocked
+    /// - 0x04 - Not callable
+    /// - 0x08 - Attributes present
+    pub flags: u32,
+    /// Method comment.
+    pub comment: String,
+    /// Method parameters, in the declaration order.
+    pub params: Vec<MethodParam>,
+    /// Method attributes, as (name, value).
+    pub attributes: Vec<(String, String)>,
+}
+
+/// Represents a parameter of a method.
+#[derive(Clone, Debug)]
+pub struct MethodParam {
+    /// Name of the parameter.
+    pub name: String,
+    /// Type name of the parameter.
+    pub typ: String,
+    /// Size of the parameter, in bytes.
+    pub size: usize,
+    /// Base type (see [`Symbol::base_type`]).
+    pub base_type: u32,
+    /// Parameter flags:
+    /// - 0x01 - Input
+    /// - 0x02 - Output
+    /// - 0x04 - In/out
+    /// - 0x08 - By reference
+    pub flags: u32,
+    /// Parameter comment.
+    pub comment: String,
+}
+
 /// A mapping from type name to type.
 pub type TypeMap = HashMap<String, Type>;
 
-/// Get and decode symbol and type information from the PLC.
-pub fn get_symbol_info(device: &Device) -> Result<(Vec<Symbol>, TypeMap)> {
-    // Query the sizes of symbol and type info.
-    let mut read_data = [0; 64];
-    device.read_exact(index::SYM_UPLOAD_INFO2, 0, &mut read_data)?;
-    let symbol_len = LE::read_u32(&read_data[4..]) as usize;
-    let types_len = LE::read_u32(&read_data[12..]) as usize;
+/// Size of the `SYM_UPLOAD_INFO2` reply.
+pub(crate) const UPLOAD_INFO_SIZE: usize = 64;
+
+/// Raw symbol and type information, as uploaded from the PLC.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug)]
+pub struct SymbolUpload {
+    /// The `SYM_UPLOAD_INFO2` reply (symbol/type counts and sizes).
+    pub info: [u8; UPLOAD_INFO_SIZE],
+    /// The `SYM_UPLOAD` data.
+    pub symbol_data: Vec<u8>,
+    /// The `SYM_DT_UPLOAD` data.
+    pub type_data: Vec<u8>,
+}
+
+impl SymbolUpload {
+    /// Decode the uploaded data (see [`decode_symbol_info`]).
+    pub fn decode(self) -> Result<(Vec<Symbol>, TypeMap)> {
+        decode_symbol_info(self.symbol_data, self.type_data)
+    }
+}
+
+/// Get the current symbol version of the PLC. The version is incremented by the PLC on each
+/// online change.
+pub fn get_symbol_version(device: &Device) -> Result<u8> {
+    let mut version = [0; 1];
+    device.read_exact(index::GET_SYMVERSION, 0, &mut version)?;
+    Ok(version[0])
+}
+
+/// Upload raw symbol and type information from the PLC.
+pub fn upload_symbol_info(device: &Device) -> Result<SymbolUpload> {
+    let info = get_upload_info(device)?;
+    upload_symbol_data(device, info)
+}
+
+pub(crate) fn get_upload_info(device: &Device) -> Result<[u8; UPLOAD_INFO_SIZE]> {
+    let mut info = [0; UPLOAD_INFO_SIZE];
+    device.read_exact(index::SYM_UPLOAD_INFO2, 0, &mut info)?;
+    Ok(info)
+}
+
+pub(crate) fn upload_symbol_data(
+    device: &Device,
+    info: [u8; UPLOAD_INFO_SIZE],
+) -> Result<SymbolUpload> {
+    let symbol_len = LE::read_u32(&info[4..]) as usize;
+    let types_len = LE::read_u32(&info[12..]) as usize;
 
     // Query the type info.
     let mut type_data = vec![0; types_len];
@@ -226,7 +629,19 @@ pub fn get_symbol_info(device: &Device) -> Result<(Vec<Symbol>, TypeMap)> {
     let mut symbol_data = vec![0; symbol_len];
     device.read_exact(index::SYM_UPLOAD, 0, &mut symbol_data)?;
 
-    decode_symbol_info(symbol_data, type_data)
+    Ok(SymbolUpload {
+        info,
+        symbol_data,
+        type_data,
+    })
+}
+
+/// Get and decode symbol and type information from the PLC.
+///
+/// For large projects consider using [`crate::symcache::SymbolCache`] to avoid uploading the
+/// information at every start.
+pub fn get_symbol_info(device: &Device) -> Result<(Vec<Symbol>, TypeMap)> {
+    upload_symbol_info(device)?.decode()
 }
 
 /// Decode symbol and type information from the PLC.
@@ -235,155 +650,345 @@ pub fn get_symbol_info(device: &Device) -> Result<(Vec<Symbol>, TypeMap)> {
 /// respectively.
 ///
 /// Returns a list of symbols, and a map of type names to types.
-#[allow(clippy::too_many_lines, clippy::items_after_statements)]
 pub fn decode_symbol_info(
     symbol_data: Vec<u8>,
     type_data: Vec<u8>,
 ) -> Result<(Vec<Symbol>, TypeMap)> {
     // Decode the type info.
-    let mut buf = [0; 1024];
     let mut data_ptr = type_data.as_slice();
     let mut type_map = HashMap::new();
 
-    fn decode_type_info(mut ptr: &[u8], parent: Option<&mut Type>) -> Result<Option<Type>> {
-        let mut buf = [0; 1024];
-        let version = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
-        if version != 1 {
-            return Err(Error::invalid_data(format!(
-                "unknown type info version: {}",
-                version
-            )));
-        }
-        let _subitem_index = ptr.read_u16::<LE>().map_err(Error::invalid_data)?;
-        let _plc_interface_id = ptr.read_u16::<LE>().map_err(Error::invalid_data)?;
-        let _reserved = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
-        let size = ptr.read_u32::<LE>().map_err(Error::invalid_data)? as usize;
-        let offset = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
-        let base_type = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
-        let flags = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
-        let len_name = ptr.read_u16::<LE>().map_err(Error::invalid_data)? as usize;
-        let len_type = ptr.read_u16::<LE>().map_err(Error::invalid_data)? as usize;
-        let len_comment = ptr.read_u16::<LE>().map_err(Error::invalid_data)? as usize;
-        let array_dim = ptr.read_u16::<LE>().map_err(Error::invalid_data)?;
-        let sub_items = ptr.read_u16::<LE>().map_err(Error::invalid_data)?;
-        ptr.read_exact(&mut buf[..=len_name])
-            .map_err(Error::invalid_data)?;
-        let name = String::from_utf8_lossy(&buf[..len_name]).into_owned();
-        ptr.read_exact(&mut buf[..=len_type])
-            .map_err(Error::invalid_data)?;
-        let typ = String::from_utf8_lossy(&buf[..len_type]).into_owned();
-        ptr = &ptr[len_comment + 1..];
-
-        let mut array = vec![];
-        for _ in 0..array_dim {
-            let lower = ptr.read_i32::<LE>().map_err(Error::invalid_data)?;
-            let total = ptr.read_i32::<LE>().map_err(Error::invalid_data)?;
-            array.push((lower, lower + total - 1));
-        }
-
-        if let Some(parent) = parent {
-            assert_eq!(sub_items, 0);
-            // Offset -1 marks that the field is placed somewhere else in memory
-            // (e.g. AT %Mxx).
-            let offset = if offset == 0xFFFF_FFFF {
-                None
-            } else {
-                Some(offset)
-            };
-            parent.fields.push(Field {
-                name,
-                typ,
-                offset,
-                size,
-                array,
-                base_type,
-                flags,
-            });
-            Ok(None)
-        } else {
-            assert_eq!(offset, 0);
-            let mut typinfo = Type {
-                name,
-                size,
-                array,
-                base_type,
-                flags,
-                fields: Vec::new(),
-            };
-
-            for _ in 0..sub_items {
-                let sub_size = ptr.read_u32::<LE>().map_err(Error::invalid_data)? as usize;
-                let (sub_ptr, rest) = ptr.split_at(sub_size - 4);
-                decode_type_info(sub_ptr, Some(&mut typinfo))?;
-                ptr = rest;
-            }
-
-            // following fields (variable length), which we jump over:
-            // - type GUID if flags has Type GUID
-            // - copy mask of *size* bytes
-            // - # of methods and method entries if flags has Method infos
-            // - # of attributes and attributes if flags has Attributes
-            // - # of enum infos and enum infos if flags has Enum infos
-            // - refactor infos if flags has Refactor infos
-            // - splevels if flags has SP levels
-
-            Ok(Some(typinfo))
-        }
-    }
-
     while !data_ptr.is_empty() {
-        let entry_size = data_ptr.read_u32::<LE>().map_err(Error::invalid_data)? as usize;
-        let (entry_ptr, rest) = data_ptr.split_at(entry_size - 4);
+        let entry_ptr = read_entry(&mut data_ptr, "type")?;
         let typ =
             decode_type_info(entry_ptr, None)?.ok_or_else(|| Error::invalid_data("no type"))?;
         type_map.insert(typ.name.clone(), typ);
-        data_ptr = rest;
     }
 
     // Decode the symbol info.
     let mut symbols = Vec::new();
     let mut data_ptr = symbol_data.as_slice();
     while !data_ptr.is_empty() {
-        let entry_size = usize::try_from(data_ptr.read_u32::<LE>().map_err(Error::invalid_data)?)
-            .map_err(Error::invalid_data)?;
-        let (mut entry_ptr, rest) = data_ptr.split_at(entry_size - 4);
-        let ix_group = entry_ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
-        let ix_offset = entry_ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
-        let size = usize::try_from(entry_ptr.read_u32::<LE>().map_err(Error::invalid_data)?)
-            .map_err(Error::invalid_data)?;
-        let base_type = entry_ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
-        let flags = u32::from(entry_ptr.read_u16::<LE>().map_err(Error::invalid_data)?);
-        let _legacy_array_dim = entry_ptr.read_u16::<LE>().map_err(Error::invalid_data)?;
-        let len_name = usize::from(entry_ptr.read_u16::<LE>().map_err(Error::invalid_data)?);
-        let len_type = usize::from(entry_ptr.read_u16::<LE>().map_err(Error::invalid_data)?);
-        let _len_comment = usize::from(entry_ptr.read_u16::<LE>().map_err(Error::invalid_data)?);
-        entry_ptr
-            .read_exact(&mut buf[..=len_name])
-            .map_err(Error::invalid_data)?;
-        let name = String::from_utf8_lossy(&buf[..len_name]).into_owned();
-        entry_ptr
-            .read_exact(&mut buf[..=len_type])
-            .map_err(Error::invalid_data)?;
-        let typ = String::from_utf8_lossy(&buf[..len_type]).into_owned();
-        // following fields (variable length), which we jump over:
-        // - comment with \0
-        // - type GUID if flags has Type GUID
-        // - # of attributes and attribute entries if flags has Attributes
-        // - flags2 if flags has Extended flags
-        // - if flags2 has Old names
-
-        symbols.push(Symbol {
+        let entry_ptr = read_entry(&mut data_ptr, "symbol")?;
+        symbols.push(decode_symbol_entry(entry_ptr)?);
+    }
+
+    Ok((symbols, type_map))
+}
+
+// Decode a single type entry (without the leading entry length), as returned by `SYM_DT_UPLOAD`
+// and `GET_DT_INFO_BYNAME_EX`. Fields are added to the parent and `None` is returned.
+fn decode_type_info(mut ptr: &[u8], parent: Option<&mut Type>) -> Result<Option<Type>> {
+    let version = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
+    if version != 1 {
+        return Err(Error::invalid_data(format!(
+            "unknown type info version: {}",
+            version
+        )));
+    }
+    let _subitem_index = ptr.read_u16::<LE>().map_err(Error::invalid_data)?;
+    let _plc_interface_id = ptr.read_u16::<LE>().map_err(Error::invalid_data)?;
+    let _reserved = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
+    let size = ptr.read_u32::<LE>().map_err(Error::invalid_data)? as usize;
+    let offset = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
+    let base_type = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
+    let flags = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
+    let len_name = ptr.read_u16::<LE>().map_err(Error::invalid_data)? as usize;
+    let len_type = ptr.read_u16::<LE>().map_err(Error::invalid_data)? as usize;
+    let len_comment = ptr.read_u16::<LE>().map_err(Error::invalid_data)? as usize;
+    let array_dim = ptr.read_u16::<LE>().map_err(Error::invalid_data)?;
+    let sub_items = ptr.read_u16::<LE>().map_err(Error::invalid_data)?;
+    let name = read_string(&mut ptr, len_name)?;
+    let typ = read_string(&mut ptr, len_type)?;
+    skip(&mut ptr, len_comment + 1)?;
+
+    let mut array = vec![];
+    for _ in 0..array_dim {
+        let lower = ptr.read_i32::<LE>().map_err(Error::invalid_data)?;
+        let total = ptr.read_i32::<LE>().map_err(Error::invalid_data)?;
+        let upper = lower
+            .checked_add(total)
+            .and_then(|v| v.checked_sub(1))
+            .ok_or_else(|| Error::invalid_data(format!("{}: invalid array bounds", name)))?;
+        array.push((lower, upper));
+    }
+
+    if let Some(parent) = parent {
+        if sub_items != 0 {
+            return Err(Error::invalid_data(format!(
+                "{}: a field entry can not have sub-items",
+                name
+            )));
+        }
+        // Offset -1 marks that the field is placed somewhere else in memory
+        // (e.g. AT %Mxx).
+        let offset = if offset == 0xFFFF_FFFF {
+            None
+        } else {
+            Some(offset)
+        };
+        parent.fields.push(Field {
             name,
-            ix_group,
-            ix_offset,
             typ,
+            offset,
             size,
+            array,
             base_type,
             flags,
         });
+        Ok(None)
+    } else {
+        if offset != 0 {
+            return Err(Error::invalid_data(format!(
+                "{}: a type entry can not have an offset",
+                name
+            )));
+        }
+        let mut typinfo = Type {
+            name,
+            size,
+            array,
+            base_type,
+            flags,
+            fields: Vec::new(),
+            attributes: Vec::new(),
+            enums: Vec::new(),
+            methods: Vec::new(),
+        };
+
+        for _ in 0..sub_items {
+            let sub_ptr = read_entry(&mut ptr, "field")?;
+            decode_type_info(sub_ptr, Some(&mut typinfo))?;
+        }
+
+        // The optional fields are decoded on a best-effort basis: the type itself is still
+        // usable if they are malformed.
+        if let Err(error) = decode_type_extras(ptr, &mut typinfo) {
+            debug!(typ = typinfo.name, %error, "unable to decode type extras");
+        }
 
-        data_ptr = rest;
+        Ok(Some(typinfo))
     }
+}
 
-    Ok((symbols, type_map))
+// Decode a single symbol entry (without the leading entry length), as returned by `SYM_UPLOAD`
+// and `GET_SYMINFO_BYNAME_EX`.
+fn decode_symbol_entry(mut ptr: &[u8]) -> Result<Symbol> {
+    let ix_group = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
+    let ix_offset = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
+    let size = usize::try_from(ptr.read_u32::<LE>().map_err(Error::invalid_data)?)
+        .map_err(Error::invalid_data)?;
+    let base_type = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
+    let flags = u32::from(ptr.read_u16::<LE>().map_err(Error::invalid_data)?);
+    let _legacy_array_dim = ptr.read_u16::<LE>().map_err(Error::invalid_data)?;
+    let len_name = usize::from(ptr.read_u16::<LE>().map_err(Error::invalid_data)?);
+    let len_type = usize::from(ptr.read_u16::<LE>().map_err(Error::invalid_data)?);
+    let len_comment = usize::from(ptr.read_u16::<LE>().map_err(Error::invalid_data)?);
+    let name = read_string(&mut ptr, len_name)?;
+    let typ = read_string(&mut ptr, len_type)?;
+    let comment = read_string(&mut ptr, len_comment)?;
+    // The following fields are optional and may be missing in older TwinCAT versions.
+    if flags & SYMBOL_FLAG_TYPE_GUID != 0 && ptr.len() >= 16 {
+        ptr = &ptr[16..];
+    }
+    let mut attributes = Vec::new();
+    if flags & SYMBOL_FLAG_ATTRIBUTES != 0 && !ptr.is_empty() {
+        attributes = read_attributes(&mut ptr)?;
+    }
+    // - flags2 if flags has Extended flags
+    // - if flags2 has Old names
+    Ok(Symbol {
+        name,
+        ix_group,
+        ix_offset,
+        typ,
+        size,
+        base_type,
+        flags,
+        comment,
+        attributes,
+    })
+}
+
+// Decode variable-length fields which follow the sub-items of a type entry:
+// - type GUID if flags has Type GUID
+// - copy mask of *size* bytes if flags has Copy mask
+// - # of methods and method entries if flags has Method infos
+// - # of attributes and attributes if flags has Attributes
+// - # of enum infos and enum infos if flags has Enum infos
+// - refactor infos if flags has Refactor infos (ignored)
+// - splevels if flags has SP levels (ignored)
+fn decode_type_extras(mut ptr: &[u8], typinfo: &mut Type) -> Result<()> {
+    let flags = typinfo.flags;
+    if flags & TYPE_FLAG_TYPE_GUID != 0 {
+        skip(&mut ptr, 16)?;
+    }
+    if flags & TYPE_FLAG_COPY_MASK != 0 {
+        skip(&mut ptr, typinfo.size)?;
+    }
+    if flags & TYPE_FLAG_METHOD_INFOS != 0 {
+        let count = ptr.read_u16::<LE>().map_err(Error::invalid_data)?;
+        for _ in 0..count {
+            let len = ptr.read_u32::<LE>().map_err(Error::invalid_data)? as usize;
+            let entry_ptr = len
+                .checked_sub(4)
+                .and_then(|len| ptr.get(..len))
+                .ok_or_else(|| Error::invalid_data("invalid method entry size"))?;
+            typinfo.methods.push(decode_method_entry(entry_ptr)?);
+            ptr = &ptr[len - 4..];
+        }
+    }
+    if flags & TYPE_FLAG_ATTRIBUTES != 0 {
+        typinfo.attributes = read_attributes(&mut ptr)?;
+    }
+    if flags & TYPE_FLAG_ENUM_INFOS != 0 {
+        let signed = matches!(
+            typinfo.base_type,
+            BASE_TYPE_INT8 | BASE_TYPE_INT16 | BASE_TYPE_INT32 | BASE_TYPE_INT64
+        );
+        let count = ptr.read_u16::<LE>().map_err(Error::invalid_data)?;
+        for _ in 0..count {
+            let len_name = usize::from(ptr.read_u8().map_err(Error::invalid_data)?);
+            let name = read_string(&mut ptr, len_name)?;
+            let size = typinfo.size;
+            if size == 0 || size > 8 || ptr.len() < size {
+                return Err(Error::invalid_data("invalid enum value size"));
+            }
+            let value = if signed {
+                ptr.read_int::<LE>(size).map_err(Error::invalid_data)?
+            } else {
+                #[allow(clippy::cast_possible_wrap)]
+                let value = ptr.read_uint::<LE>(size).map_err(Error::invalid_data)? as i64;
+                value
+            };
+            typinfo.enums.push((name, value));
+        }
+    }
+    Ok(())
+}
+
+// Decode a single method entry (without the leading entry length):
+// - version, vtable index, return size, return align size, reserved (u32 each)
+// - return type GUID
+// - return base type, flags (u32 each)
+// - name, return type and comment lengths, parameter count (u16 each)
+// - name, return type, comment
+// - parameter entries
+// - attributes if flags has Attributes
+fn decode_method_entry(mut ptr: &[u8]) -> Result<Method> {
+    let _version = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
+    let _vtable_index = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
+    let return_size = usize::try_from(ptr.read_u32::<LE>().map_err(Error::invalid_data)?)
+        .map_err(Error::invalid_data)?;
+    let _return_align_size = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
+    let _reserved = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
+    skip(&mut ptr, 16)?;
+    let return_base_type = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
+    let flags = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
+    let len_name = usize::from(ptr.read_u16::<LE>().map_err(Error::invalid_data)?);
+    let len_return_type = usize::from(ptr.read_u16::<LE>().map_err(Error::invalid_data)?);
+    let len_comment = usize::from(ptr.read_u16::<LE>().map_err(Error::invalid_data)?);
+    let param_count = ptr.read_u16::<LE>().map_err(Error::invalid_data)?;
+    let name = read_string(&mut ptr, len_name)?;
+    let return_type = read_string(&mut ptr, len_return_type)?;
+    let comment = read_string(&mut ptr, len_comment)?;
+    let mut params = Vec::with_capacity(usize::from(param_count));
+    for _ in 0..param_count {
+        let len = ptr.read_u32::<LE>().map_err(Error::invalid_data)? as usize;
+        let entry_ptr = len
+            .checked_sub(4)
+            .and_then(|len| ptr.get(..len))
+            .ok_or_else(|| Error::invalid_data("invalid method parameter entry size"))?;
+        params.push(decode_method_param(entry_ptr)?);
+        ptr = &ptr[len - 4..];
+    }
+    let mut attributes = Vec::new();
+    if flags & METHOD_FLAG_ATTRIBUTES != 0 && !ptr.is_empty() {
+        attributes = read_attributes(&mut ptr)?;
+    }
+    Ok(Method {
+        name,
+        return_type,
+        return_size,
+        return_base_type,
+        flags,
+        comment,
+        params,
+        attributes,
+    })
+}
+
+// Decode a single method parameter entry (without the leading entry length):
+// - size, align size, base type, flags, reserved (u32 each)
+// - type GUID
+// - length-is parameter index, name, type and comment lengths (u16 each)
+// - name, type, comment
+fn decode_method_param(mut ptr: &[u8]) -> Result<MethodParam> {
+    let size = usize::try_from(ptr.read_u32::<LE>().map_err(Error::invalid_data)?)
+        .map_err(Error::invalid_data)?;
+    let _align_size = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
+    let base_type = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
+    let flags = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
+    let _reserved = ptr.read_u32::<LE>().map_err(Error::invalid_data)?;
+    skip(&mut ptr, 16)?;
+    let _length_is_para = ptr.read_u16::<LE>().map_err(Error::invalid_data)?;
+    let len_name = usize::from(ptr.read_u16::<LE>().map_err(Error::invalid_data)?);
+    let len_type = usize::from(ptr.read_u16::<LE>().map_err(Error::invalid_data)?);
+    let len_comment = usize::from(ptr.read_u16::<LE>().map_err(Error::invalid_data)?);
+    let name = read_string(&mut ptr, len_name)?;
+    let typ = read_string(&mut ptr, len_type)?;
+    let comment = read_string(&mut ptr, len_comment)?;
+    Ok(MethodParam {
+        name,
+        typ,
+        size,
+        base_type,
+        flags,
+        comment,
+    })
+}
+
+// Read an entry which starts with its length (including the length itself).
+fn read_entry<'a>(ptr: &mut &'a [u8], what: &str) -> Result<&'a [u8]> {
+    let len = usize::try_from(ptr.read_u32::<LE>().map_err(Error::invalid_data)?)
+        .map_err(Error::invalid_data)?;
+    let entry = len
+        .checked_sub(4)
+        .and_then(|len| ptr.get(..len))
+        .ok_or_else(|| Error::invalid_data(format!("invalid {} entry size", what)))?;
+    *ptr = &ptr[entry.len()..];
+    Ok(entry)
+}
+
+fn skip(ptr: &mut &[u8], len: usize) -> Result<()> {
+    if ptr.len() < len {
+        return Err(Error::invalid_data("unexpected end of data"));
+    }
+    *ptr = &ptr[len..];
+    Ok(())
+}
+
+// Read a Windows-1252 string of the given length, followed by a zero byte.
+fn read_string(ptr: &mut &[u8], len: usize) -> Result<String> {
+    if ptr.len() <= len {
+        return Err(Error::invalid_data("string out of bounds"));
+    }
+    let value = decode_1252_lossy(&ptr[..len]);
+    *ptr = &ptr[len + 1..];
+    Ok(value)
+}
+
+// Read attributes: u16 count, then u8 name length, u8 value length, name and value, both
+// followed by a zero byte.
+fn read_attributes(ptr: &mut &[u8]) -> Result<Vec<(String, String)>> {
+    let count = ptr.read_u16::<LE>().map_err(Error::invalid_data)?;
+    let mut attributes = Vec::with_capacity(usize::from(count));
+    for _ in 0..count {
+        let len_name = usize::from(ptr.read_u8().map_err(Error::invalid_data)?);
+        let len_value = usize::from(ptr.read_u8().map_err(Error::invalid_data)?);
+        let name = read_string(ptr, len_name)?;
+        let value = read_string(ptr, len_value)?;
+        attributes.push((name, value));
+    }
+    Ok(attributes)
 }
@@ -0,0 +1,173 @@
+//! Persistent on-disk cache of symbol uploads.
+//!
+//! Uploading symbol and type information ([`crate::symbol::get_symbol_info`]) of a large PLC
+//! project can take seconds. [`SymbolCache`] stores the raw upload in a local file together with
+//! the symbol version and the `SYM_UPLOAD_INFO2` header, and re-uses it as long as the PLC reports
+//! the same ones.
+//!
+//! Cache file format (all integers are little-endian):
+//!
+//! * magic `RPADSSYM` (8 bytes)
+//! * format version (u8)
+//! * symbol version (u8)
+//! * device address (8 bytes: NetId + port)
+//! * `SYM_UPLOAD_INFO2` header (64 bytes)
+//! * symbol data length (u32) and type data length (u32)
+//! * symbol data
+//! * type data
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use roboplc::{Error, Result};
+use tracing::{debug, warn};
+
+use crate::symbol::{self, Symbol, SymbolUpload, TypeMap, UPLOAD_INFO_SIZE};
+use crate::{AmsAddr, Device};
+
+const MAGIC: &[u8; 8] = b"RPADSSYM";
+const FORMAT_VERSION: u8 = 1;
+
+/// On-disk cache of symbol uploads for a single device.
+///
+/// Example:
+///
+/// ```rust,no_run
+/// use roboplc_io_ads::symcache::SymbolCache;
+/// # fn example(device: &roboplc_io_ads::Device) -> roboplc::Result<()> {
+/// let cache = SymbolCache::new("/var/cache/plc1.symbols");
+/// let (symbols, types) = cache.get_symbol_info(device)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct SymbolCache {
+    path: PathBuf,
+}
+
+impl SymbolCache {
+    /// Create a new cache which is stored in the given file.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Return the cache file path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get raw symbol and type information.
+    ///
+    /// The cached data is returned if the symbol version and the upload header reported by the
+    /// PLC match the cached ones. Otherwise the data is uploaded from the PLC and the cache file
+    /// is replaced atomically. Errors while storing the cache are logged only.
+    pub fn load(&self, device: &Device) -> Result<SymbolUpload> {
+        let version = symbol::get_symbol_version(device)?;
+        let info = symbol::get_upload_info(device)?;
+        match self.read(device.addr()) {
+            Ok((cached_version, upload)) if cached_version == version && upload.info == info => {
+                debug!(path = %self.path.display(), version, "symbol cache hit");
+                return Ok(upload);
+            }
+            Ok(_) => {
+                debug!(path = %self.path.display(), version, "symbol cache outdated");
+            }
+            Err(error) => {
+                debug!(path = %self.path.display(), %error, "symbol cache not loaded");
+            }
+        }
+        let upload = symbol::upload_symbol_data(device, info)?;
+        // do not store the data if an online change has happened during the upload
+        if symbol::get_symbol_version(device)? == version {
+            if let Err(error) = self.write(device.addr(), version, &upload) {
+                warn!(path = %self.path.display(), %error, "unable to store symbol cache");
+            }
+        }
+        Ok(upload)
+    }
+
+    /// Get and decode symbol and type information (see [`SymbolCache::load`]).
+    pub fn get_symbol_info(&self, device: &Device) -> Result<(Vec<Symbol>, TypeMap)> {
+        self.load(device)?.decode()
+    }
+
+    /// Remove the cache file, if exists.
+    pub fn invalidate(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn read(&self, addr: AmsAddr) -> Result<(u8, SymbolUpload)> {
+        let data = fs::read(&self.path)?;
+        let mut ptr = data.as_slice();
+        let mut magic = [0; 8];
+        ptr.read_exact(&mut magic).map_err(Error::invalid_data)?;
+        if &magic != MAGIC {
+            return Err(Error::invalid_data("invalid cache file magic"));
+        }
+        let format = ptr.read_u8().map_err(Error::invalid_data)?;
+        if format != FORMAT_VERSION {
+            return Err(Error::invalid_data(format!(
+                "unsupported cache format: {}",
+                format
+            )));
+        }
+        let version = ptr.read_u8().map_err(Error::invalid_data)?;
+        let cached_addr = AmsAddr::read_from(&mut ptr).map_err(Error::invalid_data)?;
+        if cached_addr != addr {
+            return Err(Error::invalid_data(format!(
+                "cache belongs to another device: {}",
+                cached_addr
+            )));
+        }
+        let mut info = [0; UPLOAD_INFO_SIZE];
+        ptr.read_exact(&mut info).map_err(Error::invalid_data)?;
+        let symbol_len = ptr.read_u32::<LE>().map_err(Error::invalid_data)? as usize;
+        let types_len = ptr.read_u32::<LE>().map_err(Error::invalid_data)? as usize;
+        if ptr.len() != symbol_len + types_len {
+            return Err(Error::invalid_data("cache file size mismatch"));
+        }
+        let (symbol_data, type_data) = ptr.split_at(symbol_len);
+        Ok((
+            version,
+            SymbolUpload {
+                info,
+                symbol_data: symbol_data.to_vec(),
+                type_data: type_data.to_vec(),
+            },
+        ))
+    }
+
+    fn write(&self, addr: AmsAddr, version: u8, upload: &SymbolUpload) -> Result<()> {
+        let mut data = Vec::with_capacity(
+            MAGIC.len() + 18 + UPLOAD_INFO_SIZE + upload.symbol_data.len() + upload.type_data.len(),
+        );
+        data.extend(MAGIC);
+        data.push(FORMAT_VERSION);
+        data.push(version);
+        addr.write_to(&mut data)?;
+        data.extend(upload.info);
+        data.write_u32::<LE>(
+            u32::try_from(upload.symbol_data.len()).map_err(Error::invalid_data)?,
+        )?;
+        data.write_u32::<LE>(u32::try_from(upload.type_data.len()).map_err(Error::invalid_data)?)?;
+        data.extend(&upload.symbol_data);
+        data.extend(&upload.type_data);
+        // write a temporary file and rename it, so the cache is never left half-written
+        let mut tmp_name = self.path.file_name().unwrap_or_default().to_owned();
+        tmp_name.push(".tmp");
+        let tmp_path = self.path.with_file_name(tmp_name);
+        {
+            let mut file = fs::File::create(&tmp_path)?;
+            file.write_all(&data)?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+}
@@ -4,6 +4,7 @@ use std::convert::{TryFrom, TryInto};
 use std::io::{Read, Write};
 use std::mem::size_of;
 use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
@@ -17,22 +18,49 @@ use zerocopy::{
 };
 
 use crate::client::{AddNotif, AdsHeader, IndexLength, IndexLengthRW};
+use crate::strings::encode_1252;
 use crate::{file, index};
 
 // Test modules.
 mod test_client;
+mod test_diff;
+mod test_enums;
+mod test_export;
+mod test_layout;
 mod test_netid;
+mod test_query;
+mod test_types;
 mod test_udp;
+mod test_vars;
+
+// The transmission mode and the cycle time (in ms) of the last added notification.
+type LastNotif = Arc<Mutex<Option<(u32, u32)>>>;
+
+// The port, options, request counters and the last added notification of the test server.
+type TestServer = (u16, Arc<Mutex<ServerOpts>>, Arc<ServerCounters>, LastNotif);
+
+// Requests of some kinds served by the test server.
+#[derive(Default)]
+pub struct ServerCounters {
+    // symbol uploads (SYM_UPLOAD reads)
+    uploads: AtomicUsize,
+    // handle lookups (GET_SYMHANDLE_BYNAME, including sum-up sub-requests)
+    handle_lookups: AtomicUsize,
+}
 
 // Since Cargo tests run multi-threaded, start one server per thread and
 // handle clients from the test functions in that thread.
 thread_local! {
-    pub static SERVER: Lazy<(u16, Arc<Mutex<ServerOpts>>)> = Lazy::new(|| {
+    pub static SERVER: Lazy<TestServer> = Lazy::new(|| {
         let opts = Arc::new(Mutex::new(ServerOpts::default()));
+        let counters = Arc::new(ServerCounters::default());
+        let last_notif = LastNotif::default();
 
         let socket = TcpListener::bind("127.0.0.1:0").unwrap();
         let port = socket.local_addr().unwrap().port();
         let opts_server = opts.clone();
+        let counters_server = counters.clone();
+        let last_notif_server = last_notif.clone();
         thread::spawn(move || {
             let mut server = Server {
                 opts: opts_server,
@@ -40,6 +68,8 @@ thread_local! {
                 data: vec![0; 1024],
                 file_ptr: None,
                 notif: None,
+                counters: counters_server,
+                last_notif: last_notif_server,
             };
             for client in socket.incoming().flatten() {
                 // We only need to handle one client concurrently.
@@ -47,7 +77,7 @@ thread_local! {
             }
         });
 
-        (port, opts)
+        (port, opts, counters, last_notif)
     });
 }
 
@@ -64,12 +94,28 @@ pub struct ServerOpts {
 
 pub fn config_test_server(opts: ServerOpts) -> u16 {
     SERVER.with(|obj| {
-        let (port, server_opts) = &**obj;
+        let (port, server_opts, _, _) = &**obj;
         *server_opts.lock().unwrap() = opts;
         *port
     })
 }
 
+// Returns the number of symbol uploads (SYM_UPLOAD reads) served by the test server.
+pub fn server_uploads() -> usize {
+    SERVER.with(|obj| obj.2.uploads.load(Ordering::SeqCst))
+}
+
+// Returns the number of symbol handle lookups served by the test server.
+pub fn server_handle_lookups() -> usize {
+    SERVER.with(|obj| obj.2.handle_lookups.load(Ordering::SeqCst))
+}
+
+// Returns the transmission mode and the cycle time (in ms) of the last notification added to the
+// test server.
+pub fn server_last_notif() -> Option<(u32, u32)> {
+    SERVER.with(|obj| *obj.3.lock().unwrap())
+}
+
 struct Server {
     opts: Arc<Mutex<ServerOpts>>,
     data: Vec<u8>,
@@ -79,6 +125,10 @@ struct Server {
     notif: Option<(usize, usize)>,
     // The simulated device state.
     state: (crate::AdsState, u16),
+    // Number of requests served.
+    counters: Arc<ServerCounters>,
+    // The last added notification.
+    last_notif: LastNotif,
 }
 
 impl Server {
@@ -246,10 +296,50 @@ impl Server {
         out.write_u32::<LE>(0).unwrap();
         // Simulate symbol access.
         if grp == index::RW_SYMVAL_BYHANDLE {
-            if off != 77 {
+            // symbols live in self.data
+            let Some(offset) = symbol_offset(off) else {
                 return (vec![], 0x710);
+            };
+            off = offset;
+        } else if grp == index::GET_SYMVERSION {
+            off = 1000; // symbol version is simulated with a memory byte
+        } else if grp == index::PLC_RW_MX {
+            // only single bit requests are supported
+            if len != 1 {
+                return (vec![], 0x702);
             }
-            off = 1020; // symbol lives at the end of self.data
+            let Some(byte) = self.data.get(off / 8) else {
+                return (vec![], 0x703);
+            };
+            out.write_u32::<LE>(1).unwrap();
+            out.push((byte >> (off % 8)) & 1);
+            return (out, 0);
+        } else if grp == index::SYM_UPLOAD_INFO2
+            || grp == index::SYM_UPLOAD
+            || grp == index::SYM_DT_UPLOAD
+        {
+            let (symbol_data, type_data) = symbol_upload();
+            let reply = match grp {
+                index::SYM_UPLOAD_INFO2 => {
+                    let mut info = vec![0; 64];
+                    LE::write_u32(&mut info[0..], u32::try_from(plc_symbols().len()).unwrap());
+                    LE::write_u32(&mut info[4..], u32::try_from(symbol_data.len()).unwrap());
+                    LE::write_u32(&mut info[8..], u32::try_from(plc_types().len()).unwrap());
+                    LE::write_u32(&mut info[12..], u32::try_from(type_data.len()).unwrap());
+                    info
+                }
+                index::SYM_UPLOAD => {
+                    self.counters.uploads.fetch_add(1, Ordering::SeqCst);
+                    symbol_data
+                }
+                _ => type_data,
+            };
+            if reply.len() != len {
+                return (vec![], 0x705);
+            }
+            out.write_u32::<LE>(request.length.get()).unwrap();
+            out.extend(reply);
+            return (out, 0);
         } else if grp != index::PLC_RW_M {
             return (vec![], 0x702);
         }
@@ -271,12 +361,28 @@ impl Server {
         let len = request.length.get() as usize;
 
         if grp == index::RW_SYMVAL_BYHANDLE {
-            if off != 77 {
+            let Some(offset) = symbol_offset(off) else {
                 return (vec![], 0x710);
+            };
+            off = offset;
+        } else if grp == index::PLC_RW_MX {
+            if len != 1 || data.len() != size_of::<IndexLength>() + 1 {
+                return (vec![], 0x705);
             }
-            off = 1020;
+            let Some(byte) = self.data.get_mut(off / 8) else {
+                return (vec![], 0x703);
+            };
+            if data[12] == 0 {
+                *byte &= !(1 << (off % 8));
+            } else {
+                *byte |= 1 << (off % 8);
+            }
+            return (0u32.to_le_bytes().into(), 0);
         } else if grp == index::RELEASE_SYMHANDLE {
-            if off != 77 {
+            if off != 0
+                || data.len() != 16
+                || symbol_offset(LE::read_u32(&data[12..]) as usize).is_none()
+            {
                 return (vec![], 0x710);
             }
             return (0u32.to_le_bytes().into(), 0);
@@ -421,6 +527,30 @@ impl Server {
                     return (vec![], 0x704);
                 }
             }
+            index::FILE_BROWSE => {
+                // entries are (name, attributes, size), the index offset is the entry index + 1
+                let entries: [(&[u8], u32, u64); 2] =
+                    [(b"Ma\xdf.txt", 0x20, 0x1_0000_0002), (b"boot", 0x10, 0)];
+                if (off == 1 && &data[16..] != b"C:\\Daten\\*.*")
+                    || (off != 1 && !data[16..].is_empty())
+                {
+                    return (vec![], 0x70C);
+                }
+                let Some((name, attrs, size)) = entries.get(off as usize - 1) else {
+                    return (vec![], 0x70C);
+                };
+                let mut entry = vec![0; 324];
+                LE::write_u32(&mut entry[..4], off + 1);
+                LE::write_u32(&mut entry[4..8], *attrs);
+                LE::write_u32(&mut entry[32..36], u32::try_from(size >> 32).unwrap());
+                LE::write_u32(
+                    &mut entry[36..40],
+                    u32::try_from(size & 0xFFFF_FFFF).unwrap(),
+                );
+                entry[48..48 + name.len()].copy_from_slice(name);
+                out.write_u32::<LE>(324).unwrap();
+                out.extend(entry);
+            }
             index::FILE_DELETE => {
                 if &data[16..] != b"/etc/passwd" {
                     return (vec![], 0x70C);
@@ -431,12 +561,83 @@ impl Server {
                 }
                 out.write_u32::<LE>(0).unwrap();
             }
-            index::GET_SYMHANDLE_BYNAME => {
-                if &data[16..] != b"SYMBOL" {
+            index::GET_SYMINFO_BYNAME_EX => {
+                let Some(symbol) = plc_symbols()
+                    .into_iter()
+                    .find(|s| s.name.as_bytes() == &data[16..])
+                else {
+                    return (vec![], 0x710);
+                };
+                let entry = symbol.encode();
+                if entry.len() > read_len {
+                    return (vec![], 0x705);
+                }
+                out.write_u32::<LE>(u32::try_from(entry.len()).unwrap())
+                    .unwrap();
+                out.extend(entry);
+            }
+            index::GET_DT_INFO_BYNAME_EX => {
+                let Some(typ) = plc_types()
+                    .into_iter()
+                    .find(|t| t.name.as_bytes() == &data[16..])
+                else {
                     return (vec![], 0x710);
+                };
+                let entry = typ.encode();
+                if entry.len() > read_len {
+                    return (vec![], 0x705);
                 }
+                out.write_u32::<LE>(u32::try_from(entry.len()).unwrap())
+                    .unwrap();
+                out.extend(entry);
+            }
+            // MAIN.fbMotor.Start(fSpeed, nMode, nCode => ...): stores the speed and returns
+            // speed > 0, nCode := nMode * 10.
+            index::RW_SYMVAL_BYHANDLE if off == 81 => {
+                if data.len() != 16 + 10 || read_len != 5 {
+                    return (vec![], 0x706);
+                }
+                let speed = LE::read_f64(&data[16..]);
+                let mode = LE::read_i16(&data[24..]);
+                self.data[700..708].copy_from_slice(&data[16..24]);
+                out.write_u32::<LE>(5).unwrap();
+                out.push(u8::from(speed > 0.0));
+                out.write_i32::<LE>(i32::from(mode) * 10).unwrap();
+            }
+            index::GET_SYMHANDLE_BYNAME => {
+                self.counters.handle_lookups.fetch_add(1, Ordering::SeqCst);
+                let Some((_, handle, _)) = SYMBOL_HANDLES
+                    .iter()
+                    .find(|(name, _, _)| name.as_bytes() == &data[16..])
+                else {
+                    return (vec![], 0x710);
+                };
                 out.write_u32::<LE>(4).unwrap();
-                out.write_u32::<LE>(77).unwrap();
+                out.write_u32::<LE>(*handle).unwrap();
+            }
+            index::GET_SYMINFO_BYNAME => {
+                let Some(symbol) = plc_symbols()
+                    .into_iter()
+                    .find(|s| s.name.as_bytes() == &data[16..])
+                else {
+                    return (vec![], 0x710);
+                };
+                out.write_u32::<LE>(12).unwrap();
+                out.write_u32::<LE>(symbol.ix_group).unwrap();
+                out.write_u32::<LE>(symbol.ix_offset).unwrap();
+                out.write_u32::<LE>(symbol.size).unwrap();
+            }
+            index::GET_SYMVAL_BYNAME => {
+                // only memory symbols are supported
+                let Some(symbol) = plc_symbols()
+                    .into_iter()
+                    .find(|s| s.name.as_bytes() == &data[16..] && s.ix_group == index::PLC_RW_M)
+                else {
+                    return (vec![], 0x710);
+                };
+                let len = read_len.min(symbol.size as usize);
+                out.write_u32::<LE>(u32::try_from(len).unwrap()).unwrap();
+                out.extend(&self.data[symbol.ix_offset as usize..][..len]);
             }
             _ => return (vec![], 0x702),
         }
@@ -448,16 +649,20 @@ impl Server {
             return (vec![], 0x706);
         }
         let request = AddNotif::read_from(data).unwrap();
-        let off = request.index_offset.get() as usize;
+        let mut off = request.index_offset.get() as usize;
         let len = request.length.get() as usize;
 
-        if request.index_group.get() != index::PLC_RW_M {
+        if request.index_group.get() == index::GET_SYMVERSION {
+            off = 1000;
+        } else if request.index_group.get() != index::PLC_RW_M {
             return (vec![], 0x702);
         }
         if off + len > self.data.len() {
             return (vec![], 0x703);
         }
         self.notif = Some((off, len));
+        *self.last_notif.lock().unwrap() =
+            Some((request.trans_mode.get(), request.cycle_time.get()));
         let mut out = 0u32.to_le_bytes().to_vec();
         out.write_u32::<LE>(132).unwrap(); // handle
         (out, 0)
@@ -479,6 +684,25 @@ impl Server {
     }
 }
 
+// Symbol handles supported by the test server: (name, handle, offset in the data).
+const SYMBOL_HANDLES: &[(&str, u32, usize)] = &[
+    ("SYMBOL", 77, 1020),
+    ("MAIN.pNode", 78, 600),
+    ("MAIN.pNode^", 79, 620),
+    ("MAIN.pNode^.pNext^", 80, 640),
+    ("MAIN.fbMotor#Start", 81, 700),
+    ("GVL_Recipe.sName", 82, 800),
+    ("GVL_Recipe.wsLabel", 83, 830),
+    ("GVL_Recipe.afValues", 84, 300),
+];
+
+fn symbol_offset(handle: usize) -> Option<usize> {
+    SYMBOL_HANDLES
+        .iter()
+        .find(|(_, h, _)| *h as usize == handle)
+        .map(|(_, _, offset)| *offset)
+}
+
 #[derive(AsBytes, FromBytes, Debug, Default)]
 #[repr(C)]
 struct SingleNotification {
@@ -489,3 +713,454 @@ struct SingleNotification {
     handle: U32<LE>,
     size: U32<LE>,
 }
+
+// An entry of the type upload data (either a type or a field of a type).
+#[derive(Default)]
+pub struct TypeEntry {
+    pub name: &'static str,
+    pub typ: &'static str,
+    pub comment: &'static str,
+    pub size: u32,
+    pub offset: u32,
+    pub base_type: u32,
+    pub flags: u32,
+    pub array: Vec<(i32, i32)>,
+    pub fields: Vec<TypeEntry>,
+    // Variable-length data following the entry (GUID, enum infos etc.).
+    pub extra: Vec<u8>,
+}
+
+impl TypeEntry {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.write_u32::<LE>(1).unwrap(); // version
+        out.write_u16::<LE>(0).unwrap(); // subitem index
+        out.write_u16::<LE>(0).unwrap(); // plc interface id
+        out.write_u32::<LE>(0).unwrap(); // reserved
+        out.write_u32::<LE>(self.size).unwrap();
+        out.write_u32::<LE>(self.offset).unwrap();
+        out.write_u32::<LE>(self.base_type).unwrap();
+        out.write_u32::<LE>(self.flags).unwrap();
+        // names and comments are encoded in Windows-1252
+        let strings = [self.name, self.typ, self.comment].map(|s| encode_1252(s).unwrap());
+        for s in &strings {
+            out.write_u16::<LE>(u16::try_from(s.len()).unwrap())
+                .unwrap();
+        }
+        out.write_u16::<LE>(u16::try_from(self.array.len()).unwrap())
+            .unwrap();
+        out.write_u16::<LE>(u16::try_from(self.fields.len()).unwrap())
+            .unwrap();
+        for s in strings {
+            out.extend(s);
+            out.push(0);
+        }
+        for (lower, upper) in &self.array {
+            out.write_i32::<LE>(*lower).unwrap();
+            out.write_i32::<LE>(upper - lower + 1).unwrap();
+        }
+        for field in &self.fields {
+            out.extend(field.encode());
+        }
+        out.extend(&self.extra);
+        with_length(out)
+    }
+}
+
+// An entry of the symbol upload data.
+#[derive(Default)]
+pub struct SymbolEntry {
+    pub name: &'static str,
+    pub typ: &'static str,
+    pub comment: &'static str,
+    pub ix_group: u32,
+    pub ix_offset: u32,
+    pub size: u32,
+    pub base_type: u32,
+    pub flags: u16,
+    // Variable-length data following the entry (GUID, attributes etc.).
+    pub extra: Vec<u8>,
+}
+
+impl SymbolEntry {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.write_u32::<LE>(self.ix_group).unwrap();
+        out.write_u32::<LE>(self.ix_offset).unwrap();
+        out.write_u32::<LE>(self.size).unwrap();
+        out.write_u32::<LE>(self.base_type).unwrap();
+        out.write_u16::<LE>(self.flags).unwrap();
+        out.write_u16::<LE>(0).unwrap(); // legacy array dim
+
+        // names and comments are encoded in Windows-1252
+        let strings = [self.name, self.typ, self.comment].map(|s| encode_1252(s).unwrap());
+        for s in &strings {
+            out.write_u16::<LE>(u16::try_from(s.len()).unwrap())
+                .unwrap();
+        }
+        for s in strings {
+            out.extend(s);
+            out.push(0);
+        }
+        out.extend(&self.extra);
+        with_length(out)
+    }
+}
+
+// Encodes attributes of a symbol or a type.
+pub fn encode_attributes(attributes: &[(&str, &str)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.write_u16::<LE>(u16::try_from(attributes.len()).unwrap())
+        .unwrap();
+    for (name, value) in attributes {
+        out.push(u8::try_from(name.len()).unwrap());
+        out.push(u8::try_from(value.len()).unwrap());
+        for s in [name, value] {
+            out.extend(s.as_bytes());
+            out.push(0);
+        }
+    }
+    out
+}
+
+// Encodes enum infos of a type with the given size.
+pub fn encode_enum_infos(values: &[(&str, i64)], size: usize) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.write_u16::<LE>(u16::try_from(values.len()).unwrap())
+        .unwrap();
+    for (name, value) in values {
+        out.push(u8::try_from(name.len()).unwrap());
+        out.extend(name.as_bytes());
+        out.push(0);
+        out.write_int::<LE>(*value, size).unwrap();
+    }
+    out
+}
+
+// A method of a function block type.
+#[derive(Default)]
+pub struct MethodEntry {
+    pub name: &'static str,
+    pub return_type: &'static str,
+    pub return_size: u32,
+    pub return_base_type: u32,
+    // (name, type, size, base type, flags)
+    pub params: Vec<(&'static str, &'static str, u32, u32, u32)>,
+    pub attributes: Vec<(&'static str, &'static str)>,
+}
+
+impl MethodEntry {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.write_u32::<LE>(1).unwrap(); // version
+        out.write_u32::<LE>(0).unwrap(); // vtable index
+        out.write_u32::<LE>(self.return_size).unwrap();
+        out.write_u32::<LE>(self.return_size).unwrap(); // align size
+        out.write_u32::<LE>(0).unwrap(); // reserved
+        out.extend([0; 16]); // return type GUID
+        out.write_u32::<LE>(self.return_base_type).unwrap();
+        let flags = if self.attributes.is_empty() { 0 } else { 0x08 };
+        out.write_u32::<LE>(flags).unwrap();
+        out.write_u16::<LE>(u16::try_from(self.name.len()).unwrap())
+            .unwrap();
+        out.write_u16::<LE>(u16::try_from(self.return_type.len()).unwrap())
+            .unwrap();
+        out.write_u16::<LE>(0).unwrap(); // comment
+        out.write_u16::<LE>(u16::try_from(self.params.len()).unwrap())
+            .unwrap();
+        for s in [self.name, self.return_type, ""] {
+            out.extend(s.as_bytes());
+            out.push(0);
+        }
+        for (name, typ, size, base_type, flags) in &self.params {
+            let mut param = Vec::new();
+            param.write_u32::<LE>(*size).unwrap();
+            param.write_u32::<LE>(*size).unwrap(); // align size
+            param.write_u32::<LE>(*base_type).unwrap();
+            param.write_u32::<LE>(*flags).unwrap();
+            param.write_u32::<LE>(0).unwrap(); // reserved
+            param.extend([0; 16]); // type GUID
+            param.write_u16::<LE>(0).unwrap(); // length-is parameter
+            param
+                .write_u16::<LE>(u16::try_from(name.len()).unwrap())
+                .unwrap();
+            param
+                .write_u16::<LE>(u16::try_from(typ.len()).unwrap())
+                .unwrap();
+            param.write_u16::<LE>(0).unwrap(); // comment
+            for s in [name, typ, &""] {
+                param.extend(s.as_bytes());
+                param.push(0);
+            }
+            out.extend(with_length(param));
+        }
+        if !self.attributes.is_empty() {
+            out.extend(encode_attributes(&self.attributes));
+        }
+        with_length(out)
+    }
+}
+
+// Encodes method infos of a type.
+pub fn encode_method_infos(methods: &[MethodEntry]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.write_u16::<LE>(u16::try_from(methods.len()).unwrap())
+        .unwrap();
+    for method in methods {
+        out.extend(method.encode());
+    }
+    out
+}
+
+// Prepends the entry length (including the length field itself).
+fn with_length(data: Vec<u8>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + 4);
+    out.write_u32::<LE>(u32::try_from(data.len() + 4).unwrap())
+        .unwrap();
+    out.extend(data);
+    out
+}
+
+// Types of the simulated PLC project.
+pub fn plc_types() -> Vec<TypeEntry> {
+    vec![
+        TypeEntry {
+            name: "FB_Motor",
+            size: 32,
+            base_type: 65,
+            flags: 0x801,
+            extra: encode_method_infos(&[MethodEntry {
+                name: "Start",
+                return_type: "BOOL",
+                return_size: 1,
+                return_base_type: 33,
+                params: vec![
+                    ("fSpeed", "LREAL", 8, 5, 0x01),
+                    ("nMode", "INT", 2, 2, 0x01),
+                    ("nCode", "DINT", 4, 3, 0x02),
+                ],
+                attributes: vec![("TcRpcEnable", "")],
+            }]),
+            ..Default::default()
+        },
+        TypeEntry {
+            name: "ST_Node",
+            size: 16,
+            base_type: 65,
+            flags: 1,
+            fields: vec![
+                TypeEntry {
+                    name: "nValue",
+                    typ: "DINT",
+                    size: 4,
+                    base_type: 3,
+                    flags: 2,
+                    ..Default::default()
+                },
+                TypeEntry {
+                    name: "pNext",
+                    typ: "POINTER TO ST_Node",
+                    size: 8,
+                    offset: 8,
+                    base_type: 65,
+                    flags: 2,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        },
+        TypeEntry {
+            name: "E_State",
+            typ: "INT",
+            size: 2,
+            base_type: 2,
+            flags: 0x2001,
+            extra: encode_enum_infos(&[("Idle", 0), ("Running", 3), ("Error", -1)], 2),
+            ..Default::default()
+        },
+        TypeEntry {
+            name: "ARRAY [1..3] OF REAL",
+            typ: "REAL",
+            size: 12,
+            base_type: 4,
+            flags: 1,
+            array: vec![(1, 3)],
+            ..Default::default()
+        },
+        TypeEntry {
+            name: "ST_Drive",
+            size: 16,
+            base_type: 65,
+            flags: 1,
+            fields: vec![
+                TypeEntry {
+                    name: "fSpeed",
+                    typ: "LREAL",
+                    size: 8,
+                    base_type: 5,
+                    flags: 2,
+                    ..Default::default()
+                },
+                TypeEntry {
+                    name: "nState",
+                    typ: "UINT",
+                    size: 2,
+                    offset: 8,
+                    base_type: 18,
+                    flags: 2,
+                    ..Default::default()
+                },
+                TypeEntry {
+                    name: "bEnabled",
+                    typ: "BOOL",
+                    size: 1,
+                    offset: 12,
+                    base_type: 33,
+                    flags: 2,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        },
+    ]
+}
+
+// Symbols of the simulated PLC project.
+#[allow(clippy::too_many_lines)]
+pub fn plc_symbols() -> Vec<SymbolEntry> {
+    vec![
+        SymbolEntry {
+            name: "MAIN.drive",
+            typ: "ST_Drive",
+            ix_group: index::PLC_RW_M,
+            ix_offset: 100,
+            size: 16,
+            base_type: 65,
+            ..Default::default()
+        },
+        SymbolEntry {
+            name: "MAIN.counter",
+            typ: "UDINT",
+            comment: "cycle counter",
+            ix_group: index::PLC_RW_M,
+            ix_offset: 200,
+            size: 4,
+            base_type: 19,
+            flags: 0x1008,
+            extra: [
+                &[0xAB; 16][..],
+                &encode_attributes(&[("OPC.UA.DA", "1"), ("unit", "cycles")]),
+            ]
+            .concat(),
+        },
+        SymbolEntry {
+            name: "GVL_Recipe.afValues",
+            typ: "ARRAY [1..3] OF REAL",
+            ix_group: index::PLC_RW_M,
+            ix_offset: 300,
+            size: 12,
+            base_type: 4,
+            flags: 1,
+            ..Default::default()
+        },
+        SymbolEntry {
+            name: "GVL_Recipe.stDrive",
+            typ: "ST_Drive",
+            ix_group: index::PLC_RW_M,
+            ix_offset: 320,
+            size: 16,
+            base_type: 65,
+            flags: 1,
+            ..Default::default()
+        },
+        SymbolEntry {
+            name: "GVL_Recipe.sName",
+            typ: "STRING(20)",
+            ix_group: index::PLC_RW_M,
+            ix_offset: 800,
+            size: 21,
+            base_type: 30,
+            ..Default::default()
+        },
+        SymbolEntry {
+            name: "GVL_Recipe.wsLabel",
+            typ: "WSTRING(10)",
+            ix_group: index::PLC_RW_M,
+            ix_offset: 830,
+            size: 22,
+            base_type: 31,
+            ..Default::default()
+        },
+        SymbolEntry {
+            name: "GVL_Recipe.fTemp",
+            typ: "REAL",
+            comment: "Solltemperatur in °C",
+            ix_group: index::PLC_RW_M,
+            ix_offset: 340,
+            size: 4,
+            base_type: 4,
+            ..Default::default()
+        },
+        SymbolEntry {
+            name: "MAIN.eState",
+            typ: "E_State",
+            ix_group: index::PLC_RW_M,
+            ix_offset: 400,
+            size: 2,
+            base_type: 2,
+            ..Default::default()
+        },
+        SymbolEntry {
+            name: "MAIN.stFlags.bReady",
+            typ: "BIT",
+            ix_group: index::PLC_RW_MX,
+            ix_offset: 500 * 8 + 3,
+            size: 1,
+            base_type: 33,
+            flags: 0x02,
+            ..Default::default()
+        },
+        SymbolEntry {
+            name: "MAIN.pNode",
+            typ: "POINTER TO ST_Node",
+            ix_group: index::PLC_RW_M,
+            ix_offset: 600,
+            size: 8,
+            base_type: 65,
+            ..Default::default()
+        },
+        SymbolEntry {
+            name: "MAIN.abStart",
+            typ: "ARRAY [0..3] OF BOOL",
+            ix_group: index::PLC_RW_M,
+            ix_offset: 510,
+            size: 4,
+            base_type: 33,
+            ..Default::default()
+        },
+        SymbolEntry {
+            name: "MAIN.fbMotor",
+            typ: "FB_Motor",
+            ix_group: index::PLC_RW_M,
+            ix_offset: 700,
+            size: 32,
+            base_type: 65,
+            flags: 0x40,
+            ..Default::default()
+        },
+    ]
+}
+
+// Returns the (SYM_UPLOAD, SYM_DT_UPLOAD) data of the simulated PLC project.
+pub fn symbol_upload() -> (Vec<u8>, Vec<u8>) {
+    (
+        plc_symbols().iter().flat_map(SymbolEntry::encode).collect(),
+        plc_types().iter().flat_map(TypeEntry::encode).collect(),
+    )
+}
+
+// Returns the symbols and types of the simulated PLC project, decoded from the upload data.
+pub fn decoded_symbol_info() -> (Vec<crate::symbol::Symbol>, crate::symbol::TypeMap) {
+    let (symbol_data, type_data) = symbol_upload();
+    crate::symbol::decode_symbol_info(symbol_data, type_data).unwrap()
+}
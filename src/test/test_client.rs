@@ -11,7 +11,7 @@ use roboplc::Error;
 use crate::test::{config_test_server, ServerOpts};
 use crate::{AmsAddr, AmsNetId, Client, Device, Source};
 
-fn run_test(opts: ServerOpts, f: impl Fn(Device)) {
+pub(super) fn run_test(opts: ServerOpts, f: impl Fn(Device)) {
     let timeouts = if let Some(tmo) = opts.timeout {
         Timeouts::new(tmo)
     } else {
@@ -180,6 +180,384 @@ fn test_symbolaccess() {
     });
 }
 
+#[test]
+fn test_handle_set() {
+    use crate::symbol::HandleSet;
+    use roboplc::io::IoMapping;
+    run_test(ServerOpts::default(), |device| {
+        let set = HandleSet::new(&device, &["SYMBOL", "blub", "SYMBOL"]).unwrap();
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.get("SYMBOL"), Some(77));
+        assert_eq!(set.get("blub"), None);
+        assert_eq!(set.failed().len(), 1);
+        assert_eq!(set.failed()[0].0, "blub");
+        assert!(matches!(set.failed()[0].1, Error::API(_, 0x710)));
+        assert!(set.is_valid());
+        set.release().unwrap();
+
+        let handles = HandleSet::new(&device, &["SYMBOL"]).unwrap().into_handles();
+        assert_eq!(handles.len(), 1);
+        handles[0].1.write_value(&0x0a0b_0c0d_u32).unwrap();
+        assert_eq!(handles[0].1.read_value::<u32>().unwrap(), 0x0a0b_0c0d);
+
+        // duplicate symbols share the handle
+        let mut mappings = device
+            .mappings(&[("SYMBOL", 4), ("blub", 4), ("SYMBOL", 4)])
+            .unwrap();
+        assert_eq!(mappings[0].read::<u32>().unwrap(), 0x0a0b_0c0d);
+        assert!(mappings[1].read::<u32>().is_err());
+        drop(mappings.remove(0));
+        assert_eq!(mappings[1].read::<u32>().unwrap(), 0x0a0b_0c0d);
+    });
+}
+
+#[test]
+fn test_bits() {
+    use crate::bits::BitAddress;
+    use crate::index::{IO_RW_QX, PLC_RW_MX};
+    run_test(ServerOpts::default(), |device| {
+        let addr: BitAddress = "%MX500.3".parse().unwrap();
+        assert_eq!(addr, BitAddress::new(PLC_RW_MX, 4003));
+        assert_eq!(addr.to_string(), "%MX500.3");
+        assert_eq!(
+            "qx2.7".parse::<BitAddress>().unwrap(),
+            BitAddress::from_byte(IO_RW_QX, 2, 7).unwrap()
+        );
+        assert!("%MX1.8".parse::<BitAddress>().is_err());
+        assert!("%MW1.1".parse::<BitAddress>().is_err());
+        assert!("%MX1".parse::<BitAddress>().is_err());
+
+        device.write(crate::index::PLC_RW_M, 500, &[0]).unwrap();
+        device.write_bit("%MX500.3", true).unwrap();
+        let mut buf = [0; 1];
+        device
+            .read_exact(crate::index::PLC_RW_M, 500, &mut buf)
+            .unwrap();
+        assert_eq!(buf, [0b1000]);
+        // a packed BIT member
+        assert!(device.read_bit("MAIN.stFlags.bReady").unwrap());
+        device.write_bit("MAIN.stFlags.bReady", false).unwrap();
+        assert!(!device.read_bit("%MX500.3").unwrap());
+
+        device
+            .write_bits("%MX500.6", &[true, false, true, true])
+            .unwrap();
+        assert_eq!(
+            device.read_bits("%MX500.5", 6).unwrap(),
+            [false, true, false, true, true, false]
+        );
+        assert_eq!(
+            device.read_bits("%MX500.0", 16).unwrap()[6..10],
+            [true, false, true, true]
+        );
+
+        // BOOL values
+        device
+            .write_bits("MAIN.abStart", &[true, false, false, true])
+            .unwrap();
+        assert_eq!(
+            device.read_bits("MAIN.abStart", 4).unwrap(),
+            [true, false, false, true]
+        );
+        assert!(device.read_bit("MAIN.abStart").unwrap());
+        assert!(device.read_bit("MAIN.none").is_err());
+    });
+}
+
+#[test]
+fn test_deref() {
+    use crate::deref::{deref, pointee_type, PointerKind};
+    use crate::index::PLC_RW_M;
+    use crate::symbol::get_symbol_info;
+    assert_eq!(
+        pointee_type("POINTER TO ST_Node"),
+        Some((PointerKind::Pointer, "ST_Node"))
+    );
+    assert_eq!(
+        pointee_type("reference to INT"),
+        Some((PointerKind::Reference, "INT"))
+    );
+    assert_eq!(pointee_type("POINTER TO "), None);
+    assert_eq!(pointee_type("ST_Node"), None);
+    run_test(ServerOpts::default(), |device| {
+        let (_, types) = get_symbol_info(&device).unwrap();
+        device.write(PLC_RW_M, 600, &[0; 8]).unwrap();
+        assert!(deref(&device, &types, "MAIN.pNode", "POINTER TO ST_Node")
+            .unwrap()
+            .is_none());
+
+        // MAIN.pNode -> node1 (42) -> node2 (-7) -> NULL
+        device
+            .write(PLC_RW_M, 600, &0x1000u64.to_le_bytes())
+            .unwrap();
+        let mut node1 = vec![0; 16];
+        node1[..4].copy_from_slice(&42i32.to_le_bytes());
+        node1[8..].copy_from_slice(&0x2000u64.to_le_bytes());
+        device.write(PLC_RW_M, 620, &node1).unwrap();
+        let mut node2 = vec![0; 16];
+        node2[..4].copy_from_slice(&(-7i32).to_le_bytes());
+        device.write(PLC_RW_M, 640, &node2).unwrap();
+
+        let node = deref(&device, &types, "MAIN.pNode", "POINTER TO ST_Node")
+            .unwrap()
+            .unwrap();
+        assert_eq!(node.path, "MAIN.pNode^");
+        assert_eq!(node.typ, "ST_Node");
+        assert_eq!(node.field_data("nValue").unwrap(), 42i32.to_le_bytes());
+        let node = node.deref_field(&device, &types, "pNext").unwrap().unwrap();
+        assert_eq!(node.path, "MAIN.pNode^.pNext^");
+        assert_eq!(node.field_data("nValue").unwrap(), (-7i32).to_le_bytes());
+        assert!(node
+            .deref_field(&device, &types, "pNext")
+            .unwrap()
+            .is_none());
+        assert!(node.deref_field(&device, &types, "nValue").is_err());
+        assert!(node.deref_field(&device, &types, "pPrev").is_err());
+
+        // references are dereferenced by the PLC
+        let value = deref(&device, &types, "MAIN.pNode^", "REFERENCE TO DINT")
+            .unwrap()
+            .unwrap();
+        assert_eq!(value.path, "MAIN.pNode^");
+        assert_eq!(value.data, 42i32.to_le_bytes());
+    });
+}
+
+#[test]
+fn test_call_method() {
+    use crate::rpc::{call, find_method};
+    use crate::symbol::{
+        get_type_info, METHOD_FLAG_NOT_CALLABLE, METHOD_PARAM_FLAG_IN, METHOD_PARAM_FLAG_OUT,
+    };
+    run_test(ServerOpts::default(), |device| {
+        let typ = get_type_info(&device, "FB_Motor").unwrap();
+        assert_eq!(typ.methods.len(), 1);
+        let method = find_method(&typ, "start").unwrap();
+        assert_eq!(method.name, "Start");
+        assert_eq!(method.return_type, "BOOL");
+        assert_eq!(method.return_size, 1);
+        assert_eq!(
+            method.attributes,
+            [("TcRpcEnable".to_owned(), String::new())]
+        );
+        let params = method
+            .params
+            .iter()
+            .map(|p| (p.name.as_str(), p.typ.as_str(), p.size, p.flags))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            params,
+            [
+                ("fSpeed", "LREAL", 8, METHOD_PARAM_FLAG_IN),
+                ("nMode", "INT", 2, METHOD_PARAM_FLAG_IN),
+                ("nCode", "DINT", 4, METHOD_PARAM_FLAG_OUT)
+            ]
+        );
+        assert!(get_type_info(&device, "FB_Missing").is_err());
+
+        let result = device
+            .call_method(
+                "MAIN.fbMotor",
+                "Start",
+                &[&1500.0f64.to_le_bytes(), &3i16.to_le_bytes()],
+            )
+            .unwrap();
+        assert_eq!(result.value, [1]);
+        assert_eq!(result.output("nCode").unwrap(), 30i32.to_le_bytes());
+        let mut buf = [0; 8];
+        device
+            .read_exact(crate::index::PLC_RW_M, 700, &mut buf)
+            .unwrap();
+        assert_eq!(buf, 1500.0f64.to_le_bytes());
+
+        let result = call(
+            &device,
+            "MAIN.fbMotor",
+            method,
+            &[&(-1.0f64).to_le_bytes(), &0i16.to_le_bytes()],
+        )
+        .unwrap();
+        assert_eq!(result.value, [0]);
+        assert_eq!(result.outputs, [("nCode".to_owned(), vec![0; 4])]);
+
+        assert!(matches!(
+            device.call_method("MAIN.fbMotor", "Start", &[&1500.0f64.to_le_bytes()]),
+            Err(Error::InvalidData(_))
+        ));
+        assert!(matches!(
+            device.call_method("MAIN.fbMotor", "Start", &[&[0; 4], &[0; 2]]),
+            Err(Error::InvalidData(_))
+        ));
+        assert!(matches!(
+            device.call_method("MAIN.fbMotor", "Stop", &[]),
+            Err(Error::InvalidData(_))
+        ));
+
+        // not callable methods are rejected before any request is sent
+        let mut method = method.clone();
+        method.flags |= METHOD_FLAG_NOT_CALLABLE;
+        assert!(matches!(
+            call(
+                &device,
+                "MAIN.fbMotor",
+                &method,
+                &[&1500.0f64.to_le_bytes(), &3i16.to_le_bytes()],
+            ),
+            Err(Error::InvalidData(_))
+        ));
+        device
+            .read_exact(crate::index::PLC_RW_M, 700, &mut buf)
+            .unwrap();
+        assert_eq!(buf, (-1.0f64).to_le_bytes());
+    });
+}
+
+#[test]
+fn test_symbol_version_watch() {
+    use crate::symbol::Handle;
+    use roboplc::io::IoMapping;
+    run_test(ServerOpts::default(), |device| {
+        let notif_chan = device.client.get_notification_channel();
+        let version_chan = device.client.get_symbol_version_channel();
+        device.write(crate::index::PLC_RW_M, 1000, &[5]).unwrap();
+
+        device.watch_symbol_version().unwrap();
+        // the first notification is sent by the test server before the handle is known to the
+        // client, but is still processed by the watch
+        assert!(notif_chan.try_recv().is_err());
+        // the second call is a no-op
+        device.watch_symbol_version().unwrap();
+        assert_eq!(device.symbol_generation(), 0);
+
+        let handle = Handle::new(&device, "SYMBOL").unwrap();
+        let mut mapping = device.mapping("SYMBOL", 4);
+        mapping.write(0x0102_0304_u32).unwrap();
+        assert!(handle.is_valid());
+
+        // simulate an online change
+        device.write(crate::index::PLC_RW_M, 1000, &[6]).unwrap();
+        assert_eq!(device.symbol_generation(), 1);
+        assert!(!handle.is_valid());
+        let event = version_chan.try_recv().unwrap();
+        assert_eq!(
+            event.addr,
+            AmsAddr::new(AmsNetId::new(1, 2, 3, 4, 5, 6), 851)
+        );
+        assert_eq!(event.version, 6);
+        assert!(version_chan.try_recv().is_err());
+
+        // the mapping re-creates the handle
+        assert_eq!(mapping.read::<u32>().unwrap(), 0x0102_0304);
+        assert!(Handle::new(&device, "SYMBOL").unwrap().is_valid());
+
+        // symbol version notifications are not passed to the notification channel
+        assert!(notif_chan.try_recv().is_err());
+
+        device.unwatch_symbol_version().unwrap();
+        assert_eq!(device.symbol_generation(), 0);
+    });
+}
+
+#[test]
+fn test_symbol_info() {
+    run_test(ServerOpts::default(), |device| {
+        let (symbols, types) = crate::symbol::get_symbol_info(&device).unwrap();
+        let symbol = |name: &str| symbols.iter().find(|s| s.name == name).unwrap();
+        let drive = symbol("MAIN.drive");
+        assert_eq!(drive.typ, "ST_Drive");
+        assert_eq!(drive.ix_offset, 100);
+        let counter = symbol("MAIN.counter");
+        assert_eq!(counter.size, 4);
+        assert_eq!(counter.comment, "cycle counter");
+        assert_eq!(counter.attributes.len(), 2);
+        let drive = &types["ST_Drive"];
+        assert_eq!(drive.size, 16);
+        assert_eq!(drive.fields.len(), 3);
+        assert_eq!(drive.fields[2].name, "bEnabled");
+        assert_eq!(drive.fields[2].offset, Some(12));
+    });
+}
+
+#[test]
+fn test_symbol_info_by_name() {
+    run_test(ServerOpts::default(), |device| {
+        let symbol = crate::symbol::get_info(&device, "MAIN.counter").unwrap();
+        assert_eq!(symbol.typ, "UDINT");
+        assert_eq!(symbol.ix_group, crate::index::PLC_RW_M);
+        assert_eq!(symbol.ix_offset, 200);
+        assert_eq!(symbol.size, 4);
+        assert_eq!(symbol.base_type, 19);
+        assert_eq!(symbol.comment, "cycle counter");
+        assert_eq!(
+            symbol.attributes,
+            [
+                ("OPC.UA.DA".to_owned(), "1".to_owned()),
+                ("unit".to_owned(), "cycles".to_owned())
+            ]
+        );
+        let symbol = crate::symbol::get_info(&device, "MAIN.drive").unwrap();
+        assert_eq!(symbol.typ, "ST_Drive");
+        assert!(symbol.attributes.is_empty());
+        assert!(matches!(
+            crate::symbol::get_info(&device, "MAIN.none"),
+            Err(Error::API(_, 0x710))
+        ));
+    });
+}
+
+#[test]
+fn test_symbol_cache() {
+    use crate::symcache::SymbolCache;
+    use crate::test::server_uploads;
+    use std::collections::BTreeSet;
+    run_test(ServerOpts::default(), |device| {
+        let path = std::env::temp_dir().join(format!(
+            "roboplc-io-ads-test-{}.symbols",
+            std::process::id()
+        ));
+        let cache = SymbolCache::new(&path);
+        cache.invalidate().unwrap();
+        device.write(crate::index::PLC_RW_M, 1000, &[1]).unwrap();
+
+        let names = |symbols: &[crate::symbol::Symbol]| {
+            symbols.iter().map(|s| s.name.clone()).collect::<Vec<_>>()
+        };
+        let uploads = server_uploads();
+        let (symbols, types) = cache.get_symbol_info(&device).unwrap();
+        let symbol_names = names(&symbols);
+        assert!(symbol_names.contains(&"MAIN.drive".to_owned()));
+        assert!(symbol_names.contains(&"MAIN.counter".to_owned()));
+        assert!(types.contains_key("ST_Drive"));
+        assert_eq!(server_uploads(), uploads + 1);
+        assert!(path.exists());
+
+        // the same version, the cache is used
+        let (symbols, cached_types) = cache.get_symbol_info(&device).unwrap();
+        assert_eq!(names(&symbols), symbol_names);
+        assert_eq!(
+            cached_types.keys().collect::<BTreeSet<_>>(),
+            types.keys().collect::<BTreeSet<_>>()
+        );
+        assert_eq!(server_uploads(), uploads + 1);
+
+        // the version has been changed, the cache is refreshed
+        device.write(crate::index::PLC_RW_M, 1000, &[2]).unwrap();
+        cache.get_symbol_info(&device).unwrap();
+        assert_eq!(server_uploads(), uploads + 2);
+        cache.get_symbol_info(&device).unwrap();
+        assert_eq!(server_uploads(), uploads + 2);
+
+        // a broken cache file is replaced
+        std::fs::write(&path, b"garbage").unwrap();
+        let (symbols, _) = cache.get_symbol_info(&device).unwrap();
+        assert_eq!(names(&symbols), symbol_names);
+        assert_eq!(server_uploads(), uploads + 3);
+
+        cache.invalidate().unwrap();
+        assert!(!path.exists());
+    });
+}
+
 #[test]
 fn test_notification() {
     use crate::notif::{Attributes, Sample, TransmissionMode};
@@ -201,6 +579,7 @@ fn test_notification() {
         // from the test server.
         let first = chan.try_recv().unwrap();
         let second = chan.try_recv().unwrap();
+        assert_eq!(first.source(), device.addr());
 
         println!("{:?}", first);
 
@@ -310,6 +689,78 @@ fn test_string_type() {
     });
 }
 
+#[test]
+fn test_string_1252() {
+    use crate::strings::{decode_1252, decode_1252_lossy, encode_1252, encode_1252_lossy};
+    use crate::strings::{Cp1252Error, String as PlcString};
+    type String8 = PlcString<8>;
+
+    assert_eq!(encode_1252("Maß €5").unwrap(), b"Ma\xdf \x805");
+    assert_eq!(decode_1252(b"\xc4nderung \x80").unwrap(), "Änderung €");
+    assert_eq!(encode_1252("a→b"), Err(Cp1252Error::Unmappable(1)));
+    assert_eq!(encode_1252_lossy("a→b"), b"a?b");
+    assert_eq!(decode_1252(b"a\x81"), Err(Cp1252Error::Undefined(1)));
+    assert_eq!(decode_1252_lossy(b"a\x81"), "a\u{81}");
+    assert_eq!(encode_1252_lossy("a\u{81}"), b"a\x81");
+    assert!(encode_1252("a\u{81}").is_err());
+
+    let s = String8::from_str_1252("Grüße").unwrap();
+    assert_eq!(s.as_bytes(), b"Gr\xfc\xdfe");
+    assert_eq!(s.to_string_1252().unwrap(), "Grüße");
+    assert!(String::try_from(s).is_err());
+    assert_eq!(
+        String8::from_str_1252("Übergröße"),
+        Err(Cp1252Error::TooLong)
+    );
+    assert_eq!(
+        String8::from_str_1252_lossy("Ω")
+            .unwrap()
+            .to_string_1252_lossy(),
+        "?"
+    );
+
+    run_test(ServerOpts::default(), |device| {
+        let entries = crate::file::read_dir(&device, "C:\\Daten").unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "Maß.txt");
+        assert_eq!(entries[0].raw_name, b"Ma\xdf.txt");
+        assert_eq!(entries[0].size, 0x1_0000_0002);
+        assert_eq!(entries[1].name, "boot");
+        assert_eq!(entries[1].attributes, 0x10);
+        assert!(crate::file::read_dir(&device, "C:\\Dätä→").is_err());
+
+        let symbol = crate::symbol::get_info(&device, "GVL_Recipe.fTemp").unwrap();
+        assert_eq!(symbol.comment, "Solltemperatur in °C");
+    });
+}
+
+#[test]
+fn test_file_1252() {
+    use crate::file::{File, READ};
+    run_test(ServerOpts::default(), |device| {
+        // names which can not be encoded are rejected before any request is sent
+        assert!(matches!(
+            File::open_1252(&device, "/etc/pässwd→", READ),
+            Err(Error::InvalidData(_))
+        ));
+        assert!(matches!(
+            File::delete_1252(&device, "/etc/pässwd→", 0),
+            Err(Error::InvalidData(_))
+        ));
+        // the server does not know the encoded name
+        assert!(matches!(
+            File::open_1252(&device, "/etc/pässwd", READ),
+            Err(Error::API(_, 0x70C))
+        ));
+        let mut file = File::open_1252(&device, "/etc/passwd", READ).unwrap();
+        let mut vec = vec![];
+        file.read_to_end(&mut vec).unwrap();
+        assert_eq!(vec.len(), 888);
+        drop(file);
+        File::delete_1252(&device, "/etc/passwd", 0).unwrap();
+    });
+}
+
 #[test]
 fn test_wstring_type() {
     type WString5 = crate::strings::WString<5>;
@@ -344,3 +795,500 @@ fn test_wstring_type() {
         assert!(<Vec<u16>>::from(ret) == [u16::from(b'a'), u16::from(b'b'), u16::from(b'c')]);
     });
 }
+
+#[test]
+fn test_runtime_sized() {
+    use crate::array::{ArraySymbol, PlcArray};
+    use crate::strings::{PlcString, PlcWString};
+    use crate::{AdsMapping, Handle};
+    use roboplc::io::IoMapping;
+
+    run_test(ServerOpts::default(), |device| {
+        let mut s = PlcString::for_symbol(&device, "GVL_Recipe.sName").unwrap();
+        assert_eq!(s.capacity(), 20);
+        assert_eq!(s.size(), 21);
+        assert!(s.is_empty());
+        s.set_str_1252("Rezept Größe").unwrap();
+        assert!(matches!(
+            s.set_str_1252("Rezept mit Übergröße 12"),
+            Err(Error::InvalidData(_))
+        ));
+        let handle = Handle::new(&device, "GVL_Recipe.sName").unwrap();
+        s.write(&handle).unwrap();
+        let mut s2 = PlcString::with_capacity(20);
+        s2.read(&handle).unwrap();
+        assert_eq!(s2, s);
+        assert_eq!(s2.to_string(), "Rezept Größe");
+        let mut mapping = AdsMapping::new(&device, "GVL_Recipe.sName", s.size());
+        let s3: PlcString = mapping.read().unwrap();
+        assert_eq!(s3.as_bytes(), b"Rezept Gr\xf6\xdfe");
+        assert_eq!(s3.capacity(), 20);
+
+        let mut ws = PlcWString::for_symbol(&device, "GVL_Recipe.wsLabel").unwrap();
+        assert_eq!(ws.capacity(), 10);
+        ws.set_str("Ωmega").unwrap();
+        assert!(matches!(
+            ws.set_str("abcdefghijk"),
+            Err(Error::InvalidData(_))
+        ));
+        let handle = Handle::new(&device, "GVL_Recipe.wsLabel").unwrap();
+        ws.write(&handle).unwrap();
+        let mut mapping = AdsMapping::new(&device, "GVL_Recipe.wsLabel", ws.size());
+        let ws2: PlcWString = mapping.read().unwrap();
+        assert_eq!(ws2.to_string(), "Ωmega");
+        assert_eq!(ws2.len(), 5);
+
+        let values = ArraySymbol::<f32>::new(&device, "GVL_Recipe.afValues").unwrap();
+        assert_eq!(values.len(), 3);
+        values.write_range(0, &[1.0, 2.0, 3.0]).unwrap();
+        values.write_range(1, &[2.5, 3.5]).unwrap();
+        assert!(values.write_range(2, &[1.0, 2.0]).is_err());
+        assert_eq!(values.read().unwrap().into_vec(), [1.0, 2.5, 3.5]);
+        assert_eq!(values.read_range(1..).unwrap().into_vec(), [2.5, 3.5]);
+        assert_eq!(values.read_range(..=0).unwrap().into_vec(), [1.0]);
+        assert!(values.read_range(2..2).unwrap().is_empty());
+        assert!(values.read_range(2..4).is_err());
+        assert!(ArraySymbol::<f64>::new(&device, "GVL_Recipe.afValues").is_err());
+
+        let handle = Handle::new(&device, "GVL_Recipe.afValues").unwrap();
+        let mut arr = PlcArray::<f32>::read(&handle, 3).unwrap();
+        assert_eq!(arr.len(), 3);
+        arr[0] = 0.5;
+        arr.write(&handle).unwrap();
+        let mut mapping = AdsMapping::new(&device, "GVL_Recipe.afValues", 12);
+        let arr2: PlcArray<f32> = mapping.read().unwrap();
+        assert_eq!(arr2, arr);
+        mapping
+            .write(PlcArray::from(vec![4.0f32, 5.0, 6.0]))
+            .unwrap();
+        assert_eq!(values.read_range(2..).unwrap().into_vec(), [6.0]);
+    });
+}
+
+#[test]
+fn test_process_image() {
+    use crate::strings::PlcString;
+
+    run_test(ServerOpts::default(), |device| {
+        let mut image = device.process_image();
+        let symbol = image.add_type::<u32>("SYMBOL").unwrap();
+        let values = image.add("GVL_Recipe.afValues", 12).unwrap();
+        let missing = image.add_type::<u32>("MAIN.missing").unwrap();
+        let name = image.add("GVL_Recipe.sName", 21).unwrap();
+        assert!(image.add_type::<u32>("SYMBOL").is_err());
+        assert_eq!(image.len(), 4);
+        assert_eq!(image.symbol(name), Some("GVL_Recipe.sName"));
+        // nothing read yet
+        assert!(image.data(symbol).is_err());
+        // nothing to write
+        image.write().unwrap();
+
+        image.set(symbol, 0x1234_5678u32).unwrap();
+        let mut data = Vec::new();
+        for value in [1.0f32, 2.0, 3.0] {
+            data.extend(value.to_le_bytes());
+        }
+        image.set_data(values, &data).unwrap();
+        let mut s = PlcString::with_capacity(20);
+        s.set_str_1252("image").unwrap();
+        image.set(name, s.clone()).unwrap();
+        // all valid symbols are written, the missing one is reported
+        image.set(missing, 1u32).unwrap();
+        let err = image.write().unwrap_err().to_string();
+        assert!(err.contains("MAIN.missing"), "{}", err);
+        assert!(!err.contains("SYMBOL"), "{}", err);
+        // values are not retained
+        image.write().unwrap();
+
+        image.read().unwrap();
+        assert_eq!(image.get::<u32>(symbol).unwrap(), 0x1234_5678);
+        assert_eq!(image.data(values).unwrap(), data);
+        assert_eq!(image.get::<PlcString>(name).unwrap(), s);
+        let err = image.get::<u32>(missing).unwrap_err().to_string();
+        assert!(err.contains("MAIN.missing"), "{}", err);
+        assert!(image.get::<u32>(10).is_err());
+    });
+}
+
+#[test]
+fn test_process_image_unresolved() {
+    use crate::test::server_handle_lookups;
+
+    run_test(ServerOpts::default(), |device| {
+        let mut image = device.process_image();
+        let symbol = image.add_type::<u32>("SYMBOL").unwrap();
+        let missing = image.add_type::<u32>("MAIN.missing").unwrap();
+        image.read().unwrap();
+        let lookups = server_handle_lookups();
+        let sets = image.handle_set_count();
+        assert_eq!(sets, 1);
+        // the failed symbol is not looked up again until an online change
+        for _ in 0..3 {
+            image.read().unwrap();
+            image.set(missing, 1u32).unwrap();
+            assert!(image.write().is_err());
+            assert!(image.get::<u32>(symbol).is_ok());
+            assert!(image.get::<u32>(missing).is_err());
+        }
+        assert_eq!(server_handle_lookups(), lookups);
+        assert_eq!(image.handle_set_count(), sets);
+
+        // an image with no resolved symbols keeps no handle sets
+        let mut image = device.process_image();
+        image.add_type::<u32>("MAIN.missing").unwrap();
+        image.read().unwrap();
+        image.read().unwrap();
+        assert_eq!(server_handle_lookups(), lookups + 1);
+        assert_eq!(image.handle_set_count(), 0);
+    });
+}
+
+#[test]
+fn test_index_mapping() {
+    use crate::{index, AdsIndexMapping};
+    use roboplc::io::IoMapping;
+
+    run_test(ServerOpts::default(), |device| {
+        let mut mapping = AdsIndexMapping::for_symbol(&device, "GVL_Recipe.afValues", 12).unwrap();
+        assert_eq!(mapping.index_group(), index::PLC_RW_M);
+        assert_eq!(mapping.index_offset(), 300);
+        mapping.write([1u32, 2, 3]).unwrap();
+        let mut by_handle = device.mapping("GVL_Recipe.afValues", 12);
+        assert_eq!(by_handle.read::<[u32; 3]>().unwrap(), [1, 2, 3]);
+        // a part of the area
+        let mut mapping = device.index_mapping(index::PLC_RW_M, 304, 4);
+        assert_eq!(mapping.read::<u32>().unwrap(), 2);
+        mapping.write(4u32).unwrap();
+        assert_eq!(by_handle.read::<[u32; 3]>().unwrap(), [1, 4, 3]);
+        // the server rejects areas it does not have
+        let mut mapping = device.index_mapping(index::IO_RW_I, 0, 4);
+        assert!(mapping.read::<u32>().is_err());
+        assert!(AdsIndexMapping::for_symbol(&device, "MAIN.missing", 4).is_err());
+    });
+}
+
+#[test]
+fn test_cached_mapping() {
+    use crate::test::server_last_notif;
+    use crate::{index, AdsCachedMapping};
+    use roboplc::io::IoMapping;
+
+    run_test(ServerOpts::default(), |device| {
+        let notif_chan = device.client.get_notification_channel();
+        device
+            .write(index::PLC_RW_M, 300, &[1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0])
+            .unwrap();
+        let mapping = device.cached_mapping("GVL_Recipe.afValues", 12).unwrap();
+        assert_eq!(server_last_notif(), Some((4, 0)));
+        // the notification is re-added as cyclic, to refresh unchanged values
+        let mut mapping = mapping.with_max_age(Duration::from_millis(200));
+        assert!(notif_chan.try_recv().is_err());
+        assert_eq!(mapping.read::<[u32; 3]>().unwrap(), [1, 2, 3]);
+        assert_eq!(server_last_notif(), Some((3, 100)));
+        assert!(mapping.age().unwrap() < Duration::from_millis(200));
+
+        // each request generates a notification from the test server
+        device.write(index::PLC_RW_M, 304, &[5, 0, 0, 0]).unwrap();
+        assert_eq!(mapping.read::<[u32; 3]>().unwrap(), [1, 5, 3]);
+        assert_eq!(
+            mapping.timestamp().unwrap(),
+            Timestamp::from_nanos(1_234_567_890_123_456_789 / 100 * 100)
+        );
+        mapping.write([7u32, 8, 9]).unwrap();
+        assert_eq!(mapping.read::<[u32; 3]>().unwrap(), [7, 8, 9]);
+        // cached samples are not passed to the notification channel
+        assert!(notif_chan.try_recv().is_err());
+
+        std::thread::sleep(Duration::from_millis(300));
+        assert!(matches!(mapping.read::<[u32; 3]>(), Err(Error::Timeout)));
+        device.get_state().unwrap();
+        assert_eq!(mapping.read::<[u32; 3]>().unwrap(), [7, 8, 9]);
+
+        // the notification is deleted on drop
+        drop(mapping);
+        assert!(device.delete_notification(132).is_err());
+        assert!(AdsCachedMapping::new(&device, "MAIN.missing", 4).is_err());
+    });
+}
+
+#[test]
+fn test_change_only_writes() {
+    use crate::{index, AdsMapping};
+    use roboplc::io::IoMapping;
+
+    run_test(ServerOpts::default(), |device| {
+        let plc_data = || {
+            let mut buf = [0; 12];
+            device.read_exact(index::PLC_RW_M, 300, &mut buf).unwrap();
+            buf
+        };
+        device
+            .write(index::PLC_RW_M, 300, &[1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0])
+            .unwrap();
+        let mut mapping =
+            AdsMapping::new(&device, "GVL_Recipe.afValues", 12).with_change_only_writes(2);
+        assert_eq!(mapping.read::<[u32; 3]>().unwrap(), [1, 2, 3]);
+        // the PLC modifies the first element
+        device.write(index::PLC_RW_M, 300, &[10]).unwrap();
+        mapping.write([1u32, 5, 3]).unwrap();
+        assert_eq!(plc_data(), [10, 0, 0, 0, 5, 0, 0, 0, 3, 0, 0, 0]);
+        // two ranges are written with a sum-up request
+        mapping.write([1u32, 6, 0x0100]).unwrap();
+        assert_eq!(plc_data(), [10, 0, 0, 0, 6, 0, 0, 0, 0, 1, 0, 0]);
+        // nothing has been changed
+        device.write(index::PLC_RW_M, 304, &[7]).unwrap();
+        mapping.write([1u32, 6, 0x0100]).unwrap();
+        assert_eq!(plc_data()[4], 7);
+        // too many ranges, the full data is written
+        mapping.write([2u32, 7, 4]).unwrap();
+        assert_eq!(plc_data(), [2, 0, 0, 0, 7, 0, 0, 0, 4, 0, 0, 0]);
+        // no previous image
+        let mut mapping =
+            AdsMapping::new(&device, "GVL_Recipe.afValues", 12).with_change_only_writes(2);
+        mapping.write([1u32, 2, 3]).unwrap();
+        assert_eq!(plc_data(), [1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0]);
+    });
+}
+
+#[test]
+fn test_mapping_options() {
+    use crate::{index, AdsMapping, AdsMappingOptions};
+    use roboplc::io::binrw::Endian;
+    use roboplc::io::IoMapping;
+
+    run_test(ServerOpts::default(), |device| {
+        device
+            .write(index::PLC_RW_M, 300, &[1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0])
+            .unwrap();
+        // a part of the symbol, the buffer is extended to fit it
+        let mut mapping = AdsMapping::new(&device, "GVL_Recipe.afValues", 0)
+            .with_options(AdsMappingOptions::new().range(4, 8).verify(true));
+        assert_eq!(mapping.read::<[u32; 2]>().unwrap(), [2, 3]);
+        mapping.write(5u32).unwrap();
+        assert!(mapping.write([1u32, 2, 3]).is_err());
+        let mut full = device.mapping("GVL_Recipe.afValues", 12);
+        assert_eq!(full.read::<[u32; 3]>().unwrap(), [1, 5, 3]);
+
+        let mut mapping = AdsMapping::new(&device, "GVL_Recipe.afValues", 12)
+            .with_options(AdsMappingOptions::new().endian(Endian::Big));
+        mapping.write([1u32, 2, 3]).unwrap();
+        assert_eq!(
+            full.read::<[u32; 3]>().unwrap(),
+            [1 << 24, 2 << 24, 3 << 24]
+        );
+        assert_eq!(mapping.read::<[u32; 3]>().unwrap(), [1, 2, 3]);
+
+        // the test server creates no handle for the symbol
+        let mut mapping = AdsMapping::new(&device, "GVL_Recipe.stDrive", 16);
+        assert!(mapping.read::<[u8; 16]>().is_err());
+        let mut mapping = AdsMapping::new(&device, "GVL_Recipe.stDrive", 16)
+            .with_options(AdsMappingOptions::new().fallback_by_name(true).verify(true));
+        mapping.write([7u8; 16]).unwrap();
+        assert_eq!(mapping.read::<[u8; 16]>().unwrap(), [7; 16]);
+    });
+}
+
+#[test]
+fn test_mapping_auto_sized() {
+    use roboplc::io::IoMapping;
+
+    run_test(ServerOpts::default(), |device| {
+        let mut mapping = device.mapping_auto_sized("GVL_Recipe.afValues");
+        mapping.write([1u32, 2, 3]).unwrap();
+        assert_eq!(mapping.read::<[u32; 3]>().unwrap(), [1, 2, 3]);
+        let Err(Error::InvalidData(err)) = mapping.read::<[u32; 2]>() else {
+            panic!("size mismatch not detected");
+        };
+        assert_eq!(
+            err,
+            "GVL_Recipe.afValues: the type has read 8 bytes, the symbol holds 12"
+        );
+        let Err(Error::InvalidData(err)) = mapping.read::<[u32; 4]>() else {
+            panic!("size mismatch not detected");
+        };
+        assert!(err.contains("requires more bytes"), "{}", err);
+        let Err(Error::InvalidData(err)) = mapping.write([1u32, 2]) else {
+            panic!("size mismatch not detected");
+        };
+        assert!(err.contains("has written 8 bytes"), "{}", err);
+        assert!(device
+            .mapping_auto_sized("MAIN.missing")
+            .read::<u32>()
+            .is_err());
+    });
+}
+
+#[test]
+fn test_notification_routing() {
+    use crate::notif::{Attributes, TransmissionMode};
+    use roboplc::io::IoMapping;
+    use std::sync::{Arc, Mutex};
+
+    run_test(ServerOpts::default(), |device| {
+        let chan = device.client.get_notification_channel();
+        let attrib = Attributes::new(
+            4,
+            TransmissionMode::ServerOnChange,
+            Duration::from_secs(1),
+            Duration::from_secs(1),
+        );
+        device.write(0x4020, 0, &[4, 4, 1, 1]).unwrap();
+        let subscription = device
+            .add_notification_channel(0x4020, 0, &attrib, 16)
+            .unwrap();
+        assert_eq!(subscription.source(), device.addr());
+        device.write(0x4020, 0, &[8, 8, 1, 1]).unwrap();
+        let rx = subscription.receiver().unwrap();
+        // the first sample is sent before the handle is known to the client, but still routed
+        let mut first = rx.try_recv().unwrap();
+        assert_eq!(first.source, device.addr());
+        assert_eq!(first.handle, subscription.handle());
+        assert_eq!(first.read::<u32>().unwrap(), 0x0101_0404);
+        assert_eq!(rx.try_recv().unwrap().data, [8, 8, 1, 1]);
+        assert!(rx.try_recv().is_err());
+        // routed samples are not passed to the common channel
+        assert!(chan.try_recv().is_err());
+        // the notification is deleted on drop
+        let handle = subscription.handle();
+        drop(subscription);
+        assert!(device.delete_notification(handle).is_err());
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let r = received.clone();
+        let subscription = device
+            .add_notification_callback(0x4020, 0, &attrib, move |sample| {
+                r.lock().unwrap().push(sample.data.to_vec());
+            })
+            .unwrap();
+        assert!(subscription.receiver().is_none());
+        device.write(0x4020, 0, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(
+            *received.lock().unwrap(),
+            [vec![8, 8, 1, 1], vec![1, 2, 3, 4]]
+        );
+        assert!(chan.try_recv().is_err());
+        drop(subscription);
+        device.write(0x4020, 0, &[0; 4]).unwrap();
+        assert_eq!(received.lock().unwrap().len(), 2);
+        assert!(chan.try_recv().is_err());
+    });
+}
+
+#[test]
+fn test_typed_subscription() {
+    use crate::index;
+    use crate::notif::{decode_from_bytes, Attributes, TransmissionMode};
+
+    run_test(ServerOpts::default(), |device| {
+        let attrib = Attributes::new(
+            12,
+            TransmissionMode::ServerOnChange,
+            Duration::ZERO,
+            Duration::ZERO,
+        );
+        device
+            .write(index::PLC_RW_M, 300, &[1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0])
+            .unwrap();
+        let subscription = device
+            .subscribe::<[u32; 3]>("GVL_Recipe.afValues", &attrib)
+            .unwrap();
+        assert_eq!(subscription.source(), device.addr());
+        device.write(index::PLC_RW_M, 304, &[5]).unwrap();
+        let ts = Timestamp::from_nanos(1_234_567_890_123_456_789 / 100 * 100);
+        assert_eq!(subscription.try_recv().unwrap(), (ts, [1, 2, 3]));
+        assert_eq!(
+            subscription.recv_timeout(Duration::from_secs(1)).unwrap(),
+            (ts, [1, 5, 3])
+        );
+        assert!(subscription.try_recv().is_err());
+        assert!(subscription.errors().try_recv().is_err());
+        drop(subscription);
+
+        let subscription = device
+            .subscribe_with("GVL_Recipe.afValues", &attrib, decode_from_bytes::<u64>)
+            .unwrap();
+        assert!(subscription.try_recv().is_err());
+        let error = subscription.errors().try_recv().unwrap();
+        assert_eq!(error.data, [1, 0, 0, 0, 5, 0, 0, 0, 3, 0, 0, 0]);
+        assert!(matches!(error.error, Error::InvalidData(_)));
+        drop(subscription);
+
+        assert!(device.subscribe::<u32>("MAIN.missing", &attrib).is_err());
+    });
+}
+
+#[test]
+fn test_client_notifications() {
+    use crate::client::{AddNotifRequest, DelNotifRequest};
+    use crate::notif::{Attributes, TransmissionMode};
+
+    run_test(ServerOpts::default(), |device| {
+        let chan = device.client.get_notification_channel();
+        let attrib =
+            |mode| Attributes::new(4, mode, Duration::from_millis(1), Duration::from_millis(5));
+        let timeout = Duration::from_secs(1);
+        device.write(0x4020, 0, &[4, 4, 1, 1]).unwrap();
+
+        // polled each cycle, delivered to the common channel as server notifications
+        let handle = device
+            .add_notification(0x4020, 0, &attrib(TransmissionMode::ClientCycle))
+            .unwrap();
+        for _ in 0..2 {
+            let notif = chan.recv_timeout(timeout).unwrap();
+            assert_eq!(notif.source(), device.addr());
+            let mut samples = notif.samples();
+            let sample = samples.next().unwrap();
+            assert_eq!(sample.handle, handle);
+            assert_eq!(sample.data, [4, 4, 1, 1]);
+            assert_eq!(samples.next(), None);
+        }
+        device.delete_notification(handle).unwrap();
+
+        // read once: the notifications are polled in order, so the samples of the deleted
+        // notification can only be received before
+        let once = device
+            .add_notification(0x4020, 0, &attrib(TransmissionMode::Client1Req))
+            .unwrap();
+        loop {
+            let notif = chan.recv_timeout(timeout).unwrap();
+            let sample_handle = notif.samples().next().unwrap().handle;
+            if sample_handle == once {
+                break;
+            }
+            assert_eq!(sample_handle, handle);
+        }
+
+        // on change: only the changed data is delivered
+        let subscription = device
+            .add_notification_channel(0x4020, 0, &attrib(TransmissionMode::ClientOnChange), 16)
+            .unwrap();
+        let rx = subscription.receiver().unwrap().clone();
+        assert_eq!(rx.recv_timeout(timeout).unwrap().data, [4, 4, 1, 1]);
+        // the subscription has been polled after the notification read once
+        assert!(chan.try_recv().is_err());
+        device.write(0x4020, 0, &[8, 8, 1, 1]).unwrap();
+        let sample = rx.recv_timeout(timeout).unwrap();
+        assert_eq!(sample.handle, subscription.handle());
+        assert_eq!(sample.data, [8, 8, 1, 1]);
+        device.delete_notification(once).unwrap();
+        drop(subscription);
+        device.write(0x4020, 0, &[1, 2, 3, 4]).unwrap();
+        // routed samples are delivered only while the notification exists and are never passed
+        // to the common channel
+        assert!(rx.try_recv().is_err());
+        assert!(chan.try_recv().is_err());
+
+        // sum-up requests are emulated as well
+        let mut requests =
+            [AddNotifRequest::new(0x4020, 0, &attrib(TransmissionMode::Client1Req)).unwrap()];
+        device.add_notification_multi(&mut requests).unwrap();
+        let handle = requests[0].handle().unwrap();
+        let notif = chan.recv_timeout(timeout).unwrap();
+        let sample = notif.samples().next().unwrap();
+        assert_eq!(sample.handle, handle);
+        assert_eq!(sample.data, [1, 2, 3, 4]);
+        let mut requests = [DelNotifRequest::new(handle)];
+        device.delete_notification_multi(&mut requests).unwrap();
+        requests[0].ensure().unwrap();
+    });
+}
@@ -0,0 +1,99 @@
+//! Tests for symbol and type comparison.
+
+use crate::diff::{diff, FieldChange};
+use crate::test::decoded_symbol_info;
+
+#[test]
+fn test_diff_unchanged() {
+    let (old_symbols, old_types) = decoded_symbol_info();
+    let (mut new_symbols, new_types) = decoded_symbol_info();
+    // names are case-insensitive, the order does not matter
+    new_symbols.reverse();
+    new_symbols[0].name = new_symbols[0].name.to_ascii_lowercase();
+    let changes = diff(&old_symbols, &old_types, &new_symbols, &new_types);
+    assert!(changes.is_empty());
+    assert!(!changes.affects("MAIN.drive"));
+    assert_eq!(changes.to_string(), "");
+}
+
+#[test]
+fn test_diff_changes() {
+    let (old_symbols, old_types) = decoded_symbol_info();
+    let (mut new_symbols, mut new_types) = decoded_symbol_info();
+
+    let counter = new_symbols
+        .iter_mut()
+        .find(|s| s.name == "MAIN.counter")
+        .unwrap();
+    counter.typ = String::from("ULINT");
+    counter.size = 8;
+    counter.ix_offset = 208;
+    counter.base_type = 21;
+    new_symbols.retain(|s| s.name != "GVL_Recipe.fTemp");
+    let mut added = new_symbols[0].clone();
+    added.name = String::from("MAIN.stDrive2");
+    new_symbols.push(added);
+    // comments are ignored
+    new_symbols[0].comment = String::from("changed");
+
+    let drive = new_types.get_mut("ST_Drive").unwrap();
+    drive.fields[1].typ = String::from("UDINT");
+    drive.fields[1].size = 4;
+    drive.fields[1].offset = Some(12);
+    let mut mode = drive.fields[1].clone();
+    mode.name = String::from("nMode");
+    mode.offset = Some(8);
+    drive.fields.insert(1, mode);
+    drive.fields.retain(|f| f.name != "bEnabled");
+    let mut state = new_types.remove("E_State").unwrap();
+    state.name = String::from("E_Mode");
+    new_types.insert(state.name.clone(), state);
+
+    let changes = diff(&old_symbols, &old_types, &new_symbols, &new_types);
+    assert!(!changes.is_empty());
+    assert_eq!(changes.added_symbols.len(), 1);
+    assert_eq!(changes.removed_symbols[0].name, "GVL_Recipe.fTemp");
+    assert_eq!(changes.changed_symbols.len(), 1);
+    let change = &changes.changed_symbols[0];
+    assert!(change.type_changed() && change.size_changed() && change.location_changed());
+    assert!(!change.flags_changed());
+    assert_eq!(changes.added_types[0].name, "E_Mode");
+    assert_eq!(changes.removed_types[0].name, "E_State");
+    assert_eq!(changes.changed_types.len(), 1);
+    let type_change = &changes.changed_types[0];
+    assert!(!type_change.size_changed());
+    assert_eq!(type_change.fields.len(), 3);
+    assert!(matches!(&type_change.fields[0], FieldChange::Added(f) if f.name == "nMode"));
+    assert!(matches!(
+        &type_change.fields[1],
+        FieldChange::Changed { old, new } if old.offset == Some(8) && new.offset == Some(12)
+    ));
+    assert!(matches!(&type_change.fields[2], FieldChange::Removed(f) if f.name == "bEnabled"));
+
+    // changed and removed symbols
+    assert!(changes.affects("main.counter"));
+    assert!(changes.affects("GVL_Recipe.fTemp"));
+    // unchanged symbols of changed or removed types, including members of arrays and structures
+    assert!(changes.affects("MAIN.drive"));
+    assert!(changes.affects("GVL_Recipe.stDrive"));
+    assert!(changes.affects("MAIN.eState"));
+    // unchanged symbols and added ones
+    assert!(!changes.affects("GVL_Recipe.afValues"));
+    assert!(!changes.affects("MAIN.pNode"));
+    assert!(!changes.affects("MAIN.stDrive2"));
+    assert!(!changes.affects("MAIN.unknown"));
+
+    assert_eq!(
+        changes.to_string(),
+        "+ MAIN.stDrive2 : ST_Drive\n\
+         - GVL_Recipe.fTemp : REAL\n\
+         ~ MAIN.counter: type UDINT -> ULINT, size 4 -> 8, location 16#4020:200 -> 16#4020:208, \
+         base type 19 -> 21\n\
+         + type E_Mode\n\
+         - type E_State\n\
+         ~ type ST_Drive\n\
+         \x20 + nMode : UDINT @ 8\n\
+         \x20 ~ nState : UINT @ 8 -> UDINT @ 12\n\
+         \x20 - bEnabled : BOOL\n"
+    );
+}
@@ -0,0 +1,134 @@
+//! Tests for PLC enums.
+
+use roboplc::Error;
+
+use crate::enums::{generate_rust, EnumType, PlcEnum};
+use crate::symbol::{Handle, TypeMap};
+use crate::test::decoded_symbol_info;
+use crate::test::ServerOpts;
+
+use super::test_client::run_test;
+
+// The output of `EnumType::to_rust` for `E_State`, compiled to verify the generated code.
+mod generated {
+    use roboplc::prelude::*;
+
+    /// PLC enum `E_State`
+    #[binrw]
+    #[brw(little, repr = i16)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[repr(i16)]
+    pub enum EState {
+        Idle = 0,
+        Running = 3,
+        Error = -1,
+    }
+
+    impl EState {
+        /// Qualified PLC name of the value.
+        pub fn plc_name(self) -> &'static str {
+            match self {
+                EState::Idle => "E_State.Idle",
+                EState::Running => "E_State.Running",
+                EState::Error => "E_State.Error",
+            }
+        }
+    }
+
+    impl std::fmt::Display for EState {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(self.plc_name())
+        }
+    }
+}
+
+fn types() -> TypeMap {
+    decoded_symbol_info().1
+}
+
+#[test]
+fn test_enum_type() {
+    let types = types();
+    assert_eq!(
+        types["E_State"].enums,
+        [
+            ("Idle".to_owned(), 0),
+            ("Running".to_owned(), 3),
+            ("Error".to_owned(), -1)
+        ]
+    );
+    assert!(EnumType::from_type(&types["ST_Drive"]).is_none());
+
+    let e = EnumType::from_type(&types["E_State"]).unwrap();
+    assert_eq!(e.size(), 2);
+    assert_eq!(e.name_of(3), Some("Running"));
+    assert_eq!(e.value_of("e_state.error"), Some(-1));
+    assert_eq!(e.value_of("IDLE"), Some(0));
+    assert_eq!(e.value_of("Stopped"), None);
+
+    let value = e.decode(&[0xff, 0xff]).unwrap();
+    assert_eq!(value.value, -1);
+    assert_eq!(value.to_string(), "E_State.Error");
+    assert_eq!(e.decode(&[7, 0]).unwrap().to_string(), "E_State(7)");
+    assert!(e.decode(&[7]).is_err());
+
+    assert_eq!(e.encode(-1).unwrap(), [0xff, 0xff]);
+    assert!(e.encode(0x8000).is_err());
+}
+
+#[test]
+fn test_enum_rust() {
+    use roboplc::io::binrw::BinRead;
+    let types = types();
+    let code = generate_rust(&types);
+    assert!(code.starts_with("/// PLC enum `E_State`\n#[binrw]\n#[brw(little, repr = i16)]\n"));
+    assert!(code.contains("pub enum EState {\n    Idle = 0,\n    Running = 3,\n    Error = -1,\n}"));
+    assert!(code.contains("EState::Running => \"E_State.Running\","));
+
+    let value = generated::EState::read_le(&mut std::io::Cursor::new([3, 0])).unwrap();
+    assert_eq!(value, generated::EState::Running);
+    assert_eq!(value.to_string(), "E_State.Running");
+}
+
+#[test]
+fn test_enum_rust_unsigned() {
+    use crate::symbol::BASE_TYPE_UINT64;
+    let mut typ = types()["E_State"].clone();
+    "E_Mask".clone_into(&mut typ.name);
+    typ.size = 8;
+    typ.base_type = BASE_TYPE_UINT64;
+    typ.enums = vec![
+        ("Self".to_owned(), 0),
+        ("_".to_owned(), 1),
+        ("All".to_owned(), -1),
+    ];
+    let code = EnumType::from_type(&typ).unwrap().to_rust();
+    assert!(code.contains("#[repr(u64)]"));
+    assert!(code.contains(
+        "pub enum EMask {\n    Self_ = 0,\n    __ = 1,\n    All = 18446744073709551615,\n}"
+    ));
+    assert!(code.contains("EMask::Self_ => \"E_Mask.Self\","));
+}
+
+#[test]
+fn test_enum_read_write() {
+    let e = EnumType::from_type(&types()["E_State"]).unwrap();
+    run_test(ServerOpts::default(), |device| {
+        let handle = Handle::new(&device, "SYMBOL").unwrap();
+        e.write_name(&handle, "Running").unwrap();
+        assert_eq!(
+            e.read(&handle).unwrap(),
+            PlcEnum {
+                type_name: "E_State".to_owned(),
+                name: Some("Running".to_owned()),
+                value: 3
+            }
+        );
+        e.write(&handle, -1).unwrap();
+        assert_eq!(e.read(&handle).unwrap().to_string(), "E_State.Error");
+        assert!(matches!(
+            e.write_name(&handle, "Stopped"),
+            Err(Error::InvalidData(_))
+        ));
+    });
+}
@@ -0,0 +1,335 @@
+//! Tests for symbol export and offline import.
+
+use crate::export::{symbol_to_json, symbols_to_csv, to_json, type_to_json, types_to_csv};
+use crate::import::{load, read_tmc, read_tpy};
+use crate::index;
+use crate::query::Query;
+use crate::symbol::{
+    BASE_TYPE_BIGTYPE, BASE_TYPE_BIT, BASE_TYPE_INT16, BASE_TYPE_REAL32, BASE_TYPE_REAL64,
+    BASE_TYPE_STRING, BASE_TYPE_UINT16, SYMBOL_FLAG_ATTRIBUTES, TYPE_FLAG_ENUM_INFOS,
+};
+use crate::test::decoded_symbol_info;
+
+#[test]
+fn test_export_json() {
+    let (mut symbols, types) = decoded_symbol_info();
+    let counter = symbols.iter().find(|s| s.name == "MAIN.counter").unwrap();
+    assert_eq!(
+        symbol_to_json(counter),
+        "{\"name\":\"MAIN.counter\",\"type\":\"UDINT\",\"ix_group\":16416,\"ix_offset\":200,\
+         \"size\":4,\"base_type\":19,\"flags\":4104,\"comment\":\"cycle counter\",\
+         \"attributes\":[{\"name\":\"OPC.UA.DA\",\"value\":\"1\"},\
+         {\"name\":\"unit\",\"value\":\"cycles\"}]}"
+    );
+    assert_eq!(
+        type_to_json(&types["ST_Drive"]),
+        "{\"name\":\"ST_Drive\",\"size\":16,\"base_type\":65,\"flags\":1,\"array\":[],\
+         \"fields\":[{\"name\":\"fSpeed\",\"type\":\"LREAL\",\"offset\":0,\"size\":8,\
+         \"base_type\":5,\"flags\":2,\"array\":[]},\
+         {\"name\":\"nState\",\"type\":\"UINT\",\"offset\":8,\"size\":2,\
+         \"base_type\":18,\"flags\":2,\"array\":[]},\
+         {\"name\":\"bEnabled\",\"type\":\"BOOL\",\"offset\":12,\"size\":1,\
+         \"base_type\":33,\"flags\":2,\"array\":[]}],\
+         \"attributes\":[],\"enums\":[],\"methods\":[]}"
+    );
+    let json = to_json(&symbols, &types);
+    assert!(json.starts_with("{\n  \"symbols\": [\n    {\"name\":\"MAIN.drive\""));
+    assert!(json.contains("\"comment\":\"Solltemperatur in °C\""));
+    assert!(json.contains("{\"name\":\"Running\",\"value\":3}"));
+    assert!(json.ends_with("}\n  ]\n}\n"));
+    // types are sorted by name
+    let e_state = json.find("{\"name\":\"E_State\"").unwrap();
+    let st_drive = json.find("{\"name\":\"ST_Drive\"").unwrap();
+    assert!(e_state < st_drive);
+
+    symbols[0].comment = String::from("\"quoted\"\n\ttab\u{1}");
+    assert!(symbol_to_json(&symbols[0]).contains(r#""comment":"\"quoted\"\n\ttab\u0001""#));
+    assert_eq!(
+        to_json(&[], &crate::symbol::TypeMap::new()),
+        "{\n  \"symbols\": [],\n  \"types\": []\n}\n"
+    );
+}
+
+#[test]
+fn test_export_csv() {
+    let (mut symbols, types) = decoded_symbol_info();
+    symbols[0].comment = String::from("speed, \"actual\"");
+    let csv = symbols_to_csv(&symbols[..2]);
+    let lines = csv.split("\r\n").collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            "name,type,ix_group,ix_offset,size,base_type,flags,comment,attributes",
+            "MAIN.drive,ST_Drive,16416,100,16,65,0,\"speed, \"\"actual\"\"\",",
+            "MAIN.counter,UDINT,16416,200,4,19,4104,cycle counter,OPC.UA.DA=1;unit=cycles",
+            ""
+        ]
+    );
+    let csv = types_to_csv(&types);
+    assert!(csv.starts_with("type,size,base_type,flags,field,field_type,"));
+    assert!(csv.contains("\r\nST_Drive,16,65,1,nState,UINT,8,2,18\r\n"));
+    assert!(csv.contains("\r\nE_State,2,2,8193,,,,,\r\n"));
+}
+
+const TMC: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<TcModuleClass xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <DataTypes>
+    <DataType>
+      <Name GUID="{8B9E8E5B-0C5A-4D5F-9A51-6C2C0D9C5A01}">ST_Drive</Name>
+      <BitSize>128</BitSize>
+      <SubItem>
+        <Name>fSpeed</Name>
+        <Type>LREAL</Type>
+        <Comment><![CDATA[ speed, in m/s ]]></Comment>
+        <BitSize>64</BitSize>
+        <BitOffs>0</BitOffs>
+      </SubItem>
+      <SubItem>
+        <Name>nState</Name>
+        <Type>E_State</Type>
+        <BitSize>16</BitSize>
+        <BitOffs>64</BitOffs>
+      </SubItem>
+      <SubItem>
+        <Name>anCount</Name>
+        <Type>T_Count</Type>
+        <ArrayInfo>
+          <LBound>1</LBound>
+          <Elements>2</Elements>
+        </ArrayInfo>
+        <BitSize>32</BitSize>
+        <BitOffs>80</BitOffs>
+      </SubItem>
+      <SubItem>
+        <Name>bEnabled</Name>
+        <Type>BOOL</Type>
+        <BitSize>8</BitSize>
+        <BitOffs>112</BitOffs>
+      </SubItem>
+    </DataType>
+    <DataType>
+      <Name>T_Count</Name>
+      <BitSize>16</BitSize>
+      <BaseType>T_Word</BaseType>
+    </DataType>
+    <DataType>
+      <Name>T_Word</Name>
+      <BitSize>16</BitSize>
+      <BaseType>UINT</BaseType>
+    </DataType>
+    <DataType>
+      <Name>E_State</Name>
+      <BitSize>16</BitSize>
+      <BaseType>INT</BaseType>
+      <EnumInfo>
+        <Text>Idle</Text>
+        <Enum>0</Enum>
+      </EnumInfo>
+      <EnumInfo>
+        <Text>Fault</Text>
+        <Enum>-1</Enum>
+      </EnumInfo>
+      <Properties>
+        <Property>
+          <Name>qualified_only</Name>
+        </Property>
+      </Properties>
+    </DataType>
+  </DataTypes>
+  <Modules>
+    <Module>
+      <DataAreas>
+        <DataArea>
+          <AreaNo AreaType="InternalData" CreateSymbols="true">3</AreaNo>
+          <Name>PlcTask Internal</Name>
+          <Symbol>
+            <Name>MAIN.stDrive</Name>
+            <BitSize>128</BitSize>
+            <BaseType>ST_Drive</BaseType>
+            <BitOffs>800</BitOffs>
+          </Symbol>
+          <Symbol>
+            <Name>GVL.afValues</Name>
+            <Comment><![CDATA[ Werte & Grenzen ]]></Comment>
+            <BitSize>96</BitSize>
+            <BaseType>REAL</BaseType>
+            <ArrayInfo>
+              <LBound>1</LBound>
+              <Elements>3</Elements>
+            </ArrayInfo>
+            <BitOffs>1024</BitOffs>
+            <Properties>
+              <Property>
+                <Name>unit</Name>
+                <Value>&#176;C</Value>
+              </Property>
+            </Properties>
+          </Symbol>
+        </DataArea>
+        <DataArea>
+          <AreaNo AreaType="InputDst">0</AreaNo>
+          <Symbol>
+            <Name>MAIN.bSensor</Name>
+            <BitSize>8</BitSize>
+            <BaseType>BOOL</BaseType>
+            <BitOffs>16</BitOffs>
+          </Symbol>
+        </DataArea>
+      </DataAreas>
+    </Module>
+  </Modules>
+</TcModuleClass>
+"#;
+
+#[test]
+fn test_import_tmc() {
+    let (symbols, types) = read_tmc(TMC.as_bytes()).unwrap();
+    assert_eq!(symbols.len(), 3);
+    let drive = &symbols[0];
+    assert_eq!(drive.name, "MAIN.stDrive");
+    assert_eq!(drive.typ, "ST_Drive");
+    assert_eq!(
+        (drive.ix_group, drive.ix_offset, drive.size),
+        (index::PLC_RW_DB, 100, 16)
+    );
+    assert_eq!(drive.base_type, BASE_TYPE_BIGTYPE);
+    let values = &symbols[1];
+    assert_eq!(values.typ, "ARRAY [1..3] OF REAL");
+    assert_eq!(values.base_type, BASE_TYPE_REAL32);
+    assert_eq!(values.comment, "Werte & Grenzen");
+    assert_eq!(values.attributes, [("unit".to_owned(), "°C".to_owned())]);
+    assert_eq!(values.flags, SYMBOL_FLAG_ATTRIBUTES);
+    let sensor = &symbols[2];
+    assert_eq!((sensor.ix_group, sensor.ix_offset), (index::IO_RW_I, 2));
+    assert_eq!(sensor.base_type, BASE_TYPE_BIT);
+
+    assert_eq!(types.len(), 4);
+    let st_drive = &types["ST_Drive"];
+    assert_eq!(st_drive.size, 16);
+    assert_eq!(st_drive.fields.len(), 4);
+    assert_eq!(st_drive.fields[0].base_type, BASE_TYPE_REAL64);
+    assert_eq!(st_drive.fields[1].base_type, BASE_TYPE_INT16);
+    let counts = &st_drive.fields[2];
+    assert_eq!(counts.typ, "ARRAY [1..2] OF T_Count");
+    assert_eq!(counts.array, [(1, 2)]);
+    assert_eq!((counts.offset, counts.size), (Some(10), 4));
+    // resolved through the alias chain
+    assert_eq!(counts.base_type, BASE_TYPE_UINT16);
+    assert_eq!(types["T_Count"].base_type, BASE_TYPE_UINT16);
+    let e_state = &types["E_State"];
+    assert_eq!(
+        e_state.enums,
+        [("Idle".to_owned(), 0), ("Fault".to_owned(), -1)]
+    );
+    assert_ne!(e_state.flags & TYPE_FLAG_ENUM_INFOS, 0);
+    assert_eq!(
+        e_state.attributes,
+        [("qualified_only".to_owned(), String::new())]
+    );
+
+    let result = Query::new()
+        .name("MAIN.*")
+        .unwrap()
+        .expand(true)
+        .run(&symbols, &types);
+    let names = result.iter().map(|s| s.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            "MAIN.stDrive.fSpeed",
+            "MAIN.stDrive.nState",
+            "MAIN.stDrive.anCount[1]",
+            "MAIN.stDrive.anCount[2]",
+            "MAIN.stDrive.bEnabled",
+            "MAIN.bSensor"
+        ]
+    );
+    assert_eq!(result[3].ix_offset, 112);
+
+    assert!(read_tmc(&b"<TcModuleClass><DataTypes>"[..]).is_err());
+    assert!(read_tmc(
+        &b"<TcModuleClass><DataTypes><DataType><Name>T</Name><BitSize>x</BitSize>\
+           </DataType></DataTypes></TcModuleClass>"[..]
+    )
+    .is_err());
+    // malformed array bounds
+    for tmc in [
+        TMC.replace("<Elements>2</Elements>", "<Elements>0</Elements>"),
+        TMC.replace("<Elements>3</Elements>", "<Elements>-5</Elements>"),
+        TMC.replace("<LBound>1</LBound>", "<LBound>2147483647</LBound>"),
+    ] {
+        assert!(matches!(
+            read_tmc(tmc.as_bytes()),
+            Err(roboplc::Error::InvalidData(_))
+        ));
+    }
+}
+
+const TPY: &str = r#"<?xml version="1.0" encoding="ISO-8859-1"?>
+<PlcProjectInfo>
+  <DataTypes>
+    <DataType>
+      <Name Decoration="16#3B4F2A10">ST_Recipe</Name>
+      <BitSize>208</BitSize>
+      <SubItem>
+        <Name>sName</Name>
+        <Type>STRING(20)</Type>
+        <BitSize>168</BitSize>
+        <BitOffs>0</BitOffs>
+      </SubItem>
+      <SubItem>
+        <Name>fTemp</Name>
+        <Type>REAL</Type>
+        <BitSize>32</BitSize>
+        <BitOffs>176</BitOffs>
+      </SubItem>
+    </DataType>
+  </DataTypes>
+  <Symbols>
+    <Symbol>
+      <Name>.stRecipe</Name>
+      <Type Decoration="16#3B4F2A10">ST_Recipe</Type>
+      <IGroup>16448</IGroup>
+      <IOffset>40</IOffset>
+      <BitSize>208</BitSize>
+      <Comment> Rezept f&#252;r Ofen 1 </Comment>
+    </Symbol>
+    <Symbol>
+      <Name>MAIN.sMessage</Name>
+      <Type>STRING(80)</Type>
+      <IGroup>16416</IGroup>
+      <IOffset>0</IOffset>
+      <BitSize>648</BitSize>
+    </Symbol>
+  </Symbols>
+</PlcProjectInfo>
+"#;
+
+#[test]
+fn test_import_tpy() {
+    let (symbols, types) = read_tpy(TPY.as_bytes()).unwrap();
+    assert_eq!(symbols.len(), 2);
+    assert_eq!(symbols[0].name, ".stRecipe");
+    assert_eq!(symbols[0].typ, "ST_Recipe");
+    assert_eq!(
+        (symbols[0].ix_group, symbols[0].ix_offset, symbols[0].size),
+        (index::PLC_RW_DB, 40, 26)
+    );
+    assert_eq!(symbols[0].comment, "Rezept für Ofen 1");
+    assert_eq!(symbols[1].base_type, BASE_TYPE_STRING);
+    assert_eq!(symbols[1].size, 81);
+    let recipe = &types["ST_Recipe"];
+    assert_eq!(recipe.fields[0].base_type, BASE_TYPE_STRING);
+    assert_eq!(recipe.fields[1].offset, Some(22));
+
+    // exported files of the same project are identical
+    let (symbols2, types2) = read_tpy(TPY.as_bytes()).unwrap();
+    assert_eq!(to_json(&symbols, &types), to_json(&symbols2, &types2));
+
+    let path = std::env::temp_dir().join(format!("roboplc-io-ads-{}.tpy", std::process::id()));
+    std::fs::write(&path, TPY).unwrap();
+    let (symbols3, _) = load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(symbols3.len(), 2);
+    assert!(load("project.xml").is_err());
+}
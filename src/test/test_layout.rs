@@ -0,0 +1,223 @@
+//! Test for the layout validation.
+
+use crate::layout::{self, LayoutDiff, LayoutField, PlcLayout};
+use crate::symbol::{Field, Symbol, Type, TypeMap};
+
+#[derive(PlcLayout)]
+#[allow(dead_code)]
+struct Drive {
+    #[plc(name = "fSpeed")]
+    speed: f64,
+    #[plc(name = "nState")]
+    state: u16,
+    #[plc(skip)]
+    pad: [u8; 2],
+    #[plc(name = "aCurrent")]
+    current: [i32; 3],
+}
+
+#[derive(PlcLayout)]
+#[plc(size = 24)]
+#[allow(dead_code)]
+struct Padded {
+    #[plc(name = "fSpeed")]
+    speed: f64,
+    #[plc(name = "bEnabled", offset = 16)]
+    enabled: bool,
+}
+
+#[derive(PlcLayout)]
+#[allow(dead_code)]
+struct Machine {
+    #[plc(name = "nId")]
+    id: u32,
+    #[plc(skip)]
+    pad: u32,
+    #[plc(name = "stDrive")]
+    drive: Drive,
+}
+
+#[derive(PlcLayout)]
+#[allow(dead_code)]
+struct RenamedDrive {
+    #[plc(name = "fVelocity")]
+    speed: f64,
+    #[plc(name = "nState")]
+    state: u16,
+    #[plc(skip)]
+    pad: [u8; 2],
+    #[plc(name = "aCurrent")]
+    current: [i32; 3],
+}
+
+#[derive(PlcLayout)]
+#[allow(dead_code)]
+struct RenamedMachine {
+    #[plc(name = "nId")]
+    id: u32,
+    #[plc(skip)]
+    pad: u32,
+    #[plc(name = "stDrive")]
+    drive: RenamedDrive,
+}
+
+fn field(name: &str, offset: u32, size: usize) -> Field {
+    Field {
+        name: name.to_owned(),
+        typ: String::new(),
+        offset: Some(offset),
+        size,
+        array: vec![],
+        base_type: 0,
+        flags: 0,
+    }
+}
+
+fn drive_type(fields: Vec<Field>) -> Type {
+    Type {
+        name: "ST_Drive".to_owned(),
+        size: 24,
+        array: vec![],
+        fields,
+        base_type: 65,
+        flags: 0,
+        attributes: vec![],
+        enums: vec![],
+        methods: vec![],
+    }
+}
+
+#[test]
+fn test_derive() {
+    assert_eq!(Drive::plc_size(), 24);
+    assert_eq!(
+        Drive::plc_fields(),
+        [
+            LayoutField {
+                name: "fSpeed",
+                offset: 0,
+                size: 8,
+                fields: vec![]
+            },
+            LayoutField {
+                name: "nState",
+                offset: 8,
+                size: 2,
+                fields: vec![]
+            },
+            LayoutField {
+                name: "aCurrent",
+                offset: 12,
+                size: 12,
+                fields: vec![]
+            },
+        ]
+    );
+    assert_eq!(Padded::plc_size(), 24);
+    assert_eq!(Padded::plc_fields()[1].offset, 16);
+    assert_eq!(<[crate::strings::String<10>; 2]>::plc_size(), 22);
+}
+
+#[test]
+fn test_layout_diff() {
+    let typ = drive_type(vec![
+        field("FSPEED", 0, 8),
+        field("nState", 8, 2),
+        field("aCurrent", 12, 12),
+    ]);
+    assert!(layout::diff::<Drive>(&typ).is_empty());
+    layout::verify::<Drive>(&typ).unwrap();
+
+    let typ = drive_type(vec![
+        field("fSpeed", 0, 8),
+        field("nState", 8, 4),
+        field("aCurrent", 16, 8),
+        field("bReady", 20, 1),
+    ]);
+    assert_eq!(
+        layout::diff::<Drive>(&typ),
+        [
+            LayoutDiff::FieldSize {
+                field: "nState".to_owned(),
+                rust: 2,
+                plc: 4
+            },
+            LayoutDiff::Offset {
+                field: "aCurrent".to_owned(),
+                rust: 12,
+                plc: Some(16)
+            },
+            LayoutDiff::FieldSize {
+                field: "aCurrent".to_owned(),
+                rust: 12,
+                plc: 8
+            },
+            LayoutDiff::MissingInRust {
+                field: "bReady".to_owned()
+            },
+        ]
+    );
+    assert!(layout::verify::<Drive>(&typ).is_err());
+}
+
+#[test]
+fn test_verify_symbol() {
+    let symbols = vec![Symbol {
+        name: "MAIN.stMachine".to_owned(),
+        ix_group: 0x4040,
+        ix_offset: 0,
+        typ: "ST_Machine".to_owned(),
+        size: 32,
+        base_type: 65,
+        flags: 0,
+        comment: String::new(),
+        attributes: Vec::new(),
+    }];
+    let mut types = TypeMap::new();
+    let drive_field = Field {
+        typ: "ST_Drive".to_owned(),
+        ..field("stDrive", 8, 24)
+    };
+    types.insert(
+        "ST_Machine".to_owned(),
+        Type {
+            name: "ST_Machine".to_owned(),
+            size: 32,
+            array: vec![],
+            fields: vec![field("nId", 0, 4), drive_field],
+            base_type: 65,
+            flags: 0,
+            attributes: vec![],
+            enums: vec![],
+            methods: vec![],
+        },
+    );
+    types.insert(
+        "ST_Drive".to_owned(),
+        drive_type(vec![
+            field("fSpeed", 0, 8),
+            field("nState", 8, 2),
+            field("aCurrent", 12, 12),
+        ]),
+    );
+    layout::verify_symbol::<Drive>(&symbols, &types, "MAIN.stMachine.stDrive").unwrap();
+    layout::verify_symbol::<u32>(&symbols, &types, "main.stmachine.nid").unwrap();
+    assert!(layout::verify_symbol::<Drive>(&symbols, &types, "MAIN.stMachine").is_err());
+    // nested structures are compared recursively
+    assert_eq!(Machine::plc_fields()[1].fields, Drive::plc_fields());
+    layout::verify_symbol::<Machine>(&symbols, &types, "MAIN.stMachine").unwrap();
+    assert_eq!(
+        layout::diff_with_types::<RenamedMachine>(&types["ST_Machine"], &types),
+        [
+            LayoutDiff::MissingInPlc {
+                field: "stDrive.fVelocity".to_owned()
+            },
+            LayoutDiff::MissingInRust {
+                field: "stDrive.fSpeed".to_owned()
+            },
+        ]
+    );
+    assert!(layout::diff::<RenamedMachine>(&types["ST_Machine"]).is_empty());
+    assert!(layout::verify_symbol::<RenamedMachine>(&symbols, &types, "MAIN.stMachine").is_err());
+    assert!(layout::verify_symbol::<Drive>(&symbols, &types, "MAIN.blub").is_err());
+}
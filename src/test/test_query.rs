@@ -0,0 +1,119 @@
+//! Tests for symbol queries.
+
+use crate::query::Query;
+use crate::symbol::{Symbol, BASE_TYPE_REAL32, SYMBOL_FLAG_PERSISTENT};
+use crate::test::decoded_symbol_info;
+
+fn names(symbols: &[Symbol]) -> Vec<&str> {
+    symbols.iter().map(|s| s.name.as_str()).collect()
+}
+
+#[test]
+fn test_query_top_level() {
+    let (symbols, types) = decoded_symbol_info();
+    let result = Query::new().name("main.*").unwrap().run(&symbols, &types);
+    assert_eq!(
+        names(&result),
+        [
+            "MAIN.drive",
+            "MAIN.counter",
+            "MAIN.eState",
+            "MAIN.stFlags.bReady",
+            "MAIN.pNode",
+            "MAIN.abStart",
+            "MAIN.fbMotor"
+        ]
+    );
+
+    let result = Query::new()
+        .name_regex(r"^GVL_\w+\.f")
+        .unwrap()
+        .run(&symbols, &types);
+    assert_eq!(names(&result), ["GVL_Recipe.fTemp"]);
+
+    let result = Query::new()
+        .with_flags(SYMBOL_FLAG_PERSISTENT)
+        .size(..16)
+        .run(&symbols, &types);
+    assert_eq!(names(&result), ["GVL_Recipe.afValues"]);
+
+    let result = Query::new()
+        .without_flags(SYMBOL_FLAG_PERSISTENT)
+        .type_name("st_*")
+        .unwrap()
+        .run(&symbols, &types);
+    assert_eq!(names(&result), ["MAIN.drive"]);
+
+    let result = Query::new().attribute("opc.ua.da").run(&symbols, &types);
+    assert_eq!(names(&result), ["MAIN.counter"]);
+    let result = Query::new()
+        .attribute_value("unit", "cycles")
+        .run(&symbols, &types);
+    assert_eq!(names(&result), ["MAIN.counter"]);
+    assert!(Query::new()
+        .attribute_value("unit", "ms")
+        .run(&symbols, &types)
+        .is_empty());
+}
+
+#[test]
+fn test_query_expand() {
+    let (symbols, types) = decoded_symbol_info();
+    let result = Query::new()
+        .name("GVL_Recipe.*")
+        .unwrap()
+        .type_name("REAL")
+        .unwrap()
+        .with_flags(SYMBOL_FLAG_PERSISTENT)
+        .expand(true)
+        .run(&symbols, &types);
+    assert_eq!(
+        names(&result),
+        [
+            "GVL_Recipe.afValues[1]",
+            "GVL_Recipe.afValues[2]",
+            "GVL_Recipe.afValues[3]"
+        ]
+    );
+    assert_eq!(result[2].ix_offset, 308);
+    assert_eq!(result[2].size, 4);
+    assert_eq!(result[2].base_type, BASE_TYPE_REAL32);
+
+    let result = Query::new()
+        .name("*.stDrive.*")
+        .unwrap()
+        .expand(true)
+        .run(&symbols, &types);
+    assert_eq!(
+        names(&result),
+        [
+            "GVL_Recipe.stDrive.fSpeed",
+            "GVL_Recipe.stDrive.nState",
+            "GVL_Recipe.stDrive.bEnabled"
+        ]
+    );
+    assert_eq!(result[1].ix_offset, 328);
+    assert_eq!(result[1].typ, "UINT");
+    assert_eq!(result[2].flags, SYMBOL_FLAG_PERSISTENT);
+
+    let result = Query::new()
+        .name("GVL_Recipe.afValues[?]")
+        .unwrap()
+        .expand(true)
+        .run(&symbols, &types);
+    assert_eq!(result.len(), 3);
+
+    // malformed array bounds are not expanded
+    let mut symbols = symbols;
+    for symbol in &mut symbols {
+        if symbol.name == "GVL_Recipe.afValues" {
+            "ARRAY [-2147483648..2147483647] OF REAL".clone_into(&mut symbol.typ);
+        }
+    }
+    let result = Query::new()
+        .name("GVL_Recipe.afValues*")
+        .unwrap()
+        .expand(true)
+        .run(&symbols, &types);
+    assert!(result.is_empty());
+}
@@ -0,0 +1,206 @@
+//! Tests for time and date types and dynamic values.
+
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use bma_ts::Timestamp;
+use roboplc::io::binrw::{BinRead, BinWrite};
+use zerocopy::{AsBytes, FromBytes};
+
+use crate::symbol::{decode_symbol_info, TypeMap};
+use crate::test::{decoded_symbol_info, symbol_upload};
+use crate::types::{Calendar, Date, DateAndTime, LDate, LDateAndTime, LTime, Time, TimeOfDay};
+use crate::value::{decode, Value};
+
+#[test]
+fn test_time_literals() {
+    let t: Time = "T#1h2m3s4ms".parse().unwrap();
+    assert_eq!(t.raw(), 3_723_004);
+    assert_eq!(t.to_string(), "T#1h2m3s4ms");
+    assert_eq!("time#1.5s".parse::<Time>().unwrap().raw(), 1500);
+    assert_eq!("T#1d_12h".parse::<Time>().unwrap().to_string(), "T#1d12h");
+    assert_eq!(Time::new(0).to_string(), "T#0ms");
+    assert!("T#1x".parse::<Time>().is_err());
+    assert!("T#".parse::<Time>().is_err());
+    assert!("T#50d".parse::<Time>().is_err());
+
+    let t: LTime = "LTIME#1m5us6ns".parse().unwrap();
+    assert_eq!(t.raw(), 60_000_005_006);
+    assert_eq!(t.to_string(), "LTIME#1m5us6ns");
+    assert_eq!(LTime::new(0).to_string(), "LTIME#0ns");
+
+    let t: TimeOfDay = "TOD#12:30:15.5".parse().unwrap();
+    assert_eq!(
+        (t.hour(), t.minute(), t.second(), t.millisecond()),
+        (12, 30, 15, 500)
+    );
+    assert_eq!(t, TimeOfDay::from_hms_milli(12, 30, 15, 500).unwrap());
+    assert_eq!(t.to_string(), "TOD#12:30:15.5");
+    assert!("TOD#24:00:00".parse::<TimeOfDay>().is_err());
+
+    let d: Date = "D#2024-02-29".parse().unwrap();
+    assert_eq!(d, Date::from_ymd(2024, 2, 29).unwrap());
+    assert_eq!(d.raw(), 1_709_164_800);
+    assert_eq!(d.to_string(), "D#2024-02-29");
+    assert!("D#2023-02-29".parse::<Date>().is_err());
+    assert!("D#1969-12-31".parse::<Date>().is_err());
+    assert_eq!(
+        "LDATE#2024-02-29".parse::<LDate>().unwrap().raw(),
+        1_709_164_800_000_000_000
+    );
+
+    let dt: DateAndTime = "DT#2024-01-01-12:00:00".parse().unwrap();
+    assert_eq!(dt.raw(), 1_704_110_400);
+    assert_eq!(dt.to_string(), "DT#2024-01-01-12:00:00");
+    let ldt: LDateAndTime = "LDT#2024-01-01-12:00:00.123456789".parse().unwrap();
+    assert_eq!(ldt.raw(), 1_704_110_400_123_456_789);
+    assert_eq!(ldt.to_string(), "LDT#2024-01-01-12:00:00.123456789");
+    assert!("DT#2024-01-01 12:00:00".parse::<DateAndTime>().is_err());
+}
+
+#[test]
+fn test_time_conversions() {
+    let t = Time::try_from(Duration::from_micros(1_500_700)).unwrap();
+    assert_eq!(t.raw(), 1500);
+    assert_eq!(Duration::from(t), Duration::from_millis(1500));
+    assert!(Time::try_from(Duration::from_secs(u64::from(u32::MAX))).is_err());
+    assert_eq!(
+        Duration::from(LTime::new(1_000_000_001)),
+        Duration::new(1, 1)
+    );
+    assert!(TimeOfDay::try_from(Duration::from_secs(86_400)).is_err());
+
+    let ts = Timestamp::from_secs(1_704_110_400);
+    let dt = DateAndTime::try_from(ts).unwrap();
+    assert_eq!(Timestamp::from(dt), ts);
+    // dates are truncated to midnight
+    let d = Date::try_from(ts).unwrap();
+    assert_eq!(d.to_string(), "D#2024-01-01");
+    assert_eq!(
+        Calendar::from(LDateAndTime::new(1_704_110_400_000_000_007)),
+        Calendar::date(2024, 1, 1).with_time(12, 0, 0, 7)
+    );
+    assert_eq!(
+        DateAndTime::try_from(Calendar::date(2106, 2, 7).with_time(6, 28, 15, 0))
+            .unwrap()
+            .raw(),
+        u32::MAX
+    );
+    assert!(DateAndTime::try_from(Calendar::date(2106, 2, 7).with_time(6, 28, 16, 0)).is_err());
+    assert!(Date::try_from(Calendar::date(2024, 13, 1)).is_err());
+}
+
+#[test]
+fn test_time_binary() {
+    let t = Time::new(0x0102_0304);
+    assert_eq!(t.as_bytes(), [4, 3, 2, 1]);
+    assert_eq!(Time::read_from(&[4, 3, 2, 1][..]).unwrap(), t);
+    let mut c = std::io::Cursor::new(Vec::new());
+    LDateAndTime::new(5).write_le(&mut c).unwrap();
+    assert_eq!(c.get_ref(), &[5, 0, 0, 0, 0, 0, 0, 0]);
+    c.set_position(0);
+    assert_eq!(LDateAndTime::read_le(&mut c).unwrap().raw(), 5);
+    assert!(Time::new(1) < Time::new(2));
+}
+
+fn types() -> TypeMap {
+    decoded_symbol_info().1
+}
+
+#[test]
+fn test_malformed_upload() {
+    let (symbol_data, type_data) = symbol_upload();
+    let decode = |symbol_data: Vec<u8>, type_data: Vec<u8>| {
+        matches!(
+            decode_symbol_info(symbol_data, type_data),
+            Err(roboplc::Error::InvalidData(_))
+        )
+    };
+    // truncated uploads
+    assert!(decode(
+        symbol_data[..symbol_data.len() - 1].to_vec(),
+        type_data.clone()
+    ));
+    assert!(decode(
+        symbol_data.clone(),
+        type_data[..type_data.len() - 1].to_vec()
+    ));
+    // entry sizes which do not cover the size field itself
+    let mut data = type_data.clone();
+    data[..4].copy_from_slice(&2u32.to_le_bytes());
+    assert!(decode(symbol_data.clone(), data));
+    let mut data = symbol_data.clone();
+    data[..4].copy_from_slice(&0u32.to_le_bytes());
+    assert!(decode(data, type_data.clone()));
+    // a top-level type entry with an offset
+    let mut data = type_data;
+    data[20..24].copy_from_slice(&4u32.to_le_bytes());
+    assert!(decode(symbol_data, data));
+}
+
+#[test]
+fn test_value_decode() {
+    let types = types();
+    assert_eq!(
+        decode("TIME", 19, &1500u32.to_le_bytes(), &types).unwrap(),
+        Value::Time(Time::new(1500))
+    );
+    assert_eq!(
+        decode("DT", 19, &1_704_110_400u32.to_le_bytes(), &types)
+            .unwrap()
+            .to_string(),
+        "DT#2024-01-01-12:00:00"
+    );
+    assert_eq!(
+        decode("INT", 2, &[0xfe, 0xff], &types).unwrap(),
+        Value::Int(-2)
+    );
+    assert_eq!(
+        decode("STRING(10)", 30, b"hello\0\0\0\0\0\0", &types).unwrap(),
+        Value::String("hello".to_owned())
+    );
+    assert!(decode("DINT", 3, &[0; 2], &types).is_err());
+    // unknown types are decoded by the base type
+    assert_eq!(
+        decode("T_Count", 18, &[7, 0], &types).unwrap(),
+        Value::UInt(7)
+    );
+    assert_eq!(
+        decode("I_Unknown", 65, &[1, 2], &types)
+            .unwrap()
+            .to_string(),
+        "16#0102"
+    );
+
+    let mut data = vec![0; 16];
+    data[..8].copy_from_slice(&12.5f64.to_le_bytes());
+    data[8..10].copy_from_slice(&3u16.to_le_bytes());
+    data[12] = 1;
+    let value = decode("ST_Drive", 65, &data, &types).unwrap();
+    assert_eq!(
+        value.to_string(),
+        "(fSpeed := 12.5, nState := 3, bEnabled := TRUE)"
+    );
+    let data = [1.0f32, 2.0, 3.5]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        decode("ARRAY [1..3] OF REAL", 4, &data, &types)
+            .unwrap()
+            .to_string(),
+        "[1, 2, 3.5]"
+    );
+    assert_eq!(
+        decode("E_State", 2, &[3, 0], &types).unwrap().to_string(),
+        "E_State.Running"
+    );
+    // malformed array bounds
+    for typ in [
+        "ARRAY [3..1] OF REAL",
+        "ARRAY [-2147483648..2147483647] OF REAL",
+        "ARRAY [-2147483648..2147483647, -2147483648..2147483647, 0..1] OF REAL",
+    ] {
+        assert!(decode(typ, 4, &data, &types).is_err(), "{}", typ);
+    }
+}
@@ -0,0 +1,79 @@
+//! Tests for structures bound to PLC symbols.
+
+use roboplc::io::IoMapping;
+
+use super::test_client::run_test;
+use crate::strings::PlcString;
+use crate::test::ServerOpts;
+use crate::vars::AdsVars;
+
+#[derive(AdsVars)]
+struct Recipe {
+    #[ads(symbol = "SYMBOL")]
+    counter: u32,
+    #[ads(symbol = "GVL_Recipe.afValues")]
+    values: [u32; 3],
+    #[ads(symbol = "GVL_Recipe.sName", size = 21)]
+    name: PlcString,
+    #[ads(skip)]
+    local: u8,
+}
+
+#[derive(AdsVars, Default)]
+struct Broken {
+    #[ads(symbol = "SYMBOL")]
+    counter: u32,
+    #[ads(symbol = "MAIN.missing")]
+    missing: u32,
+}
+
+#[test]
+fn test_vars() {
+    run_test(ServerOpts::default(), |device| {
+        assert_eq!(
+            Recipe::ads_fields(),
+            [
+                ("SYMBOL", 4),
+                ("GVL_Recipe.afValues", 12),
+                ("GVL_Recipe.sName", 21)
+            ]
+        );
+        let mut name = PlcString::with_capacity(20);
+        name.set_str_1252("recipe").unwrap();
+        let recipe = Recipe {
+            counter: 42,
+            values: [1, 2, 3],
+            name,
+            local: 1,
+        };
+        let mut image = Recipe::bind(&device).unwrap();
+        recipe.write_all(&mut image).unwrap();
+        let mut mapping = device.mapping("GVL_Recipe.afValues", 12);
+        assert_eq!(mapping.read::<[u32; 3]>().unwrap(), [1, 2, 3]);
+        mapping.write([4u32, 5, 6]).unwrap();
+
+        let mut read = Recipe {
+            counter: 0,
+            values: [0; 3],
+            name: PlcString::with_capacity(20),
+            local: 0,
+        };
+        read.read_all(&mut image).unwrap();
+        assert_eq!(read.counter, 42);
+        assert_eq!(read.values, [4, 5, 6]);
+        assert_eq!(read.name.to_string(), "recipe");
+        assert_eq!(read.local, 0);
+        // handles are kept by the image
+        read.read_all(&mut image).unwrap();
+        assert_eq!(read.values, [4, 5, 6]);
+
+        // valid fields are still read
+        let mut broken = Broken::default();
+        let mut image = Broken::bind(&device).unwrap();
+        let err = broken.read_all(&mut image).unwrap_err().to_string();
+        assert!(err.contains("MAIN.missing"), "{}", err);
+        assert_eq!(broken.counter, 42);
+        assert_eq!(broken.missing, 0);
+        assert!(broken.write_all(&mut image).is_err());
+    });
+}
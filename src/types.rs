@@ -0,0 +1,686 @@
+//! IEC 61131-3 time and date types.
+//!
+//! The types are transparent wrappers around the raw PLC values and can be read and written
+//! either with zerocopy (e.g. [`crate::symbol::Handle::read_value`]) or with binrw (e.g. inside
+//! structures used with [`crate::AdsMapping`]):
+//!
+//! | PLC type              | Rust type          | Raw value                             |
+//! |-----------------------|--------------------|---------------------------------------|
+//! | `TIME`                | [`Time`]           | `u32`, milliseconds                   |
+//! | `LTIME`               | [`LTime`]          | `u64`, nanoseconds                    |
+//! | `TIME_OF_DAY` (`TOD`) | [`TimeOfDay`]      | `u32`, milliseconds since midnight    |
+//! | `DATE`                | [`Date`]           | `u32`, seconds since 1970-01-01       |
+//! | `DATE_AND_TIME` (`DT`)| [`DateAndTime`]    | `u32`, seconds since 1970-01-01       |
+//! | `LDATE`               | [`LDate`]          | `u64`, nanoseconds since 1970-01-01   |
+//! | `LDATE_AND_TIME` (`LDT`) | [`LDateAndTime`] | `u64`, nanoseconds since 1970-01-01 |
+//!
+//! Durations are converted from/to [`std::time::Duration`], dates from/to
+//! [`bma_ts::Timestamp`] and [`Calendar`]. All types are parsed from and printed as IEC
+//! literals (`T#1h2m`, `TOD#12:00:00.5`, `D#2024-01-01`, `DT#2024-01-01-12:00:00`). Conversions
+//! to a type with a lower resolution truncate the value.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{Read, Seek, Write};
+use std::str::FromStr;
+use std::time::Duration;
+
+use bma_ts::Timestamp;
+use roboplc::io::binrw::{BinRead, BinResult, BinWrite, Endian};
+use roboplc::{Error, Result};
+use zerocopy::byteorder::{U32, U64};
+use zerocopy::{AsBytes, FromBytes, LE};
+
+const NANOS_PER_MILLI: u128 = 1_000_000;
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+const NANOS_PER_DAY: u128 = 86_400 * NANOS_PER_SEC;
+
+macro_rules! time_type {
+    ($name: ident, $plc: literal, $raw: ty, $zc: ident, $unit: expr, $resolution: expr) => {
+        #[doc = concat!("PLC `", $plc, "` value.")]
+        #[derive(AsBytes, FromBytes, Clone, Copy, Default, PartialEq, Eq, Hash)]
+        #[repr(transparent)]
+        pub struct $name($zc<LE>);
+
+        impl $name {
+            /// Create a value from the raw PLC value.
+            pub fn new(raw: $raw) -> Self {
+                Self($zc::new(raw))
+            }
+
+            /// Get the raw PLC value.
+            pub fn raw(self) -> $raw {
+                self.0.get()
+            }
+
+            fn to_nanos(self) -> u128 {
+                u128::from(self.raw()) * $unit
+            }
+
+            fn from_nanos(nanos: u128) -> Result<Self> {
+                let raw = <$raw>::try_from(nanos / $resolution * $resolution / $unit)
+                    .map_err(|_| Error::invalid_data(concat!($plc, " value out of range")))?;
+                Ok(Self::new(raw))
+            }
+        }
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.raw().cmp(&other.raw())
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(self, f)
+            }
+        }
+
+        impl BinRead for $name {
+            type Args<'a> = ();
+
+            fn read_options<R: Read + Seek>(
+                reader: &mut R,
+                endian: Endian,
+                args: Self::Args<'_>,
+            ) -> BinResult<Self> {
+                <$raw>::read_options(reader, endian, args).map(Self::new)
+            }
+        }
+
+        impl BinWrite for $name {
+            type Args<'a> = ();
+
+            fn write_options<W: Write + Seek>(
+                &self,
+                writer: &mut W,
+                endian: Endian,
+                args: Self::Args<'_>,
+            ) -> BinResult<()> {
+                self.raw().write_options(writer, endian, args)
+            }
+        }
+    };
+}
+
+macro_rules! duration_conversions {
+    ($name: ident) => {
+        impl From<$name> for Duration {
+            fn from(value: $name) -> Self {
+                nanos_to_duration(value.to_nanos())
+            }
+        }
+
+        impl TryFrom<Duration> for $name {
+            type Error = Error;
+
+            fn try_from(value: Duration) -> Result<Self> {
+                Self::from_nanos(value.as_nanos())
+            }
+        }
+    };
+}
+
+macro_rules! date_conversions {
+    ($name: ident) => {
+        impl From<$name> for Timestamp {
+            fn from(value: $name) -> Self {
+                Timestamp::from(nanos_to_duration(value.to_nanos()))
+            }
+        }
+
+        impl TryFrom<Timestamp> for $name {
+            type Error = Error;
+
+            fn try_from(value: Timestamp) -> Result<Self> {
+                Self::from_nanos(value.as_nanos())
+            }
+        }
+
+        impl From<$name> for Calendar {
+            fn from(value: $name) -> Self {
+                Calendar::from_unix_nanos(value.to_nanos())
+            }
+        }
+
+        impl TryFrom<Calendar> for $name {
+            type Error = Error;
+
+            fn try_from(value: Calendar) -> Result<Self> {
+                Self::from_nanos(value.to_unix_nanos()?)
+            }
+        }
+    };
+}
+
+time_type!(Time, "TIME", u32, U32, NANOS_PER_MILLI, NANOS_PER_MILLI);
+time_type!(LTime, "LTIME", u64, U64, 1, 1);
+time_type!(
+    TimeOfDay,
+    "TIME_OF_DAY",
+    u32,
+    U32,
+    NANOS_PER_MILLI,
+    NANOS_PER_MILLI
+);
+time_type!(Date, "DATE", u32, U32, NANOS_PER_SEC, NANOS_PER_DAY);
+time_type!(
+    DateAndTime,
+    "DATE_AND_TIME",
+    u32,
+    U32,
+    NANOS_PER_SEC,
+    NANOS_PER_SEC
+);
+time_type!(LDate, "LDATE", u64, U64, 1, NANOS_PER_DAY);
+time_type!(LDateAndTime, "LDATE_AND_TIME", u64, U64, 1, 1);
+
+duration_conversions!(Time);
+duration_conversions!(LTime);
+date_conversions!(Date);
+date_conversions!(DateAndTime);
+date_conversions!(LDate);
+date_conversions!(LDateAndTime);
+
+impl From<TimeOfDay> for Duration {
+    fn from(value: TimeOfDay) -> Self {
+        nanos_to_duration(value.to_nanos())
+    }
+}
+
+impl TryFrom<Duration> for TimeOfDay {
+    type Error = Error;
+
+    /// Convert a duration since midnight, which must be less than 24 hours.
+    fn try_from(value: Duration) -> Result<Self> {
+        if value.as_nanos() >= NANOS_PER_DAY {
+            return Err(Error::invalid_data("TIME_OF_DAY value out of range"));
+        }
+        Self::from_nanos(value.as_nanos())
+    }
+}
+
+impl TimeOfDay {
+    /// Create a value from hours, minutes, seconds and milliseconds.
+    pub fn from_hms_milli(hour: u8, minute: u8, second: u8, milli: u16) -> Result<Self> {
+        if hour > 23 || minute > 59 || second > 59 || milli > 999 {
+            return Err(Error::invalid_data("invalid time of day"));
+        }
+        Ok(Self::new(
+            ((u32::from(hour) * 60 + u32::from(minute)) * 60 + u32::from(second)) * 1000
+                + u32::from(milli),
+        ))
+    }
+
+    /// Hours (0-23).
+    pub fn hour(self) -> u8 {
+        u8::try_from(self.raw() / 3_600_000 % 24).unwrap_or_default()
+    }
+
+    /// Minutes (0-59).
+    pub fn minute(self) -> u8 {
+        u8::try_from(self.raw() / 60_000 % 60).unwrap_or_default()
+    }
+
+    /// Seconds (0-59).
+    pub fn second(self) -> u8 {
+        u8::try_from(self.raw() / 1000 % 60).unwrap_or_default()
+    }
+
+    /// Milliseconds (0-999).
+    pub fn millisecond(self) -> u16 {
+        u16::try_from(self.raw() % 1000).unwrap_or_default()
+    }
+}
+
+impl Date {
+    /// Create a date from the year, month (1-12) and day (1-31).
+    pub fn from_ymd(year: i32, month: u8, day: u8) -> Result<Self> {
+        Self::try_from(Calendar::date(year, month, day))
+    }
+}
+
+impl LDate {
+    /// Create a date from the year, month (1-12) and day (1-31).
+    pub fn from_ymd(year: i32, month: u8, day: u8) -> Result<Self> {
+        Self::try_from(Calendar::date(year, month, day))
+    }
+}
+
+/// A calendar date and time (UTC), used to convert PLC date types.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Calendar {
+    /// Year.
+    pub year: i32,
+    /// Month (1-12).
+    pub month: u8,
+    /// Day of the month (1-31).
+    pub day: u8,
+    /// Hours (0-23).
+    pub hour: u8,
+    /// Minutes (0-59).
+    pub minute: u8,
+    /// Seconds (0-59).
+    pub second: u8,
+    /// Nanoseconds (0-999999999).
+    pub nanosecond: u32,
+}
+
+impl Calendar {
+    /// Create a calendar date at midnight.
+    pub fn date(year: i32, month: u8, day: u8) -> Self {
+        Self {
+            year,
+            month,
+            day,
+            ..Self::default()
+        }
+    }
+
+    /// Set the time of the day.
+    pub fn with_time(mut self, hour: u8, minute: u8, second: u8, nanosecond: u32) -> Self {
+        self.hour = hour;
+        self.minute = minute;
+        self.second = second;
+        self.nanosecond = nanosecond;
+        self
+    }
+
+    fn from_unix_nanos(nanos: u128) -> Self {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+        let (year, month, day) = civil_from_days((nanos / NANOS_PER_DAY) as i64);
+        let time = nanos % NANOS_PER_DAY;
+        let secs = time / NANOS_PER_SEC;
+        #[allow(clippy::cast_possible_truncation)]
+        Self {
+            year,
+            month,
+            day,
+            hour: (secs / 3600) as u8,
+            minute: (secs / 60 % 60) as u8,
+            second: (secs % 60) as u8,
+            nanosecond: (time % NANOS_PER_SEC) as u32,
+        }
+    }
+
+    fn to_unix_nanos(self) -> Result<u128> {
+        if self.month == 0
+            || self.month > 12
+            || self.day == 0
+            || self.day > days_in_month(self.year, self.month)
+            || self.hour > 23
+            || self.minute > 59
+            || self.second > 59
+            || u128::from(self.nanosecond) >= NANOS_PER_SEC
+        {
+            return Err(Error::invalid_data(format!("invalid date: {:?}", self)));
+        }
+        let days = u128::try_from(days_from_civil(self.year, self.month, self.day))
+            .map_err(|_| Error::invalid_data("dates before 1970 are not supported"))?;
+        let secs =
+            (u128::from(self.hour) * 60 + u128::from(self.minute)) * 60 + u128::from(self.second);
+        Ok(days * NANOS_PER_DAY + secs * NANOS_PER_SEC + u128::from(self.nanosecond))
+    }
+}
+
+// IEC literals
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_duration(f, "T#", self.to_nanos(), NANOS_PER_MILLI)
+    }
+}
+
+impl FromStr for Time {
+    type Err = Error;
+
+    /// Parse a `TIME` literal, e.g. `T#1h2m3s4ms` or `TIME#1.5s`.
+    fn from_str(s: &str) -> Result<Self> {
+        let value = strip_literal_prefix(s, &["T#", "TIME#"]);
+        parse_duration(value)
+            .ok_or_else(|| invalid_literal("TIME", s))
+            .and_then(Self::from_nanos)
+    }
+}
+
+impl fmt::Display for LTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_duration(f, "LTIME#", self.to_nanos(), 1)
+    }
+}
+
+impl FromStr for LTime {
+    type Err = Error;
+
+    /// Parse an `LTIME` literal, e.g. `LTIME#1h2m3s4ms5us6ns`.
+    fn from_str(s: &str) -> Result<Self> {
+        let value = strip_literal_prefix(s, &["LT#", "LTIME#"]);
+        parse_duration(value)
+            .ok_or_else(|| invalid_literal("LTIME", s))
+            .and_then(Self::from_nanos)
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TOD#")?;
+        write_time_of_day(f, self.to_nanos())
+    }
+}
+
+impl FromStr for TimeOfDay {
+    type Err = Error;
+
+    /// Parse a `TIME_OF_DAY` literal, e.g. `TOD#12:00:00.5`.
+    fn from_str(s: &str) -> Result<Self> {
+        let value = strip_literal_prefix(s, &["TOD#", "TIME_OF_DAY#"]);
+        parse_time_of_day(value)
+            .ok_or_else(|| invalid_literal("TIME_OF_DAY", s))
+            .and_then(Self::from_nanos)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("D#")?;
+        write_date(f, Calendar::from(*self))
+    }
+}
+
+impl FromStr for Date {
+    type Err = Error;
+
+    /// Parse a `DATE` literal, e.g. `D#2024-01-01`.
+    fn from_str(s: &str) -> Result<Self> {
+        let value = strip_literal_prefix(s, &["D#", "DATE#"]);
+        parse_date(value)
+            .ok_or_else(|| invalid_literal("DATE", s))
+            .and_then(Self::try_from)
+    }
+}
+
+impl fmt::Display for LDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LDATE#")?;
+        write_date(f, Calendar::from(*self))
+    }
+}
+
+impl FromStr for LDate {
+    type Err = Error;
+
+    /// Parse an `LDATE` literal, e.g. `LDATE#2024-01-01`.
+    fn from_str(s: &str) -> Result<Self> {
+        let value = strip_literal_prefix(s, &["LD#", "LDATE#"]);
+        parse_date(value)
+            .ok_or_else(|| invalid_literal("LDATE", s))
+            .and_then(Self::try_from)
+    }
+}
+
+impl fmt::Display for DateAndTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DT#")?;
+        write_date_and_time(f, self.to_nanos())
+    }
+}
+
+impl FromStr for DateAndTime {
+    type Err = Error;
+
+    /// Parse a `DATE_AND_TIME` literal, e.g. `DT#2024-01-01-12:00:00`.
+    fn from_str(s: &str) -> Result<Self> {
+        let value = strip_literal_prefix(s, &["DT#", "DATE_AND_TIME#"]);
+        parse_date_and_time(value)
+            .ok_or_else(|| invalid_literal("DATE_AND_TIME", s))
+            .and_then(Self::try_from)
+    }
+}
+
+impl fmt::Display for LDateAndTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LDT#")?;
+        write_date_and_time(f, self.to_nanos())
+    }
+}
+
+impl FromStr for LDateAndTime {
+    type Err = Error;
+
+    /// Parse an `LDATE_AND_TIME` literal, e.g. `LDT#2024-01-01-12:00:00.123456789`.
+    fn from_str(s: &str) -> Result<Self> {
+        let value = strip_literal_prefix(s, &["LDT#", "LDATE_AND_TIME#"]);
+        parse_date_and_time(value)
+            .ok_or_else(|| invalid_literal("LDATE_AND_TIME", s))
+            .and_then(Self::try_from)
+    }
+}
+
+fn invalid_literal(typ: &str, s: &str) -> Error {
+    Error::invalid_data(format!("invalid {} literal: {}", typ, s))
+}
+
+fn nanos_to_duration(nanos: u128) -> Duration {
+    #[allow(clippy::cast_possible_truncation)]
+    Duration::new(
+        (nanos / NANOS_PER_SEC) as u64,
+        (nanos % NANOS_PER_SEC) as u32,
+    )
+}
+
+// Strip a case-insensitive literal prefix. Values without a prefix are accepted as well.
+fn strip_literal_prefix<'a>(s: &'a str, prefixes: &[&str]) -> &'a str {
+    let s = s.trim();
+    for prefix in prefixes {
+        if let Some((head, rest)) = s.get(..prefix.len()).zip(s.get(prefix.len()..)) {
+            if head.eq_ignore_ascii_case(prefix) {
+                return rest;
+            }
+        }
+    }
+    s
+}
+
+// Parse a duration (`1d2h3m4s5ms6us7ns`, `1.5s`) into nanoseconds.
+fn parse_duration(s: &str) -> Option<u128> {
+    let s = s.replace('_', "").to_ascii_lowercase();
+    if s.is_empty() {
+        return None;
+    }
+    let mut rest = s.as_str();
+    let mut total = 0u128;
+    while !rest.is_empty() {
+        let num_len = rest.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+        let (num, tail) = rest.split_at(num_len);
+        let unit_len = tail
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        let unit = match unit {
+            "d" => NANOS_PER_DAY,
+            "h" => 3600 * NANOS_PER_SEC,
+            "m" => 60 * NANOS_PER_SEC,
+            "s" => NANOS_PER_SEC,
+            "ms" => NANOS_PER_MILLI,
+            "us" => 1000,
+            "ns" => 1,
+            _ => return None,
+        };
+        total = total.checked_add(parse_decimal(num, unit)?)?;
+        rest = tail;
+    }
+    Some(total)
+}
+
+// Parse a decimal number, multiplied by the unit (the fraction is truncated to the unit).
+fn parse_decimal(s: &str, unit: u128) -> Option<u128> {
+    let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+    if int.is_empty() || frac.len() > 18 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let int = int.parse::<u128>().ok()?.checked_mul(unit)?;
+    if frac.is_empty() {
+        return Some(int);
+    }
+    let frac_value =
+        frac.parse::<u128>().ok()? * unit / 10u128.pow(u32::try_from(frac.len()).ok()?);
+    int.checked_add(frac_value)
+}
+
+// Parse `hh:mm:ss[.fraction]` into nanoseconds since midnight.
+fn parse_time_of_day(s: &str) -> Option<u128> {
+    let mut parts = s.splitn(3, ':');
+    let hour: u128 = parts.next()?.parse().ok()?;
+    let minute: u128 = parts.next()?.parse().ok()?;
+    let second = parts.next()?;
+    let second = if second.starts_with(|c: char| c.is_ascii_digit()) {
+        parse_decimal(second, NANOS_PER_SEC)?
+    } else {
+        return None;
+    };
+    if hour > 23 || minute > 59 || second >= 60 * NANOS_PER_SEC {
+        return None;
+    }
+    Some((hour * 60 + minute) * 60 * NANOS_PER_SEC + second)
+}
+
+// Parse `YYYY-MM-DD`.
+fn parse_date(s: &str) -> Option<Calendar> {
+    let mut parts = s.splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse().ok()?;
+    let day = parts.next()?.parse().ok()?;
+    Some(Calendar::date(year, month, day))
+}
+
+// Parse `YYYY-MM-DD-hh:mm:ss[.fraction]`.
+fn parse_date_and_time(s: &str) -> Option<Calendar> {
+    let (date, time) = s.get(..10).zip(s.get(10..))?;
+    let time = parse_time_of_day(time.strip_prefix('-')?)?;
+    let secs = time / NANOS_PER_SEC;
+    #[allow(clippy::cast_possible_truncation)]
+    Some(parse_date(date)?.with_time(
+        (secs / 3600) as u8,
+        (secs / 60 % 60) as u8,
+        (secs % 60) as u8,
+        (time % NANOS_PER_SEC) as u32,
+    ))
+}
+
+fn write_duration(
+    f: &mut fmt::Formatter<'_>,
+    prefix: &str,
+    nanos: u128,
+    resolution: u128,
+) -> fmt::Result {
+    f.write_str(prefix)?;
+    let units = [
+        ("d", NANOS_PER_DAY),
+        ("h", 3600 * NANOS_PER_SEC),
+        ("m", 60 * NANOS_PER_SEC),
+        ("s", NANOS_PER_SEC),
+        ("ms", NANOS_PER_MILLI),
+        ("us", 1000),
+        ("ns", 1),
+    ];
+    if nanos == 0 {
+        let unit = units
+            .iter()
+            .find(|(_, n)| *n == resolution)
+            .map_or("ms", |(u, _)| u);
+        return write!(f, "0{}", unit);
+    }
+    let mut rest = nanos;
+    for (unit, n) in units {
+        if n < resolution {
+            break;
+        }
+        if rest >= n {
+            write!(f, "{}{}", rest / n, unit)?;
+            rest %= n;
+        }
+    }
+    Ok(())
+}
+
+fn write_fraction(f: &mut fmt::Formatter<'_>, nanos: u128) -> fmt::Result {
+    if nanos == 0 {
+        return Ok(());
+    }
+    let digits = format!("{:09}", nanos);
+    write!(f, ".{}", digits.trim_end_matches('0'))
+}
+
+fn write_time_of_day(f: &mut fmt::Formatter<'_>, nanos: u128) -> fmt::Result {
+    let secs = nanos / NANOS_PER_SEC;
+    write!(
+        f,
+        "{:02}:{:02}:{:02}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )?;
+    write_fraction(f, nanos % NANOS_PER_SEC)
+}
+
+fn write_date(f: &mut fmt::Formatter<'_>, calendar: Calendar) -> fmt::Result {
+    write!(
+        f,
+        "{:04}-{:02}-{:02}",
+        calendar.year, calendar.month, calendar.day
+    )
+}
+
+fn write_date_and_time(f: &mut fmt::Formatter<'_>, nanos: u128) -> fmt::Result {
+    write_date(f, Calendar::from_unix_nanos(nanos))?;
+    f.write_str("-")?;
+    write_time_of_day(f, nanos % NANOS_PER_DAY)
+}
+
+fn is_leap_year(year: i32) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Days since 1970-01-01 for a proleptic Gregorian date (H. Hinnant's algorithm).
+fn days_from_civil(year: i32, month: u8, day: u8) -> i64 {
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = i64::from(month);
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// The inverse of `days_from_civil`.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn civil_from_days(days: i64) -> (i32, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year as i32, month, day)
+}
@@ -0,0 +1,276 @@
+//! Dynamic decoding of PLC values.
+//!
+//! [`decode`] converts raw symbol data into a [`Value`] using the type information uploaded from
+//! the PLC, so values of any type can be read without defining Rust structures first (e.g. in
+//! diagnostic tools). Values are printed as IEC literals.
+//!
+//! ```rust,no_run
+//! use roboplc_io_ads::{symbol, value};
+//! # fn example(device: &roboplc_io_ads::Device) -> roboplc::Result<()> {
+//! let (symbols, types) = symbol::get_symbol_info(device)?;
+//! for symbol in symbols.iter().filter(|s| s.name.starts_with("MAIN.")) {
+//!     println!("{} = {}", symbol.name, value::read(device, symbol, &types)?);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+
+use roboplc::{Error, Result};
+use zerocopy::FromBytes;
+
+use crate::enums::{EnumType, PlcEnum};
+use crate::symbol::{
+    Symbol, TypeMap, BASE_TYPE_BIT, BASE_TYPE_INT16, BASE_TYPE_INT32, BASE_TYPE_INT64,
+    BASE_TYPE_INT8, BASE_TYPE_REAL32, BASE_TYPE_REAL64, BASE_TYPE_STRING, BASE_TYPE_UINT16,
+    BASE_TYPE_UINT32, BASE_TYPE_UINT64, BASE_TYPE_UINT8, BASE_TYPE_WSTRING,
+};
+use crate::types::{Date, DateAndTime, LDate, LDateAndTime, LTime, Time, TimeOfDay};
+use crate::Device;
+
+/// A dynamically decoded PLC value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// `BOOL`, `BIT`
+    Bool(bool),
+    /// `SINT`, `INT`, `DINT`, `LINT`
+    Int(i64),
+    /// `USINT`, `UINT`, `UDINT`, `ULINT`, `BYTE`, `WORD`, `DWORD`, `LWORD`
+    UInt(u64),
+    /// `REAL`, `LREAL`
+    Real(f64),
+    /// `STRING` (decoded from Windows-1252)
+    String(String),
+    /// `WSTRING`
+    WString(String),
+    /// `TIME`
+    Time(Time),
+    /// `LTIME`
+    LTime(LTime),
+    /// `TIME_OF_DAY`
+    TimeOfDay(TimeOfDay),
+    /// `DATE`
+    Date(Date),
+    /// `DATE_AND_TIME`
+    DateAndTime(DateAndTime),
+    /// `LDATE`
+    LDate(LDate),
+    /// `LDATE_AND_TIME`
+    LDateAndTime(LDateAndTime),
+    /// An enum value.
+    Enum(PlcEnum),
+    /// Array elements, in memory order (multi-dimensional arrays are flattened).
+    Array(Vec<Value>),
+    /// Structure fields, as (name, value).
+    Struct(Vec<(String, Value)>),
+    /// Data of a type which can not be decoded (e.g. pointers, interfaces).
+    Raw(Vec<u8>),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bool(v) => f.write_str(if *v { "TRUE" } else { "FALSE" }),
+            Value::Int(v) => write!(f, "{}", v),
+            Value::UInt(v) => write!(f, "{}", v),
+            Value::Real(v) => write!(f, "{}", v),
+            Value::String(v) => write!(f, "'{}'", v),
+            Value::WString(v) => write!(f, "\"{}\"", v),
+            Value::Time(v) => write!(f, "{}", v),
+            Value::LTime(v) => write!(f, "{}", v),
+            Value::TimeOfDay(v) => write!(f, "{}", v),
+            Value::Date(v) => write!(f, "{}", v),
+            Value::DateAndTime(v) => write!(f, "{}", v),
+            Value::LDate(v) => write!(f, "{}", v),
+            Value::LDateAndTime(v) => write!(f, "{}", v),
+            Value::Enum(v) => write!(f, "{}", v),
+            Value::Array(values) => {
+                f.write_str("[")?;
+                for (i, v) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", v)?;
+                }
+                f.write_str("]")
+            }
+            Value::Struct(fields) => {
+                f.write_str("(")?;
+                for (i, (name, v)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{} := {}", name, v)?;
+                }
+                f.write_str(")")
+            }
+            Value::Raw(data) => {
+                f.write_str("16#")?;
+                for b in data {
+                    write!(f, "{:02X}", b)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Read a symbol value and decode it (see [`decode`]).
+pub fn read(device: &Device, symbol: &Symbol, types: &TypeMap) -> Result<Value> {
+    let mut buf = vec![0; symbol.size];
+    device.read_exact(symbol.ix_group, symbol.ix_offset, &mut buf)?;
+    decode_symbol(symbol, &buf, types)
+}
+
+/// Decode a symbol value (see [`decode`]).
+pub fn decode_symbol(symbol: &Symbol, data: &[u8], types: &TypeMap) -> Result<Value> {
+    decode(&symbol.typ, symbol.base_type, data, types)
+}
+
+/// Decode raw data of the given type. Elementary types are recognized by their names, other
+/// types are looked up in the type map. If the type is unknown, the data is decoded using the
+/// base type (see [`Symbol::base_type`]), or returned as [`Value::Raw`].
+pub fn decode(typ: &str, base_type: u32, data: &[u8], types: &TypeMap) -> Result<Value> {
+    if let Some(value) = decode_elementary(typ, data)? {
+        return Ok(value);
+    }
+    if let Some((dims, element_type)) = crate::query::array_info(typ, types) {
+        let count = crate::query::array_len(&dims).unwrap_or_default();
+        if count == 0 || data.len() % count != 0 {
+            return Err(Error::invalid_data(format!(
+                "invalid {} data length: {}",
+                typ,
+                data.len()
+            )));
+        }
+        let element_base_type = types.get(element_type).map_or(base_type, |t| t.base_type);
+        return data
+            .chunks(data.len() / count)
+            .map(|chunk| decode(element_type, element_base_type, chunk, types))
+            .collect::<Result<Vec<_>>>()
+            .map(Value::Array);
+    }
+    let Some(type_info) = types.get(typ) else {
+        return decode_base_type(base_type, data);
+    };
+    if let Some(enum_type) = EnumType::from_type(type_info) {
+        return enum_type.decode(data).map(Value::Enum);
+    }
+    if type_info.fields.is_empty() {
+        return decode_base_type(type_info.base_type, data);
+    }
+    let mut fields = Vec::with_capacity(type_info.fields.len());
+    for field in &type_info.fields {
+        // fields located outside of the structure (AT %M*) are skipped
+        let Some(offset) = field.offset.and_then(|o| usize::try_from(o).ok()) else {
+            continue;
+        };
+        let field_data = data
+            .get(offset..offset + field.size)
+            .ok_or_else(|| Error::invalid_data(format!("{}.{}: out of bounds", typ, field.name)))?;
+        fields.push((
+            field.name.clone(),
+            decode(&field.typ, field.base_type, field_data, types)?,
+        ));
+    }
+    Ok(Value::Struct(fields))
+}
+
+fn decode_elementary(typ: &str, data: &[u8]) -> Result<Option<Value>> {
+    let typ = typ.trim().to_ascii_uppercase();
+    if typ == "STRING" || typ.starts_with("STRING(") {
+        return Ok(Some(Value::String(decode_string(data))));
+    }
+    if typ == "WSTRING" || typ.starts_with("WSTRING(") {
+        return Ok(Some(Value::WString(decode_wstring(data))));
+    }
+    let value = match typ.as_str() {
+        "BOOL" | "BIT" => Value::Bool(read_uint(&typ, data, 1)? != 0),
+        "SINT" => Value::Int(read_int(&typ, data, 1)?),
+        "INT" => Value::Int(read_int(&typ, data, 2)?),
+        "DINT" => Value::Int(read_int(&typ, data, 4)?),
+        "LINT" => Value::Int(read_int(&typ, data, 8)?),
+        "USINT" | "BYTE" => Value::UInt(read_uint(&typ, data, 1)?),
+        "UINT" | "WORD" | "WCHAR" => Value::UInt(read_uint(&typ, data, 2)?),
+        "UDINT" | "DWORD" => Value::UInt(read_uint(&typ, data, 4)?),
+        "ULINT" | "LWORD" => Value::UInt(read_uint(&typ, data, 8)?),
+        "REAL" => Value::Real(f64::from(f32::from_bits(
+            u32::try_from(read_uint(&typ, data, 4)?).map_err(Error::invalid_data)?,
+        ))),
+        "LREAL" => Value::Real(f64::from_bits(read_uint(&typ, data, 8)?)),
+        "TIME" => Value::Time(read_zc(&typ, data)?),
+        "LTIME" => Value::LTime(read_zc(&typ, data)?),
+        "TIME_OF_DAY" | "TOD" => Value::TimeOfDay(read_zc(&typ, data)?),
+        "DATE" => Value::Date(read_zc(&typ, data)?),
+        "DATE_AND_TIME" | "DT" => Value::DateAndTime(read_zc(&typ, data)?),
+        "LDATE" => Value::LDate(read_zc(&typ, data)?),
+        "LDATE_AND_TIME" | "LDT" => Value::LDateAndTime(read_zc(&typ, data)?),
+        _ => return Ok(None),
+    };
+    Ok(Some(value))
+}
+
+fn decode_base_type(base_type: u32, data: &[u8]) -> Result<Value> {
+    let typ = match (base_type, data.len()) {
+        (BASE_TYPE_BIT, 1) => "BOOL",
+        (BASE_TYPE_INT8, 1) => "SINT",
+        (BASE_TYPE_INT16, 2) => "INT",
+        (BASE_TYPE_INT32, 4) => "DINT",
+        (BASE_TYPE_INT64, 8) => "LINT",
+        (BASE_TYPE_UINT8, 1) => "USINT",
+        (BASE_TYPE_UINT16, 2) => "UINT",
+        (BASE_TYPE_UINT32, 4) => "UDINT",
+        (BASE_TYPE_UINT64, 8) => "ULINT",
+        (BASE_TYPE_REAL32, 4) => "REAL",
+        (BASE_TYPE_REAL64, 8) => "LREAL",
+        (BASE_TYPE_STRING, _) => "STRING",
+        (BASE_TYPE_WSTRING, _) => "WSTRING",
+        _ => return Ok(Value::Raw(data.to_vec())),
+    };
+    Ok(decode_elementary(typ, data)?.unwrap_or_else(|| Value::Raw(data.to_vec())))
+}
+
+fn check_len(typ: &str, data: &[u8], len: usize) -> Result<()> {
+    if data.len() == len {
+        Ok(())
+    } else {
+        Err(Error::invalid_data(format!(
+            "invalid {} data length: {}",
+            typ,
+            data.len()
+        )))
+    }
+}
+
+fn read_uint(typ: &str, data: &[u8], len: usize) -> Result<u64> {
+    check_len(typ, data, len)?;
+    let mut buf = [0; 8];
+    buf[..len].copy_from_slice(data);
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_int(typ: &str, data: &[u8], len: usize) -> Result<i64> {
+    let shift = 64 - len * 8;
+    #[allow(clippy::cast_possible_wrap)]
+    Ok(((read_uint(typ, data, len)? << shift) as i64) >> shift)
+}
+
+fn read_zc<T: FromBytes>(typ: &str, data: &[u8]) -> Result<T> {
+    check_len(typ, data, std::mem::size_of::<T>())?;
+    T::read_from(data).ok_or_else(|| Error::invalid_data(format!("invalid {} data", typ)))
+}
+
+fn decode_string(data: &[u8]) -> String {
+    let len = data.iter().position(|b| *b == 0).unwrap_or(data.len());
+    crate::strings::decode_1252_lossy(&data[..len])
+}
+
+fn decode_wstring(data: &[u8]) -> String {
+    let chars = data
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .take_while(|c| *c != 0)
+        .collect::<Vec<_>>();
+    String::from_utf16_lossy(&chars)
+}
@@ -0,0 +1,113 @@
+//! Structures with fields bound to PLC symbols.
+//!
+//! Each field of an [`AdsVars`] structure is bound to its own PLC symbol, the trait can be
+//! derived (see [`macro@AdsVars`]). A structure type is bound to a device with [`AdsVars::bind`],
+//! the returned [`VarsImage`] keeps the symbol handles, which are created once and re-created
+//! after reconnects and online changes only. All fields are read or written with a single sum-up
+//! request.
+//!
+//! ```rust,ignore
+//! let mut image = Motor::bind(&device)?;
+//! let mut motor = Motor::default();
+//! loop {
+//!     motor.read_all(&mut image)?;
+//!     motor.enabled = motor.speed > 0.0;
+//!     motor.write_all(&mut image)?;
+//! }
+//! ```
+
+use std::io::Cursor;
+use std::marker::PhantomData;
+
+use roboplc::io::binrw::BinRead;
+use roboplc::{Error, Result};
+
+use crate::{AdsProcessImage, Device};
+
+#[allow(clippy::module_name_repetitions)]
+pub use roboplc_io_ads_derive::AdsVars;
+
+/// A structure which fields are bound to PLC symbols.
+#[allow(clippy::module_name_repetitions)]
+pub trait AdsVars: Sized {
+    /// Symbols and buffer sizes of the fields.
+    fn ads_fields() -> Vec<(&'static str, usize)>;
+    /// Decode the fields from the process image, the fields which have failed are left
+    /// untouched and their errors are added to `errors`.
+    fn ads_get(&mut self, image: &AdsProcessImage, errors: &mut Vec<String>);
+    /// Set the fields in the process image.
+    fn ads_set(&self, image: &mut AdsProcessImage) -> Result<()>;
+
+    /// Bind the structure type to the device. The symbol handles are created on the first access
+    /// and released when the returned image is dropped.
+    fn bind(device: &Device) -> Result<VarsImage<Self>> {
+        VarsImage::new(device)
+    }
+
+    /// Read all fields with a single sum-up request. If some of the symbols have failed, the
+    /// other fields are still updated and the error lists the failed ones.
+    fn read_all(&mut self, image: &mut VarsImage<Self>) -> Result<()> {
+        image.image.read()?;
+        let mut errors = Vec::new();
+        self.ads_get(&image.image, &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::failed(errors.join("; ")))
+        }
+    }
+
+    /// Write all fields with a single sum-up request. If some of the symbols have failed, the
+    /// error lists them.
+    fn write_all(&self, image: &mut VarsImage<Self>) -> Result<()> {
+        self.ads_set(&mut image.image)?;
+        image.image.write()
+    }
+}
+
+/// The process image of an [`AdsVars`] type, bound to a device.
+#[allow(clippy::module_name_repetitions)]
+pub struct VarsImage<T> {
+    image: AdsProcessImage,
+    _vars: PhantomData<fn() -> T>,
+}
+
+impl<T: AdsVars> VarsImage<T> {
+    /// Create a new image for the device (see [`AdsVars::bind`]).
+    pub fn new(device: &Device) -> Result<Self> {
+        let mut image = AdsProcessImage::new(device);
+        for (symbol, size) in T::ads_fields() {
+            image.add(symbol, size)?;
+        }
+        Ok(Self {
+            image,
+            _vars: PhantomData,
+        })
+    }
+}
+
+/// Decode a field from the process image, used by the derive macro.
+#[doc(hidden)]
+pub fn get_field<T>(image: &AdsProcessImage, index: usize, errors: &mut Vec<String>) -> Option<T>
+where
+    T: for<'a> BinRead<Args<'a> = ()>,
+{
+    let data = match image.data(index) {
+        Ok(data) => data,
+        Err(e) => {
+            errors.push(e.to_string());
+            return None;
+        }
+    };
+    match T::read_le(&mut Cursor::new(data)) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            errors.push(format!(
+                "{}: {}",
+                image.symbol(index).unwrap_or_default(),
+                e
+            ));
+            None
+        }
+    }
+}